@@ -1 +1,179 @@
-// do it for back of house like front of house
\ No newline at end of file
+// The kitchen side of the restaurant. Like front_of_house the module
+// itself is private: its children live in src/back_of_house/ and the crate
+// root re-exports the ones other code needs.
+pub mod inventory;
+pub mod kitchen;
+pub mod menu;
+pub mod scheduler;
+pub mod seasons;
+
+use std::collections::BTreeMap;
+
+use crate::serving::{Order, OrderBook, OrderError, OrderStatus};
+use crate::time::{Date, Timestamp};
+use inventory::{add_need, Inventory};
+use kitchen::{KitchenError, KitchenQueue, TicketId};
+use seasons::{Season, SeasonalProduce};
+
+// if we make an enum public, all of its variants are then public.
+// We only need the pub before the enum keyword,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Appetizer {
+    Soup,
+    Salad,
+}
+
+impl Appetizer {
+    // the name the appetizer goes by on the menu
+    pub fn name(&self) -> &'static str {
+        match self {
+            Appetizer::Soup => "Soup",
+            Appetizer::Salad => "Salad",
+        }
+    }
+}
+
+// access modifier for struct and its fields are seprated.
+// public struct
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakfast {
+    pub toast: String,      // public field
+    seasonal_fruit: String, // private field
+    season: Season,         // private field
+}
+
+// These use the house fruit; a configured table serves its breakfasts
+// through SeasonalProduce::breakfast instead.
+impl Breakfast {
+    pub fn spring(toast: &str) -> Breakfast {
+        SeasonalProduce::default().breakfast(toast, Season::Spring)
+    }
+
+    pub fn summer(toast: &str) -> Breakfast {
+        SeasonalProduce::default().breakfast(toast, Season::Summer)
+    }
+
+    pub fn autumn(toast: &str) -> Breakfast {
+        SeasonalProduce::default().breakfast(toast, Season::Autumn)
+    }
+
+    pub fn winter(toast: &str) -> Breakfast {
+        SeasonalProduce::default().breakfast(toast, Season::Winter)
+    }
+
+    pub fn for_date(toast: &str, date: Date) -> Breakfast {
+        SeasonalProduce::default().breakfast_on(toast, date)
+    }
+
+    // the field stays private, so nobody can swap the fruit,
+    // but customers can at least see what they are getting.
+    pub fn seasonal_fruit(&self) -> &str {
+        &self.seasonal_fruit
+    }
+
+    pub fn season(&self) -> Season {
+        self.season
+    }
+}
+
+// Re-cooks a ticket that went out wrong. The new ticket goes back on the
+// line like any other; nothing is delivered from here. Tickets for orders
+// that are paid or voided are not cooked again.
+pub fn fix_incorrect_order(
+    kitchen: &mut KitchenQueue,
+    orders: &OrderBook,
+    ticket: TicketId,
+    reason: &str,
+    now: Timestamp,
+) -> Result<TicketId, KitchenError> {
+    let order = orders.get(kitchen.ticket(ticket)?.order)?;
+    if order.status().is_closed() {
+        return Err(OrderError::Closed {
+            order: order.id,
+            status: order.status(),
+        }
+        .into());
+    }
+    kitchen.refire(ticket, reason, now)
+}
+
+// Puts an order that was sent to the kitchen on the station screens,
+// taking its ingredients out of stock. If anything is short the order
+// is left untouched and nothing is used up.
+pub fn cook_order(
+    kitchen: &mut KitchenQueue,
+    inventory: &mut Inventory,
+    order: &mut Order,
+    now: Timestamp,
+) -> Result<Vec<TicketId>, KitchenError> {
+    if !order.status().can_become(OrderStatus::Cooking) {
+        return Err(OrderError::IllegalTransition {
+            order: order.id,
+            from: order.status(),
+            to: OrderStatus::Cooking,
+        }
+        .into());
+    }
+    inventory.consume_order(order)?;
+    order
+        .start_cooking(now)
+        .expect("the order was checked to be cookable");
+    let lines: Vec<usize> = (0..order.items().len()).collect();
+    order.mark_fired(&lines);
+    Ok(kitchen.fire(order, now))
+}
+
+// Puts some lines of an order on the screens, one course at a time.
+// The first course fired starts the order cooking; later ones only need
+// it to have been sent and not closed. Only the ingredients for these
+// lines are used up, once each however often a line is listed. A line
+// that was already fired is refused; fix_incorrect_order remakes one.
+pub fn cook_lines(
+    kitchen: &mut KitchenQueue,
+    inventory: &mut Inventory,
+    order: &mut Order,
+    lines: &[usize],
+    now: Timestamp,
+) -> Result<Vec<TicketId>, KitchenError> {
+    let status = order.status();
+    if status == OrderStatus::Taken || status.is_closed() {
+        return Err(OrderError::IllegalTransition {
+            order: order.id,
+            from: status,
+            to: OrderStatus::Cooking,
+        }
+        .into());
+    }
+    if let Some(&line) = lines.iter().find(|&&line| line >= order.items().len()) {
+        return Err(OrderError::NoSuchLine {
+            order: order.id,
+            line,
+        }
+        .into());
+    }
+    let mut lines = lines.to_vec();
+    lines.sort_unstable();
+    lines.dedup();
+    if let Some(&line) = lines.iter().find(|&&line| order.is_fired(line)) {
+        return Err(OrderError::AlreadyFired {
+            order: order.id,
+            line,
+        }
+        .into());
+    }
+    let mut needs = BTreeMap::new();
+    for &line in &lines {
+        for (ingredient, quantity) in inventory.recipes().needs_for_line(&order.items()[line])? {
+            add_need(&mut needs, &ingredient, quantity)?;
+        }
+    }
+    inventory.consume(&needs)?;
+    if status == OrderStatus::SentToKitchen {
+        order
+            .start_cooking(now)
+            .expect("the order was checked to be cookable");
+    }
+    order.mark_fired(&lines);
+    Ok(kitchen.fire_lines(order, &lines, now))
+}
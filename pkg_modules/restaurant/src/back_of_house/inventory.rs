@@ -0,0 +1,384 @@
+// What is in the walk-in, what each dish takes out of it, and what needs
+// ordering before tomorrow.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Breakfast;
+use crate::serving::{LineItem, Modifier, Order};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Unit {
+    Grams,
+    Millilitres,
+    Pieces,
+    Slices,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Unit::Grams => "g",
+            Unit::Millilitres => "ml",
+            Unit::Pieces => "pcs",
+            Unit::Slices => "slices",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ingredient {
+    pub name: String,
+    pub unit: Unit,
+    pub on_hand: u32,
+    /// At or below this we are running low.
+    pub low_stock: u32,
+    /// How much we like to have after a delivery.
+    pub par: u32,
+}
+
+impl Ingredient {
+    pub fn new(name: &str, unit: Unit, on_hand: u32) -> Ingredient {
+        Ingredient {
+            name: String::from(name),
+            unit,
+            on_hand,
+            low_stock: 0,
+            par: on_hand,
+        }
+    }
+
+    pub fn low_at(mut self, low_stock: u32) -> Ingredient {
+        self.low_stock = low_stock;
+        self
+    }
+
+    pub fn par(mut self, par: u32) -> Ingredient {
+        self.par = par;
+        self
+    }
+
+    pub fn is_low(&self) -> bool {
+        self.on_hand <= self.low_stock
+    }
+}
+
+/// What one portion of a menu item uses, plus what each modifier adds.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recipe {
+    pub ingredients: Vec<(String, u32)>,
+    pub modifiers: BTreeMap<String, Vec<(String, u32)>>,
+}
+
+impl Recipe {
+    pub fn new() -> Recipe {
+        Recipe::default()
+    }
+
+    pub fn uses(mut self, ingredient: &str, quantity: u32) -> Recipe {
+        self.ingredients.push((String::from(ingredient), quantity));
+        self
+    }
+
+    /// Ordering the item with `modifier` also uses `quantity` of `ingredient`.
+    pub fn modifier_uses(mut self, modifier: &str, ingredient: &str, quantity: u32) -> Recipe {
+        self.modifiers
+            .entry(modifier.to_lowercase())
+            .or_default()
+            .push((String::from(ingredient), quantity));
+        self
+    }
+}
+
+/// Recipes by menu item name. Items without a recipe (a coffee, say) do not
+/// draw on the inventory.
+#[derive(Debug, Clone, Default)]
+pub struct RecipeBook {
+    recipes: BTreeMap<String, Recipe>,
+}
+
+impl RecipeBook {
+    pub fn new() -> RecipeBook {
+        RecipeBook::default()
+    }
+
+    /// Recipes for the dishes on `Menu::standard()`.
+    pub fn standard() -> RecipeBook {
+        let mut book = RecipeBook::new();
+        book.add(
+            "Soup",
+            Recipe::new()
+                .uses("soup base", 300)
+                .uses("cream", 30)
+                .modifier_uses("bread roll", "bread roll", 1),
+        );
+        book.add(
+            "Salad",
+            Recipe::new()
+                .uses("lettuce", 120)
+                .uses("tomato", 1)
+                .modifier_uses("feta", "feta", 40),
+        );
+        book.add(
+            "Breakfast",
+            Recipe::new()
+                .uses("egg", 2)
+                .uses("butter", 10)
+                .modifier_uses("rye toast", "rye bread", 2)
+                .modifier_uses("wheat toast", "wheat bread", 2)
+                .modifier_uses("gluten-free toast", "gluten-free bread", 2),
+        );
+        book.add(
+            "Burger",
+            Recipe::new()
+                .uses("beef patty", 1)
+                .uses("burger bun", 1)
+                .uses("potato", 250)
+                .modifier_uses("cheese", "cheddar", 20),
+        );
+        book.add(
+            "Fish and Chips",
+            Recipe::new().uses("cod fillet", 1).uses("potato", 300),
+        );
+        book
+    }
+
+    pub fn add(&mut self, item: &str, recipe: Recipe) {
+        self.recipes.insert(item.to_lowercase(), recipe);
+    }
+
+    /// Every recipe, by lower-case item name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Recipe)> {
+        self.recipes
+            .iter()
+            .map(|(item, recipe)| (item.as_str(), recipe))
+    }
+
+    pub fn recipe(&self, item: &str) -> Option<&Recipe> {
+        self.recipes.get(&item.to_lowercase())
+    }
+
+    /// Everything one order line takes, quantity and modifiers included.
+    pub fn needs_for_line(&self, line: &LineItem) -> Result<BTreeMap<String, u32>, InventoryError> {
+        let mut needs = BTreeMap::new();
+        if let Some(recipe) = self.recipe(&line.item) {
+            let mut uses: Vec<&(String, u32)> = recipe.ingredients.iter().collect();
+            for modifier in &line.modifiers {
+                if let Some(extra) = recipe.modifiers.get(&modifier.name.to_lowercase()) {
+                    uses.extend(extra);
+                }
+            }
+            for (ingredient, quantity) in uses {
+                let quantity = quantity
+                    .checked_mul(line.quantity as u32)
+                    .ok_or_else(|| InventoryError::TooMuch(ingredient.clone()))?;
+                add_need(&mut needs, ingredient, quantity)?;
+            }
+        }
+        Ok(needs)
+    }
+
+    pub fn needs_for_order(&self, order: &Order) -> Result<BTreeMap<String, u32>, InventoryError> {
+        let mut needs = BTreeMap::new();
+        for line in order.items() {
+            for (ingredient, quantity) in self.needs_for_line(line)? {
+                add_need(&mut needs, &ingredient, quantity)?;
+            }
+        }
+        Ok(needs)
+    }
+
+    /// A `Breakfast` names its toast directly, so "Rye" means two slices of
+    /// rye bread on top of the usual breakfast recipe.
+    pub fn needs_for_breakfast(
+        &self,
+        breakfast: &Breakfast,
+    ) -> Result<BTreeMap<String, u32>, InventoryError> {
+        let toast = format!("{} toast", breakfast.toast.to_lowercase());
+        let line = LineItem::new("Breakfast", 0).with_modifier(Modifier::new(&toast));
+        let mut needs = self.needs_for_line(&line)?;
+        if !self
+            .recipe("Breakfast")
+            .is_some_and(|r| r.modifiers.contains_key(&toast))
+        {
+            let bread = format!("{} bread", breakfast.toast.to_lowercase());
+            add_need(&mut needs, &bread, 2)?;
+        }
+        Ok(needs)
+    }
+}
+
+/// Adds `quantity` of `ingredient` to what is needed so far.
+pub(crate) fn add_need(
+    needs: &mut BTreeMap<String, u32>,
+    ingredient: &str,
+    quantity: u32,
+) -> Result<(), InventoryError> {
+    let needed = needs.entry(ingredient.to_string()).or_insert(0);
+    *needed = needed
+        .checked_add(quantity)
+        .ok_or_else(|| InventoryError::TooMuch(ingredient.to_string()))?;
+    Ok(())
+}
+
+/// Not enough of one ingredient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortage {
+    pub ingredient: String,
+    pub needed: u32,
+    pub on_hand: u32,
+}
+
+/// More of an ingredient to order to get back to par.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorder {
+    pub ingredient: String,
+    pub quantity: u32,
+    pub unit: Unit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InventoryError {
+    UnknownIngredient(String),
+    /// The order cannot be cooked; nothing was taken out of stock.
+    CannotFulfil(Vec<Shortage>),
+    /// More of an ingredient than can be counted, needed or on hand.
+    TooMuch(String),
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InventoryError::UnknownIngredient(name) => {
+                write!(f, "{} is not stocked", name)
+            }
+            InventoryError::CannotFulfil(shortages) => {
+                write!(f, "not enough")?;
+                for (index, shortage) in shortages.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(
+                        f,
+                        "{}{} (need {}, have {})",
+                        separator, shortage.ingredient, shortage.needed, shortage.on_hand
+                    )?;
+                }
+                Ok(())
+            }
+            InventoryError::TooMuch(name) => write!(f, "too much {} to count", name),
+        }
+    }
+}
+
+impl Error for InventoryError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    ingredients: BTreeMap<String, Ingredient>,
+    recipes: RecipeBook,
+}
+
+impl Inventory {
+    pub fn new(recipes: RecipeBook) -> Inventory {
+        Inventory {
+            ingredients: BTreeMap::new(),
+            recipes,
+        }
+    }
+
+    pub fn recipes(&self) -> &RecipeBook {
+        &self.recipes
+    }
+
+    pub fn stock(&mut self, ingredient: Ingredient) {
+        self.ingredients.insert(ingredient.name.clone(), ingredient);
+    }
+
+    pub fn ingredient(&self, name: &str) -> Option<&Ingredient> {
+        self.ingredients.get(name)
+    }
+
+    pub fn ingredients(&self) -> impl Iterator<Item = &Ingredient> {
+        self.ingredients.values()
+    }
+
+    /// A delivery came in.
+    pub fn receive(&mut self, name: &str, quantity: u32) -> Result<(), InventoryError> {
+        let ingredient = self
+            .ingredients
+            .get_mut(name)
+            .ok_or_else(|| InventoryError::UnknownIngredient(String::from(name)))?;
+        ingredient.on_hand = ingredient
+            .on_hand
+            .checked_add(quantity)
+            .ok_or_else(|| InventoryError::TooMuch(String::from(name)))?;
+        Ok(())
+    }
+
+    /// Everything that would run short if `needs` were taken out.
+    pub fn shortages(&self, needs: &BTreeMap<String, u32>) -> Vec<Shortage> {
+        needs
+            .iter()
+            .filter_map(|(name, &needed)| {
+                let on_hand = self.ingredients.get(name).map_or(0, |i| i.on_hand);
+                (on_hand < needed).then(|| Shortage {
+                    ingredient: name.clone(),
+                    needed,
+                    on_hand,
+                })
+            })
+            .collect()
+    }
+
+    pub fn can_fulfil(&self, order: &Order) -> Result<(), InventoryError> {
+        let shortages = self.shortages(&self.recipes.needs_for_order(order)?);
+        if shortages.is_empty() {
+            Ok(())
+        } else {
+            Err(InventoryError::CannotFulfil(shortages))
+        }
+    }
+
+    /// Takes what the order needs out of stock, or nothing at all if any
+    /// ingredient is short.
+    pub fn consume_order(&mut self, order: &Order) -> Result<(), InventoryError> {
+        let needs = self.recipes.needs_for_order(order)?;
+        self.consume(&needs)
+    }
+
+    pub fn consume(&mut self, needs: &BTreeMap<String, u32>) -> Result<(), InventoryError> {
+        let shortages = self.shortages(needs);
+        if !shortages.is_empty() {
+            return Err(InventoryError::CannotFulfil(shortages));
+        }
+        for (name, quantity) in needs {
+            if let Some(ingredient) = self.ingredients.get_mut(name) {
+                ingredient.on_hand -= quantity;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn low_stock(&self) -> Vec<&Ingredient> {
+        self.ingredients.values().filter(|i| i.is_low()).collect()
+    }
+
+    /// What to order for every ingredient that is running low.
+    pub fn reorder_suggestions(&self) -> Vec<Reorder> {
+        self.low_stock()
+            .into_iter()
+            .filter(|i| i.par > i.on_hand)
+            .map(|i| Reorder {
+                ingredient: i.name.clone(),
+                quantity: i.par - i.on_hand,
+                unit: i.unit,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,378 @@
+// The kitchen display: every order that is cooked becomes one ticket per
+// station, and cooks bump tickets off their screen when the food is up.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub use super::scheduler::{Course, CourseScheduler, Firing, FiringStatus, ScheduleError};
+pub use super::{cook_lines, cook_order, fix_incorrect_order};
+
+use super::inventory::InventoryError;
+use crate::hosting::TableNumber;
+use crate::menu::{Category, Menu};
+use crate::serving::{LineItem, Order, OrderError, OrderId};
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Station {
+    Grill,
+    Fry,
+    Salad,
+    Dessert,
+}
+
+impl fmt::Display for Station {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Station::Grill => "grill",
+            Station::Fry => "fry",
+            Station::Salad => "salad",
+            Station::Dessert => "dessert",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Decides which station cooks which menu item: the item's own route if it
+/// has one, otherwise the route for its category on the menu the router
+/// knows. Items nobody told us about go to the fallback station.
+#[derive(Debug, Clone)]
+pub struct StationRouter {
+    routes: BTreeMap<String, Station>,
+    categories: BTreeMap<Category, Station>,
+    menu: BTreeMap<String, Category>,
+    fallback: Station,
+}
+
+impl Default for StationRouter {
+    // For the standard menu. Starters are made at the salad station, apart
+    // from the soup, which is kept hot on the grill; breakfast and mains
+    // come off the grill, apart from what is fried; and the dessert station
+    // makes the coffee and pours the juice.
+    fn default() -> StationRouter {
+        StationRouter::new(Station::Grill)
+            .menu(&Menu::standard())
+            .route_category(Category::Appetizer, Station::Salad)
+            .route_category(Category::Breakfast, Station::Grill)
+            .route_category(Category::Main, Station::Grill)
+            .route_category(Category::Drink, Station::Dessert)
+            .route("Soup", Station::Grill)
+            .route("Fish and Chips", Station::Fry)
+    }
+}
+
+impl StationRouter {
+    pub fn new(fallback: Station) -> StationRouter {
+        StationRouter {
+            routes: BTreeMap::new(),
+            categories: BTreeMap::new(),
+            menu: BTreeMap::new(),
+            fallback,
+        }
+    }
+
+    pub fn route(mut self, item: &str, station: Station) -> StationRouter {
+        self.routes.insert(item.to_lowercase(), station);
+        self
+    }
+
+    /// Where items in `category` go unless they have a route of their own.
+    pub fn route_category(mut self, category: Category, station: Station) -> StationRouter {
+        self.categories.insert(category, station);
+        self
+    }
+
+    /// Learns the category of everything on `menu`.
+    pub fn menu(mut self, menu: &Menu) -> StationRouter {
+        for item in menu.items() {
+            self.menu.insert(item.name.to_lowercase(), item.category);
+        }
+        self
+    }
+
+    pub fn station_for(&self, item: &str) -> Station {
+        let item = item.to_lowercase();
+        self.routes
+            .get(&item)
+            .or_else(|| {
+                let category = self.menu.get(&item)?;
+                self.categories.get(category)
+            })
+            .copied()
+            .unwrap_or(self.fallback)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TicketId(pub u32);
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ticket {}", self.0)
+    }
+}
+
+/// Why a ticket had to be cooked again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refire {
+    pub original: TicketId,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub order: OrderId,
+    pub table: TableNumber,
+    pub station: Station,
+    pub items: Vec<LineItem>,
+    pub fired_at: Timestamp,
+    pub bumped_at: Option<Timestamp>,
+    pub refire: Option<Refire>,
+}
+
+impl Ticket {
+    pub fn is_bumped(&self) -> bool {
+        self.bumped_at.is_some()
+    }
+
+    /// How long the ticket has been (or was) on the screen.
+    pub fn elapsed(&self, now: Timestamp) -> Duration {
+        self.bumped_at
+            .unwrap_or(now)
+            .saturating_duration_since(self.fired_at)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KitchenError {
+    UnknownTicket(TicketId),
+    AlreadyBumped(TicketId),
+    /// Only bumped tickets can be recalled.
+    NotBumped(TicketId),
+    NothingToRecall(Station),
+    Order(OrderError),
+    Inventory(InventoryError),
+}
+
+impl fmt::Display for KitchenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KitchenError::UnknownTicket(ticket) => write!(f, "{} does not exist", ticket),
+            KitchenError::AlreadyBumped(ticket) => write!(f, "{} was already bumped", ticket),
+            KitchenError::NotBumped(ticket) => write!(f, "{} is still on the screen", ticket),
+            KitchenError::NothingToRecall(station) => {
+                write!(f, "nothing was bumped at the {} station", station)
+            }
+            KitchenError::Order(err) => write!(f, "{}", err),
+            KitchenError::Inventory(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for KitchenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KitchenError::Order(err) => Some(err),
+            KitchenError::Inventory(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OrderError> for KitchenError {
+    fn from(err: OrderError) -> KitchenError {
+        KitchenError::Order(err)
+    }
+}
+
+impl From<InventoryError> for KitchenError {
+    fn from(err: InventoryError) -> KitchenError {
+        KitchenError::Inventory(err)
+    }
+}
+
+/// Every ticket fired during the shift, open or bumped.
+#[derive(Debug, Clone, Default)]
+pub struct KitchenQueue {
+    router: StationRouter,
+    tickets: Vec<Ticket>,
+    next_id: u32,
+}
+
+impl KitchenQueue {
+    pub fn new(router: StationRouter) -> KitchenQueue {
+        KitchenQueue {
+            router,
+            tickets: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn router(&self) -> &StationRouter {
+        &self.router
+    }
+
+    /// Splits `order` into one ticket per station.
+    pub fn fire(&mut self, order: &Order, now: Timestamp) -> Vec<TicketId> {
+        let lines: Vec<usize> = (0..order.items().len()).collect();
+        self.fire_lines(order, &lines, now)
+    }
+
+    /// Like `fire`, but only for some of the order's lines, e.g. one course.
+    /// Lines the order does not have are left out.
+    pub fn fire_lines(&mut self, order: &Order, lines: &[usize], now: Timestamp) -> Vec<TicketId> {
+        let mut by_station: BTreeMap<Station, Vec<LineItem>> = BTreeMap::new();
+        for (_, item) in order
+            .items()
+            .iter()
+            .enumerate()
+            .filter(|(line, _)| lines.contains(line))
+        {
+            by_station
+                .entry(self.router.station_for(&item.item))
+                .or_default()
+                .push(item.clone());
+        }
+
+        by_station
+            .into_iter()
+            .map(|(station, items)| self.push(order.id, order.table, station, items, None, now))
+            .collect()
+    }
+
+    /// Cooks the same food again, for example because it came out wrong.
+    /// The new ticket remembers which one it replaces and why.
+    pub fn refire(
+        &mut self,
+        original: TicketId,
+        reason: &str,
+        now: Timestamp,
+    ) -> Result<TicketId, KitchenError> {
+        let ticket = self.ticket(original)?.clone();
+        let refire = Refire {
+            original,
+            reason: String::from(reason),
+        };
+        Ok(self.push(
+            ticket.order,
+            ticket.table,
+            ticket.station,
+            ticket.items,
+            Some(refire),
+            now,
+        ))
+    }
+
+    /// Takes a finished ticket off the screen.
+    pub fn bump(&mut self, id: TicketId, now: Timestamp) -> Result<&Ticket, KitchenError> {
+        let ticket = self.ticket_mut(id)?;
+        if ticket.is_bumped() {
+            return Err(KitchenError::AlreadyBumped(id));
+        }
+        ticket.bumped_at = Some(now);
+        Ok(ticket)
+    }
+
+    /// Puts a bumped ticket back on the screen, timer and all.
+    pub fn recall(&mut self, id: TicketId) -> Result<&Ticket, KitchenError> {
+        let ticket = self.ticket_mut(id)?;
+        if !ticket.is_bumped() {
+            return Err(KitchenError::NotBumped(id));
+        }
+        ticket.bumped_at = None;
+        Ok(ticket)
+    }
+
+    /// Recalls whatever the station bumped most recently, which is what the
+    /// recall button on a kitchen screen does.
+    pub fn recall_last(&mut self, station: Station) -> Result<&Ticket, KitchenError> {
+        let last = self
+            .tickets
+            .iter()
+            .filter(|t| t.station == station)
+            .filter_map(|t| t.bumped_at.map(|at| (at, t.id)))
+            .max()
+            .map(|(_, id)| id)
+            .ok_or(KitchenError::NothingToRecall(station))?;
+        self.recall(last)
+    }
+
+    pub fn ticket(&self, id: TicketId) -> Result<&Ticket, KitchenError> {
+        self.tickets
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or(KitchenError::UnknownTicket(id))
+    }
+
+    pub fn tickets(&self) -> impl Iterator<Item = &Ticket> {
+        self.tickets.iter()
+    }
+
+    /// What a station's screen shows: open tickets, oldest first.
+    pub fn queue(&self, station: Station) -> Vec<&Ticket> {
+        let mut open: Vec<&Ticket> = self
+            .tickets
+            .iter()
+            .filter(|t| t.station == station && !t.is_bumped())
+            .collect();
+        open.sort_by_key(|t| (t.fired_at, t.id));
+        open
+    }
+
+    pub fn tickets_for(&self, order: OrderId) -> impl Iterator<Item = &Ticket> {
+        self.tickets.iter().filter(move |t| t.order == order)
+    }
+
+    /// True once every ticket of the order has been bumped.
+    pub fn is_order_done(&self, order: OrderId) -> bool {
+        let mut tickets = self.tickets_for(order).peekable();
+        tickets.peek().is_some() && tickets.all(Ticket::is_bumped)
+    }
+
+    /// Open tickets that have been on the screen longer than `limit`.
+    pub fn late(&self, now: Timestamp, limit: Duration) -> Vec<&Ticket> {
+        self.tickets
+            .iter()
+            .filter(|t| !t.is_bumped() && t.elapsed(now) > limit)
+            .collect()
+    }
+
+    fn push(
+        &mut self,
+        order: OrderId,
+        table: TableNumber,
+        station: Station,
+        items: Vec<LineItem>,
+        refire: Option<Refire>,
+        now: Timestamp,
+    ) -> TicketId {
+        self.next_id += 1;
+        let id = TicketId(self.next_id);
+        self.tickets.push(Ticket {
+            id,
+            order,
+            table,
+            station,
+            items,
+            fired_at: now,
+            bumped_at: None,
+            refire,
+        });
+        id
+    }
+
+    fn ticket_mut(&mut self, id: TicketId) -> Result<&mut Ticket, KitchenError> {
+        self.tickets
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or(KitchenError::UnknownTicket(id))
+    }
+}
@@ -0,0 +1,397 @@
+// The menu catalogue: what we sell, for how much, when, and whether the
+// kitchen still has any of it left.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub use super::seasons::{Season, SeasonalProduce};
+pub use super::{Appetizer, Breakfast};
+
+use crate::serving::{Cents, LineItem, Modifier};
+use crate::time::{TimeOfDay, Timestamp, Window};
+
+/// Breakfast is served from opening until 11:00.
+pub const BREAKFAST_HOURS: Window = Window::new(TimeOfDay::MIDNIGHT, TimeOfDay::new(11, 0));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Category {
+    Appetizer,
+    Main,
+    Breakfast,
+    Drink,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Appetizer => "appetizers",
+            Category::Main => "mains",
+            Category::Breakfast => "breakfasts",
+            Category::Drink => "drinks",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Egg,
+    Nuts,
+    Peanuts,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DietaryTag {
+    Vegetarian,
+    Vegan,
+    GlutenFree,
+    DairyFree,
+    Spicy,
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Allergen::Gluten => "gluten",
+            Allergen::Dairy => "dairy",
+            Allergen::Egg => "egg",
+            Allergen::Nuts => "nuts",
+            Allergen::Peanuts => "peanuts",
+            Allergen::Soy => "soy",
+            Allergen::Fish => "fish",
+            Allergen::Shellfish => "shellfish",
+            Allergen::Sesame => "sesame",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for DietaryTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DietaryTag::Vegetarian => "vegetarian",
+            DietaryTag::Vegan => "vegan",
+            DietaryTag::GlutenFree => "gluten-free",
+            DietaryTag::DairyFree => "dairy-free",
+            DietaryTag::Spicy => "spicy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+    pub name: String,
+    pub category: Category,
+    pub price: Cents,
+    pub description: String,
+    pub allergens: Vec<Allergen>,
+    pub dietary: Vec<DietaryTag>,
+    /// The modifiers a guest may ask for, with what they cost.
+    pub modifiers: Vec<Modifier>,
+    /// When the item can be ordered. `None` means all day.
+    pub available: Option<Window>,
+    /// Portions left, if anyone is counting.
+    pub stock: Option<u32>,
+    /// Taken off for the rest of the shift.
+    pub eighty_sixed: bool,
+    /// How long the kitchen takes to make one, if anyone has timed it.
+    pub prep_time: Option<Duration>,
+}
+
+impl MenuItem {
+    pub fn new(name: &str, category: Category, price: Cents) -> MenuItem {
+        MenuItem {
+            name: String::from(name),
+            category,
+            price,
+            description: String::new(),
+            allergens: Vec::new(),
+            dietary: Vec::new(),
+            modifiers: Vec::new(),
+            available: if category == Category::Breakfast {
+                Some(BREAKFAST_HOURS)
+            } else {
+                None
+            },
+            stock: None,
+            eighty_sixed: false,
+            prep_time: None,
+        }
+    }
+
+    pub fn describe(mut self, description: &str) -> MenuItem {
+        self.description = String::from(description);
+        self
+    }
+
+    pub fn contains(mut self, allergen: Allergen) -> MenuItem {
+        self.allergens.push(allergen);
+        self
+    }
+
+    pub fn tagged(mut self, tag: DietaryTag) -> MenuItem {
+        self.dietary.push(tag);
+        self
+    }
+
+    pub fn with_modifier(mut self, name: &str, price_delta: i64) -> MenuItem {
+        self.modifiers.push(Modifier::priced(name, price_delta));
+        self
+    }
+
+    pub fn available(mut self, window: Window) -> MenuItem {
+        self.available = Some(window);
+        self
+    }
+
+    pub fn prep_time(mut self, prep_time: Duration) -> MenuItem {
+        self.prep_time = Some(prep_time);
+        self
+    }
+
+    pub fn is_available_at(&self, at: Timestamp) -> bool {
+        !self.eighty_sixed
+            && self.stock != Some(0)
+            && self
+                .available
+                .is_none_or(|window| window.contains(at.time_of_day()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MenuError {
+    UnknownItem(String),
+    UnknownModifier { item: String, modifier: String },
+    EightySixed(String),
+    OutOfStock { item: String, left: u32 },
+    NotServedNow { item: String, window: Window },
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuError::UnknownItem(item) => write!(f, "{} is not on the menu", item),
+            MenuError::UnknownModifier { item, modifier } => {
+                write!(f, "{} cannot be ordered with {}", item, modifier)
+            }
+            MenuError::EightySixed(item) => write!(f, "{} is 86'd", item),
+            MenuError::OutOfStock { item, left } => {
+                write!(f, "only {} portions of {} are left", left, item)
+            }
+            MenuError::NotServedNow { item, window } => {
+                write!(f, "{} is only served {}", item, window)
+            }
+        }
+    }
+}
+
+impl Error for MenuError {}
+
+/// Every item we sell, looked up by name regardless of case.
+#[derive(Debug, Clone, Default)]
+pub struct Menu {
+    items: BTreeMap<String, MenuItem>,
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu::default()
+    }
+
+    /// The house menu the crate ships with.
+    pub fn standard() -> Menu {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let mut menu = Menu::new();
+        menu.add(
+            MenuItem::new(Appetizer::Soup.name(), Category::Appetizer, 650)
+                .describe("Soup of the day")
+                .contains(Allergen::Dairy)
+                .tagged(DietaryTag::Vegetarian)
+                .with_modifier("bread roll", 150)
+                .prep_time(minutes(4)),
+        );
+        menu.add(
+            MenuItem::new(Appetizer::Salad.name(), Category::Appetizer, 700)
+                .describe("Garden salad with house dressing")
+                .tagged(DietaryTag::Vegan)
+                .tagged(DietaryTag::GlutenFree)
+                .with_modifier("feta", 100)
+                .with_modifier("no dressing", 0)
+                .prep_time(minutes(5)),
+        );
+        menu.add(
+            MenuItem::new("Breakfast", Category::Breakfast, 1150)
+                .describe("Toast, eggs and seasonal fruit")
+                .contains(Allergen::Gluten)
+                .contains(Allergen::Egg)
+                .tagged(DietaryTag::Vegetarian)
+                .with_modifier("rye toast", 0)
+                .with_modifier("wheat toast", 0)
+                .with_modifier("gluten-free toast", 200)
+                .prep_time(minutes(10)),
+        );
+        menu.add(
+            MenuItem::new("Burger", Category::Main, 1500)
+                .describe("Beef burger with fries")
+                .contains(Allergen::Gluten)
+                .contains(Allergen::Sesame)
+                .with_modifier("cheese", 150)
+                .with_modifier("no bun", -100)
+                .prep_time(minutes(12)),
+        );
+        menu.add(
+            MenuItem::new("Fish and Chips", Category::Main, 1650)
+                .describe("Beer-battered cod with chips")
+                .contains(Allergen::Fish)
+                .contains(Allergen::Gluten)
+                .prep_time(minutes(9)),
+        );
+        menu.add(
+            MenuItem::new("Coffee", Category::Drink, 300)
+                .with_modifier("oat milk", 50)
+                .prep_time(minutes(2)),
+        );
+        menu.add(
+            MenuItem::new("Orange Juice", Category::Drink, 400)
+                .tagged(DietaryTag::Vegan)
+                .tagged(DietaryTag::GlutenFree)
+                .prep_time(minutes(1)),
+        );
+        menu
+    }
+
+    /// Adds or replaces an item.
+    pub fn add(&mut self, item: MenuItem) {
+        self.items.insert(item.name.to_lowercase(), item);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<MenuItem> {
+        self.items.remove(&name.to_lowercase())
+    }
+
+    pub fn item(&self, name: &str) -> Result<&MenuItem, MenuError> {
+        self.items
+            .get(&name.to_lowercase())
+            .ok_or_else(|| MenuError::UnknownItem(String::from(name)))
+    }
+
+    pub fn appetizer(&self, appetizer: Appetizer) -> Result<&MenuItem, MenuError> {
+        self.item(appetizer.name())
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &MenuItem> {
+        self.items.values()
+    }
+
+    pub fn in_category(&self, category: Category) -> impl Iterator<Item = &MenuItem> {
+        self.items.values().filter(move |i| i.category == category)
+    }
+
+    /// What a guest could order at `at`.
+    pub fn available_at(&self, at: Timestamp) -> impl Iterator<Item = &MenuItem> {
+        self.items.values().filter(move |i| i.is_available_at(at))
+    }
+
+    /// Items that are safe for someone avoiding all of `allergens`.
+    pub fn without<'a>(&'a self, allergens: &'a [Allergen]) -> impl Iterator<Item = &'a MenuItem> {
+        self.items
+            .values()
+            .filter(move |i| !i.allergens.iter().any(|a| allergens.contains(a)))
+    }
+
+    /// Takes an item off for the rest of the shift.
+    pub fn eighty_six(&mut self, name: &str) -> Result<(), MenuError> {
+        self.item_mut(name)?.eighty_sixed = true;
+        Ok(())
+    }
+
+    /// Puts an 86'd item back on, optionally with a fresh count.
+    pub fn restore(&mut self, name: &str, stock: Option<u32>) -> Result<(), MenuError> {
+        let item = self.item_mut(name)?;
+        item.eighty_sixed = false;
+        item.stock = stock;
+        Ok(())
+    }
+
+    pub fn set_stock(&mut self, name: &str, stock: Option<u32>) -> Result<(), MenuError> {
+        self.item_mut(name)?.stock = stock;
+        Ok(())
+    }
+
+    /// Checks that `quantity` of an item can be ordered at `at` with the
+    /// named modifiers, and if so prices it as an order line and takes the
+    /// portions out of stock. The item is 86'd when the last portion goes.
+    pub fn order(
+        &mut self,
+        name: &str,
+        quantity: u16,
+        modifiers: &[&str],
+        at: Timestamp,
+    ) -> Result<LineItem, MenuError> {
+        let item = self.item_mut(name)?;
+        if item.eighty_sixed {
+            return Err(MenuError::EightySixed(item.name.clone()));
+        }
+        if let Some(window) = item.available {
+            if !window.contains(at.time_of_day()) {
+                return Err(MenuError::NotServedNow {
+                    item: item.name.clone(),
+                    window,
+                });
+            }
+        }
+        if let Some(left) = item.stock {
+            if left < quantity as u32 {
+                return Err(MenuError::OutOfStock {
+                    item: item.name.clone(),
+                    left,
+                });
+            }
+        }
+
+        let mut line = LineItem::new(&item.name, item.price).quantity(quantity);
+        for wanted in modifiers {
+            let modifier = item
+                .modifiers
+                .iter()
+                .find(|m| m.name.eq_ignore_ascii_case(wanted))
+                .ok_or_else(|| MenuError::UnknownModifier {
+                    item: item.name.clone(),
+                    modifier: String::from(*wanted),
+                })?;
+            line = line.with_modifier(modifier.clone());
+        }
+
+        if let Some(left) = item.stock.as_mut() {
+            *left -= quantity as u32;
+            if *left == 0 {
+                item.eighty_sixed = true;
+            }
+        }
+        Ok(line)
+    }
+
+    fn item_mut(&mut self, name: &str) -> Result<&mut MenuItem, MenuError> {
+        self.items
+            .get_mut(&name.to_lowercase())
+            .ok_or_else(|| MenuError::UnknownItem(String::from(name)))
+    }
+}
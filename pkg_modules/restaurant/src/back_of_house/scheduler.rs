@@ -0,0 +1,377 @@
+// Timing for the kitchen. `cook_order` puts a whole order on the screens the
+// moment it is sent, so a table's soup and its burgers come up whenever each
+// happens to be done. The scheduler instead works out when every line has to
+// be fired for its course to land at the pass all together, given how long
+// each item takes and how many things a station can have going at once, and
+// holds each course back until the one before it has been served.
+//
+// It only plans. Whoever drives it asks what is due and fires that through
+// `Shift::fire_course` (or lets `fire_due` do both), then says when a course
+// has gone out to the table.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use super::kitchen::{Station, StationRouter, TicketId};
+use super::menu::{Category, Menu};
+use crate::hosting::TableNumber;
+use crate::serving::{Order, OrderId};
+use crate::shift::{Shift, ShiftError};
+use crate::time::{Clock, Timestamp};
+
+const DEFAULT_PREP: Duration = Duration::from_secs(10 * 60);
+const DEFAULT_COURSE_GAP: Duration = Duration::from_secs(15 * 60);
+
+/// The order food goes out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Course {
+    Starters,
+    Mains,
+}
+
+impl Course {
+    /// Drinks go out with the starters and breakfasts with the mains.
+    pub fn of(category: Category) -> Course {
+        match category {
+            Category::Appetizer | Category::Drink => Course::Starters,
+            Category::Main | Category::Breakfast => Course::Mains,
+        }
+    }
+}
+
+impl fmt::Display for Course {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Course::Starters => "starters",
+            Course::Mains => "mains",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FiringStatus {
+    /// Waiting for the course before it to be served.
+    Held,
+    /// Has a time to go on the screen.
+    Scheduled,
+    Fired,
+    Served,
+}
+
+/// One line of an order and when it goes on the screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Firing {
+    pub order: OrderId,
+    pub table: TableNumber,
+    pub line: usize,
+    pub item: String,
+    pub course: Course,
+    pub station: Station,
+    pub prep: Duration,
+    pub status: FiringStatus,
+    /// When it has to go on the screen, once its course is scheduled.
+    pub fire_at: Option<Timestamp>,
+    /// When the whole course will be up.
+    pub lands_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScheduleError {
+    AlreadyScheduled(OrderId),
+    NotScheduled(OrderId),
+    EmptyOrder(OrderId),
+    /// A course can only be served once all of it has been fired.
+    NotFired {
+        order: OrderId,
+        course: Course,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::AlreadyScheduled(order) => write!(f, "{} is already scheduled", order),
+            ScheduleError::NotScheduled(order) => write!(f, "{} is not scheduled", order),
+            ScheduleError::EmptyOrder(order) => write!(f, "{} has nothing to cook", order),
+            ScheduleError::NotFired { order, course } => {
+                write!(f, "the {} for {} have not all been fired", course, order)
+            }
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+/// Plans when each course of each order is fired.
+#[derive(Debug, Clone)]
+pub struct CourseScheduler {
+    router: StationRouter,
+    categories: BTreeMap<String, Category>,
+    prep_times: BTreeMap<String, Duration>,
+    default_prep: Duration,
+    cooks: BTreeMap<Station, usize>,
+    course_gap: Duration,
+    // when each cook at a station is next free, in seconds
+    free_at: BTreeMap<Station, Vec<u64>>,
+    firings: Vec<Firing>,
+}
+
+impl CourseScheduler {
+    /// Courses and prep times come from `menu`, stations from `router`.
+    /// Items not on the menu are mains and take the default prep time.
+    pub fn new(menu: &Menu, router: StationRouter) -> CourseScheduler {
+        CourseScheduler {
+            router,
+            categories: menu
+                .items()
+                .map(|item| (item.name.to_lowercase(), item.category))
+                .collect(),
+            prep_times: menu
+                .items()
+                .filter_map(|item| Some((item.name.to_lowercase(), item.prep_time?)))
+                .collect(),
+            default_prep: DEFAULT_PREP,
+            cooks: BTreeMap::new(),
+            course_gap: DEFAULT_COURSE_GAP,
+            free_at: BTreeMap::new(),
+            firings: Vec::new(),
+        }
+    }
+
+    pub fn prep_time(mut self, item: &str, prep: Duration) -> CourseScheduler {
+        self.prep_times.insert(item.to_lowercase(), prep);
+        self
+    }
+
+    pub fn default_prep(mut self, prep: Duration) -> CourseScheduler {
+        self.default_prep = prep;
+        self
+    }
+
+    /// How many lines `station` can cook at once. Stations not given are
+    /// one cook, and none is taken as one.
+    pub fn cooks(mut self, station: Station, cooks: usize) -> CourseScheduler {
+        self.cooks.insert(station, cooks.max(1));
+        self
+    }
+
+    /// How long guests get with one course before the next lands.
+    pub fn course_gap(mut self, gap: Duration) -> CourseScheduler {
+        self.course_gap = gap;
+        self
+    }
+
+    pub fn firings(&self) -> &[Firing] {
+        &self.firings
+    }
+
+    pub fn firings_for(&self, order: OrderId) -> impl Iterator<Item = &Firing> {
+        self.firings.iter().filter(move |f| f.order == order)
+    }
+
+    /// Plans an order that was just sent to the kitchen. Its first course
+    /// is timed to land as soon as the kitchen can manage; later courses are
+    /// held. Returns when the first course lands.
+    pub fn schedule(&mut self, order: &Order, now: Timestamp) -> Result<Timestamp, ScheduleError> {
+        if self.firings.iter().any(|f| f.order == order.id) {
+            return Err(ScheduleError::AlreadyScheduled(order.id));
+        }
+        if order.items().is_empty() {
+            return Err(ScheduleError::EmptyOrder(order.id));
+        }
+        for (line, item) in order.items().iter().enumerate() {
+            let name = item.item.to_lowercase();
+            let firing = Firing {
+                order: order.id,
+                table: order.table,
+                line,
+                item: item.item.clone(),
+                course: self
+                    .categories
+                    .get(&name)
+                    .map_or(Course::Mains, |category| Course::of(*category)),
+                station: self.router.station_for(&item.item),
+                prep: self
+                    .prep_times
+                    .get(&name)
+                    .copied()
+                    .unwrap_or(self.default_prep),
+                status: FiringStatus::Held,
+                fire_at: None,
+                lands_at: None,
+            };
+            self.firings.push(firing);
+        }
+        let first = self
+            .firings_for(order.id)
+            .map(|f| f.course)
+            .min()
+            .expect("the order has items");
+        Ok(self.plan(order.id, first, now, now))
+    }
+
+    /// Records that `course` went out to the table and times the next one
+    /// to land a course gap from now, or later if the kitchen is busy.
+    /// Returns when the next course lands, if there is one.
+    pub fn served(
+        &mut self,
+        order: OrderId,
+        course: Course,
+        now: Timestamp,
+    ) -> Result<Option<Timestamp>, ScheduleError> {
+        if self.firings_for(order).next().is_none() {
+            return Err(ScheduleError::NotScheduled(order));
+        }
+        let unfired = self.firings_for(order).any(|f| {
+            f.course == course && matches!(f.status, FiringStatus::Held | FiringStatus::Scheduled)
+        });
+        if unfired {
+            return Err(ScheduleError::NotFired { order, course });
+        }
+        for firing in &mut self.firings {
+            if firing.order == order && firing.course == course {
+                firing.status = FiringStatus::Served;
+            }
+        }
+
+        let next = self
+            .firings_for(order)
+            .filter(|f| f.status == FiringStatus::Held)
+            .map(|f| f.course)
+            .min();
+        Ok(next.map(|next| self.plan(order, next, now, now + self.course_gap)))
+    }
+
+    /// The lines whose time has come, by order.
+    pub fn due(&self, now: Timestamp) -> BTreeMap<OrderId, Vec<usize>> {
+        let mut due: BTreeMap<OrderId, Vec<usize>> = BTreeMap::new();
+        for firing in &self.firings {
+            if firing.status == FiringStatus::Scheduled
+                && firing.fire_at.is_some_and(|at| at <= now)
+            {
+                due.entry(firing.order).or_default().push(firing.line);
+            }
+        }
+        due
+    }
+
+    /// When the next line is due, so a driver knows how long it can wait.
+    pub fn next_due(&self) -> Option<Timestamp> {
+        self.firings
+            .iter()
+            .filter(|f| f.status == FiringStatus::Scheduled)
+            .filter_map(|f| f.fire_at)
+            .min()
+    }
+
+    /// Marks lines as fired, for lines fired some other way than `fire_due`.
+    pub fn fired(&mut self, order: OrderId, lines: &[usize]) {
+        for firing in &mut self.firings {
+            if firing.order == order
+                && lines.contains(&firing.line)
+                && firing.status == FiringStatus::Scheduled
+            {
+                firing.status = FiringStatus::Fired;
+            }
+        }
+    }
+
+    /// Fires everything that is due by `clock` on the shift's kitchen.
+    pub fn fire_due(
+        &mut self,
+        shift: &mut Shift,
+        clock: &impl Clock,
+    ) -> Result<Vec<TicketId>, ShiftError> {
+        let now = clock.now();
+        let mut tickets = Vec::new();
+        for (order, lines) in self.due(now) {
+            tickets.extend(shift.fire_course(order, &lines, now)?);
+            self.fired(order, &lines);
+        }
+        Ok(tickets)
+    }
+
+    /// Drops an order, e.g. one that was voided. Cooks it had booked stay
+    /// booked.
+    pub fn cancel(&mut self, order: OrderId) {
+        self.firings.retain(|f| f.order != order);
+    }
+
+    // Times `course` of `order` to land as soon as it can, but not before
+    // `earliest`. Every line gets a cook at its station and is fired its
+    // prep time before it has to be up; a cook given more than one line
+    // cooks them back to back, the last one finishing as the course lands.
+    // If some cook is not free in time the whole course lands that much
+    // later and is tried again.
+    fn plan(
+        &mut self,
+        order: OrderId,
+        course: Course,
+        now: Timestamp,
+        earliest: Timestamp,
+    ) -> Timestamp {
+        let mut lines: Vec<(usize, Station, u64)> = self
+            .firings
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.order == order && f.course == course)
+            .map(|(index, f)| (index, f.station, f.prep.as_secs()))
+            .collect();
+        // longest first, since they decide when the course can land
+        lines.sort_by_key(|&(_, _, prep)| Reverse(prep));
+
+        let now = now.as_secs();
+        for &(_, station, _) in &lines {
+            let cooks = self.cooks.get(&station).copied().unwrap_or(1);
+            let free = self.free_at.entry(station).or_default();
+            if free.len() < cooks {
+                free.resize(cooks, 0);
+            }
+        }
+
+        let longest = lines.first().map_or(0, |&(_, _, prep)| prep);
+        let mut land = earliest.as_secs().max(now + longest);
+        let placed = loop {
+            // how far back from `land` each cook is already taken
+            let mut taken: BTreeMap<Station, Vec<u64>> = BTreeMap::new();
+            let mut placed = Vec::new();
+            let mut short = 0;
+            for &(index, station, prep) in &lines {
+                let free = &self.free_at[&station];
+                let until = taken
+                    .entry(station)
+                    .or_insert_with(|| vec![land; free.len()]);
+                let ready = |cook: usize| free[cook].max(now);
+                let cook = (0..until.len())
+                    .max_by_key(|&cook| (until[cook] as i64 - ready(cook) as i64, Reverse(cook)))
+                    .expect("every station has a cook");
+                let start = until[cook].saturating_sub(prep);
+                short = short.max(ready(cook).saturating_sub(start));
+                until[cook] = start;
+                placed.push((index, station, cook, start));
+            }
+            if short == 0 {
+                break placed;
+            }
+            land += short;
+        };
+
+        let lands_at = Timestamp::from_secs(land);
+        for (index, station, cook, start) in placed {
+            let firing = &mut self.firings[index];
+            firing.status = FiringStatus::Scheduled;
+            firing.fire_at = Some(Timestamp::from_secs(start));
+            firing.lands_at = Some(lands_at);
+            let free = &mut self.free_at.get_mut(&station).expect("resized above")[cook];
+            *free = (*free).max(land);
+        }
+        lands_at
+    }
+}
@@ -0,0 +1,117 @@
+// Which fruit comes with breakfast depends on what is in season. This is a
+// child of back_of_house, so it may fill in Breakfast's private field.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Breakfast;
+use crate::time::Date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Meteorological seasons in the northern hemisphere: spring starts on
+    /// March 1st, summer on June 1st and so on.
+    pub fn of(date: Date) -> Season {
+        match date.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// the house table, which anything not configured falls back to
+const DEFAULT_FRUIT: [(Season, &str); 4] = [
+    (Season::Spring, "strawberries"),
+    (Season::Summer, "peaches"),
+    (Season::Autumn, "apples"),
+    (Season::Winter, "oranges"),
+];
+
+/// What fruit the kitchen serves in each season.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeasonalProduce {
+    fruit: BTreeMap<Season, String>,
+}
+
+impl Default for SeasonalProduce {
+    fn default() -> SeasonalProduce {
+        DEFAULT_FRUIT
+            .iter()
+            .fold(SeasonalProduce::new(), |table, (season, fruit)| {
+                table.set(*season, fruit)
+            })
+    }
+}
+
+impl SeasonalProduce {
+    /// An empty table. Seasons without an entry fall back to the default
+    /// table's fruit.
+    pub fn new() -> SeasonalProduce {
+        SeasonalProduce {
+            fruit: BTreeMap::new(),
+        }
+    }
+
+    pub fn set(mut self, season: Season, fruit: &str) -> SeasonalProduce {
+        self.fruit.insert(season, String::from(fruit));
+        self
+    }
+
+    /// Only the seasons that were set explicitly.
+    pub fn entries(&self) -> impl Iterator<Item = (Season, &str)> {
+        self.fruit
+            .iter()
+            .map(|(season, fruit)| (*season, fruit.as_str()))
+    }
+
+    pub fn fruit(&self, season: Season) -> &str {
+        match self.fruit.get(&season) {
+            Some(fruit) => fruit,
+            None => DEFAULT_FRUIT
+                .iter()
+                .find(|(default, _)| *default == season)
+                .map(|(_, fruit)| *fruit)
+                .expect("the default table has every season"),
+        }
+    }
+
+    /// A breakfast with this table's fruit. This is the way to serve a
+    /// configured table; `Breakfast::summer` and friends always use the
+    /// default one.
+    pub fn breakfast(&self, toast: &str, season: Season) -> Breakfast {
+        Breakfast {
+            toast: String::from(toast),
+            seasonal_fruit: String::from(self.fruit(season)),
+            season,
+        }
+    }
+
+    pub fn breakfast_on(&self, toast: &str, date: Date) -> Breakfast {
+        self.breakfast(toast, Season::of(date))
+    }
+}
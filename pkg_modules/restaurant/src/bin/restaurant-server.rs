@@ -0,0 +1,68 @@
+// Serves a state file made with `restaurant init` over HTTP, so the tablets
+// and kitchen screens can share one shift. The restaurant CLI can still be
+// used on the same file while the server is down, but not while it is up:
+// the server only reads the file when it starts.
+
+use std::env;
+use std::process::ExitCode;
+
+use restaurant::server::Server;
+use restaurant::state::StateFile;
+
+const USAGE: &str = "\
+usage: restaurant-server [--state FILE] [--listen ADDR]
+
+The state file defaults to $RESTAURANT_STATE, or restaurant.json in the
+current directory. The server listens on 127.0.0.1:8080 unless told
+otherwise; use 0.0.0.0:8080 to let other devices on the network in.";
+
+fn main() -> ExitCode {
+    let mut state =
+        env::var("RESTAURANT_STATE").unwrap_or_else(|_| String::from("restaurant.json"));
+    let mut listen = String::from("127.0.0.1:8080");
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--state" => &mut state,
+            "--listen" => &mut listen,
+            "--help" | "help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            other => {
+                eprintln!(
+                    "restaurant-server: unexpected argument {}\n\n{}",
+                    other, USAGE
+                );
+                return ExitCode::from(2);
+            }
+        };
+        match args.next() {
+            Some(next) => *value = next,
+            None => {
+                eprintln!("restaurant-server: {} needs a value\n\n{}", arg, USAGE);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let loaded = match StateFile::load(&state) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("restaurant-server: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match Server::new(loaded).save_to(&state).bind(&listen) {
+        Ok(running) => {
+            eprintln!("serving {} on http://{}", state, running.addr());
+            running.wait();
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("restaurant-server: cannot listen on {}: {}", listen, err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The command line was wrong; the usage text is printed with it.
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+// Options are taken out first and whatever is left is positional, so they
+// can appear anywhere on the line.
+pub struct Args {
+    words: Vec<String>,
+}
+
+impl Args {
+    pub fn new(words: Vec<String>) -> Args {
+        Args { words }
+    }
+
+    /// `--name value` or `--name=value`.
+    pub fn option(&mut self, name: &str) -> Result<Option<String>, UsageError> {
+        let flag = format!("--{}", name);
+        let prefix = format!("--{}=", name);
+        if let Some(index) = self.words.iter().position(|w| w.starts_with(&prefix)) {
+            let word = self.words.remove(index);
+            return Ok(Some(word[prefix.len()..].to_string()));
+        }
+        let Some(index) = self.words.iter().position(|w| *w == flag) else {
+            return Ok(None);
+        };
+        if index + 1 >= self.words.len() {
+            return Err(UsageError(format!("{} needs a value", flag)));
+        }
+        self.words.remove(index);
+        Ok(Some(self.words.remove(index)))
+    }
+
+    /// Every value given for a repeatable option.
+    pub fn options(&mut self, name: &str) -> Result<Vec<String>, UsageError> {
+        let mut values = Vec::new();
+        while let Some(value) = self.option(name)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    pub fn parsed_option<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, UsageError> {
+        self.option(name)?
+            .map(|value| parse(&value, &format!("--{}", name)))
+            .transpose()
+    }
+
+    pub fn flag(&mut self, name: &str) -> bool {
+        let flag = format!("--{}", name);
+        match self.words.iter().position(|w| *w == flag) {
+            Some(index) => {
+                self.words.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The next positional argument.
+    pub fn next(&mut self, what: &str) -> Result<String, UsageError> {
+        self.next_optional()
+            .ok_or_else(|| UsageError(format!("missing {}", what)))
+    }
+
+    pub fn next_optional(&mut self) -> Option<String> {
+        let index = self.words.iter().position(|w| !w.starts_with("--"))?;
+        Some(self.words.remove(index))
+    }
+
+    pub fn next_parsed<T: FromStr>(&mut self, what: &str) -> Result<T, UsageError> {
+        parse(&self.next(what)?, what)
+    }
+
+    /// Fails on anything that was not used.
+    pub fn finish(self) -> Result<(), UsageError> {
+        match self.words.first() {
+            Some(word) => Err(UsageError(format!("unexpected argument {}", word))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse<T: FromStr>(value: &str, what: &str) -> Result<T, UsageError> {
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("{} is not a valid {}", value, what)))
+}
+
+/// Dollars, with or without cents: `12`, `12.5` or `12.50`.
+pub fn money(value: &str) -> Result<u64, UsageError> {
+    let invalid = || UsageError(format!("{} is not an amount of money", value));
+    let value = value.strip_prefix('$').unwrap_or(value);
+    let (dollars, cents) = value.split_once('.').unwrap_or((value, "0"));
+    if cents.is_empty() || cents.len() > 2 || !cents.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let dollars: u64 = dollars.parse().map_err(|_| invalid())?;
+    let cents: u64 = format!("{:0<2}", cents).parse().map_err(|_| invalid())?;
+    Ok(dollars * 100 + cents)
+}
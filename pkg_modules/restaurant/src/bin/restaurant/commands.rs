@@ -0,0 +1,610 @@
+use std::error::Error;
+use std::time::Duration;
+
+use restaurant::billing::{CheckId, InMemoryGateway, Share, Tender};
+use restaurant::config;
+use restaurant::hosting::{FloorPlan, Party, PartyId, Priority, SeatingArea, TableNumber};
+use restaurant::inventory::Inventory;
+use restaurant::kitchen::{Station, Ticket, TicketId};
+use restaurant::menu::Menu;
+use restaurant::report::ShiftReport;
+use restaurant::serving::{Cents, OrderId, OrderStatus};
+use restaurant::staff::{Role, Roster, StaffId};
+use restaurant::state::StateFile;
+
+use crate::args::{money, Args, UsageError};
+use crate::Context;
+
+type CommandResult = Result<(), Box<dyn Error>>;
+
+const STATIONS: [Station; 4] = [
+    Station::Grill,
+    Station::Fry,
+    Station::Salad,
+    Station::Dessert,
+];
+
+// Loads the state, runs `f` on it and saves it again if `f` succeeded.
+fn with_state<T>(
+    context: &Context,
+    f: impl FnOnce(&mut StateFile) -> Result<T, Box<dyn Error>>,
+) -> Result<T, Box<dyn Error>> {
+    let mut state = StateFile::load(&context.state)?;
+    let result = f(&mut state)?;
+    state.save(&context.state)?;
+    Ok(result)
+}
+
+pub fn init(context: &Context, mut args: Args) -> CommandResult {
+    let floor = args.option("floor")?;
+    let menu = args.option("menu")?;
+    let inventory = args.option("inventory")?;
+    let manager = args.option("manager")?;
+    let force = args.flag("force");
+    args.finish()?;
+
+    let floor: FloorPlan = match floor {
+        Some(path) => config::load(path)?,
+        None => return Err(UsageError(String::from("init needs --floor")).into()),
+    };
+    let menu: Menu = match menu {
+        Some(path) => config::load(path)?,
+        None => Menu::standard(),
+    };
+    let inventory: Inventory = match inventory {
+        Some(path) => config::load(path)?,
+        None => Inventory::default(),
+    };
+    if context.state.exists() && !force {
+        return Err(format!(
+            "{} already exists, use --force to start over",
+            context.state.display()
+        )
+        .into());
+    }
+    let mut staff = Roster::new();
+    let manager = manager.map(|name| (staff.add(&name, Role::Manager), name));
+    StateFile::new(floor, menu)
+        .with_staff(staff)
+        .with_inventory(inventory)
+        .save(&context.state)?;
+    println!("started a new shift in {}", context.state.display());
+    if let Some((id, name)) = manager {
+        println!("{} is {}", name, id);
+    }
+    Ok(())
+}
+
+pub fn waitlist(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("waitlist command")?.as_str() {
+        "add" => {
+            let accessible = args.flag("accessible");
+            let priority = args.flag("priority");
+            let area = args.option("area")?.map(|a| area(&a)).transpose()?;
+            let name = args.next("name")?;
+            let size: u8 = args.next_parsed("party size")?;
+            args.finish()?;
+
+            let mut party = Party::new(&name, size, now);
+            if accessible {
+                party = party.needs_accessible();
+            }
+            if let Some(area) = area {
+                party = party.prefers(area);
+            }
+            if priority {
+                party = party.priority(Priority::High);
+            }
+            with_state(context, |state| {
+                let quote = state.shift.waitlist().quote(&party);
+                let id = state.shift.add_to_waitlist(party)?;
+                println!("{}", id.0);
+                eprintln!("quoted {}", minutes(quote));
+                Ok(())
+            })
+        }
+        "list" => {
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            for (place, entry) in state.shift.waitlist().iter().enumerate() {
+                println!(
+                    "{:>3}. {:<5} {:<20} {:>2}  waiting {}, quoted {}",
+                    place + 1,
+                    entry.id.to_string(),
+                    entry.party.name,
+                    entry.party.size,
+                    minutes(now.saturating_duration_since(entry.party.arrived)),
+                    minutes(entry.quoted_wait),
+                );
+            }
+            Ok(())
+        }
+        "seat" => {
+            args.finish()?;
+            with_state(context, |state| {
+                let seated = state.shift.seat_next(now)?;
+                let tables: Vec<String> = seated.tables.iter().map(|t| t.to_string()).collect();
+                println!(
+                    "{} ({}) at {}",
+                    seated.id,
+                    seated.party.name,
+                    tables.join("+")
+                );
+                Ok(())
+            })
+        }
+        "leave" => {
+            let party = PartyId(args.next_parsed("party")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.leave_waitlist(party, now)?;
+                Ok(())
+            })
+        }
+        other => Err(UsageError(format!("unknown waitlist command {}", other)).into()),
+    }
+}
+
+pub fn table(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("table command")?.as_str() {
+        "list" => {
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            for table in state.shift.floor().tables() {
+                println!(
+                    "{:<5} {:>2} seats  {:<8} {}",
+                    table.number.to_string(),
+                    table.seats,
+                    format!("{:?}", table.area).to_lowercase(),
+                    table.state
+                );
+            }
+            Ok(())
+        }
+        "vacate" => {
+            let party = PartyId(args.next_parsed("party")?);
+            args.finish()?;
+            with_state(context, |state| {
+                for table in state.shift.vacate(party, now)? {
+                    println!("{} needs bussing", table);
+                }
+                Ok(())
+            })
+        }
+        "bus" => {
+            let table = TableNumber(args.next_parsed("table")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.bus(table, now)?;
+                Ok(())
+            })
+        }
+        other => Err(UsageError(format!("unknown table command {}", other)).into()),
+    }
+}
+
+pub fn order(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("order command")?.as_str() {
+        "new" => {
+            let table = TableNumber(args.next_parsed("table")?);
+            args.finish()?;
+            with_state(context, |state| {
+                let order = state.shift.take_order(table, Vec::new(), now)?;
+                println!("{}", order.0);
+                Ok(())
+            })
+        }
+        "add-item" => {
+            let quantity: u16 = args.parsed_option("qty")?.unwrap_or(1);
+            let seat: Option<u8> = args.parsed_option("seat")?;
+            let modifiers = args.options("with")?;
+            let order = OrderId(args.next_parsed("order")?);
+            let item = args.next("item")?;
+            args.finish()?;
+            with_state(context, |state| {
+                let modifiers: Vec<&str> = modifiers.iter().map(String::as_str).collect();
+                let mut line = state.menu.order(&item, quantity, &modifiers, now)?;
+                if let Some(seat) = seat {
+                    line = line.for_seat(seat);
+                }
+                let index = state.shift.add_item(order, line, now)?;
+                println!("{}", index);
+                Ok(())
+            })
+        }
+        "share" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let line: usize = args.next_parsed("line")?;
+            let mut shares = Vec::new();
+            while let Some(seat) = args.next_optional() {
+                shares.push(share(&seat)?);
+            }
+            with_state(context, |state| {
+                state.shift.share_item(order, line, shares, now)?;
+                Ok(())
+            })
+        }
+        "send" => {
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.send_to_kitchen(order, now)?;
+                for ticket in state.shift.cook_order(order, now)? {
+                    println!("{}", ticket.0);
+                }
+                Ok(())
+            })
+        }
+        "serve" => {
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.serve_order(order, now)?;
+                Ok(())
+            })
+        }
+        "void" => {
+            let by = by(&mut args)?;
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.on_duty(by)?.void(order, now)?;
+                Ok(())
+            })
+        }
+        "show" => {
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            let order = state.shift.orders().get(order)?;
+            println!("{} at {}, {}", order.id, order.table, order.status());
+            for (index, line) in order.items().iter().enumerate() {
+                let modifiers: Vec<&str> = line.modifiers.iter().map(|m| m.name.as_str()).collect();
+                println!(
+                    "{:>3}. {:>2}x {:<24} {:>9}",
+                    index,
+                    line.quantity,
+                    if modifiers.is_empty() {
+                        line.item.clone()
+                    } else {
+                        format!("{} ({})", line.item, modifiers.join(", "))
+                    },
+                    dollars(line.total())
+                );
+            }
+            println!("     {:<28} {:>9}", "subtotal", dollars(order.subtotal()));
+            Ok(())
+        }
+        other => Err(UsageError(format!("unknown order command {}", other)).into()),
+    }
+}
+
+pub fn kitchen(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("kitchen command")?.as_str() {
+        "queue" => {
+            let stations = match args.next_optional() {
+                Some(name) => vec![station(&name)?],
+                None => STATIONS.to_vec(),
+            };
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            for station in stations {
+                for ticket in state.shift.kitchen().queue(station) {
+                    print_ticket(ticket, now);
+                }
+            }
+            Ok(())
+        }
+        "bump" => {
+            let ticket = TicketId(args.next_parsed("ticket")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.bump(ticket, now)?;
+                let order = state.shift.kitchen().ticket(ticket)?.order;
+                let cooking = state.shift.orders().get(order)?.status() == OrderStatus::Cooking;
+                if cooking && state.shift.kitchen().is_order_done(order) {
+                    state.shift.mark_ready(order, now)?;
+                    println!("{} is ready", order);
+                }
+                Ok(())
+            })
+        }
+        other => Err(UsageError(format!("unknown kitchen command {}", other)).into()),
+    }
+}
+
+fn print_ticket(ticket: &Ticket, now: restaurant::time::Timestamp) {
+    let items: Vec<String> = ticket
+        .items
+        .iter()
+        .map(|i| format!("{}x {}", i.quantity, i.item))
+        .collect();
+    println!(
+        "{:<4} {:<8} {:<5} {:>6}  {}",
+        ticket.id.0,
+        ticket.station.to_string(),
+        ticket.table.to_string(),
+        minutes(ticket.elapsed(now)),
+        items.join(", ")
+    );
+}
+
+pub fn bill(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("bill command")?.as_str() {
+        "show" => {
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            let bill = state.shift.bill(order)?;
+            for check in bill.checks() {
+                println!("{} ({})", check.id, check.label);
+                for (index, line) in check.lines.iter().enumerate() {
+                    println!(
+                        "  {:>2}. {:>2}x {:<24} {:>9}",
+                        index,
+                        line.quantity,
+                        line.description,
+                        dollars(line.amount)
+                    );
+                }
+                println!("  {:<32} {:>9}", "subtotal", dollars(check.subtotal));
+                if check.tip > 0 {
+                    println!("  {:<32} {:>9}", "tip", dollars(check.tip));
+                }
+                println!("  {:<32} {:>9}", "paid", dollars(check.paid()));
+                if check.refunded() > 0 {
+                    println!("  {:<32} {:>9}", "refunded", dollars(check.refunded()));
+                }
+                println!("  {:<32} {:>9}", "balance", dollars(check.balance()));
+            }
+            Ok(())
+        }
+        "split" => {
+            let by_seat = args.flag("by-seat");
+            let ways: Option<usize> = args.parsed_option("ways")?;
+            let order = OrderId(args.next_parsed("order")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.open_bill(order, now)?;
+                match (by_seat, ways) {
+                    (true, None) => state.shift.split_by_seat(order, now)?,
+                    (false, Some(ways)) => state.shift.split_evenly(order, ways, now)?,
+                    _ => {
+                        return Err(UsageError(String::from(
+                            "split needs exactly one of --by-seat and --ways",
+                        ))
+                        .into())
+                    }
+                }
+                for check in state.shift.bill(order)?.checks() {
+                    println!("{} {} {}", check.id.0, check.label, dollars(check.total()));
+                }
+                Ok(())
+            })
+        }
+        "check" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let label = args.next("label")?;
+            args.finish()?;
+            with_state(context, |state| {
+                open_if_needed(state, order, context)?;
+                let check = state.shift.open_check(order, &label, now)?;
+                println!("{}", check.0);
+                Ok(())
+            })
+        }
+        "merge" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let from = CheckId(args.next_parsed("check")?);
+            let into = CheckId(args.next_parsed("check")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.merge_checks(order, from, into, now)?;
+                Ok(())
+            })
+        }
+        "move" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let from = CheckId(args.next_parsed("check")?);
+            let line: usize = args.next_parsed("line")?;
+            let to = CheckId(args.next_parsed("check")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.transfer_line(order, from, line, to, now)?;
+                Ok(())
+            })
+        }
+        "tip" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let check = CheckId(args.next_parsed("check")?);
+            let amount = money(&args.next("amount")?)?;
+            args.finish()?;
+            with_state(context, |state| {
+                open_if_needed(state, order, context)?;
+                state.shift.add_tip(order, check, amount, now)?;
+                Ok(())
+            })
+        }
+        "pay" => {
+            let order = OrderId(args.next_parsed("order")?);
+            let check = CheckId(args.next_parsed("check")?);
+            let tender = match args.next("tender")?.as_str() {
+                "cash" => Tender::Cash(money(&args.next("amount")?)?),
+                "card" => {
+                    let last4 = args.next("last four digits")?;
+                    let amount = money(&args.next("amount")?)?;
+                    Tender::Card { amount, last4 }
+                }
+                other => return Err(UsageError(format!("{} is not cash or card", other)).into()),
+            };
+            args.finish()?;
+            let card = match &tender {
+                Tender::Card { last4, .. } => Some(last4.clone()),
+                _ => None,
+            };
+            with_state(context, |state| {
+                open_if_needed(state, order, context)?;
+                // there is no card terminal behind the command line
+                let mut gateway = InMemoryGateway::new();
+                let receipt = state
+                    .shift
+                    .take_payment(order, check, tender, &mut gateway, now)?;
+                if let Some(last4) = card {
+                    println!("card ending {} recorded, not charged (simulated)", last4);
+                }
+                if receipt.change > 0 {
+                    println!("change {}", dollars(receipt.change));
+                }
+                println!("balance {}", dollars(receipt.balance));
+                Ok(())
+            })
+        }
+        other => Err(UsageError(format!("unknown bill command {}", other)).into()),
+    }
+}
+
+// A seat sharing a line, as SEAT for one part or SEAT:PARTS.
+fn share(value: &str) -> Result<Share, UsageError> {
+    let invalid = || UsageError(format!("{} is not a seat or SEAT:PARTS", value));
+    let (seat, parts) = value.split_once(':').unwrap_or((value, "1"));
+    let seat = seat.parse().map_err(|_| invalid())?;
+    let parts = parts.parse().map_err(|_| invalid())?;
+    Ok(Share::new(seat, parts))
+}
+
+// Tips and payments go on the bill as printed; print it first if nobody has.
+fn open_if_needed(
+    state: &mut StateFile,
+    order: OrderId,
+    context: &Context,
+) -> Result<(), Box<dyn Error>> {
+    if state.shift.bill(order).is_err() {
+        state.shift.open_bill(order, context.now)?;
+    }
+    Ok(())
+}
+
+pub fn report(context: &Context, mut args: Args) -> CommandResult {
+    match args.next("report command")?.as_str() {
+        "eod" => {
+            let format = args
+                .option("format")?
+                .unwrap_or_else(|| String::from("text"));
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            let report = ShiftReport::from_shift(&state.shift, &state.menu);
+            match format.as_str() {
+                "text" => print!("{}", report.to_text()),
+                "csv" => print!("{}", report.to_csv()),
+                "json" => println!("{}", report.to_json()),
+                other => {
+                    return Err(UsageError(format!("{} is not text, csv or json", other)).into())
+                }
+            }
+            Ok(())
+        }
+        other => Err(UsageError(format!("unknown report {}", other)).into()),
+    }
+}
+
+pub fn staff(context: &Context, mut args: Args) -> CommandResult {
+    let now = context.now;
+    match args.next("staff command")?.as_str() {
+        "list" => {
+            args.finish()?;
+            let state = StateFile::load(&context.state)?;
+            for member in state.shift.roster().iter() {
+                println!(
+                    "{:<9} {:<20} {:<8} {}",
+                    member.id.to_string(),
+                    member.name,
+                    member.role.to_string(),
+                    if member.is_on_shift() { "on" } else { "off" }
+                );
+            }
+            Ok(())
+        }
+        "hire" => {
+            let by = by(&mut args)?;
+            let name = args.next("name")?;
+            let role = role(&args.next("role")?)?;
+            args.finish()?;
+            with_state(context, |state| {
+                let id = state.shift.on_duty(by)?.hire(&name, role, now)?;
+                println!("{}", id.0);
+                Ok(())
+            })
+        }
+        "in" => {
+            let staff = StaffId(args.next_parsed("staff")?);
+            args.finish()?;
+            with_state(context, |state| {
+                state.shift.clock_in(staff, now)?;
+                Ok(())
+            })
+        }
+        "out" => {
+            let staff = StaffId(args.next_parsed("staff")?);
+            args.finish()?;
+            with_state(context, |state| {
+                let stint = state.shift.clock_out(staff, now)?;
+                eprintln!("worked {}", minutes(stint));
+                Ok(())
+            })
+        }
+        other => Err(UsageError(format!("unknown staff command {}", other)).into()),
+    }
+}
+
+// Who is doing it, for the commands that need someone allowed to.
+fn by(args: &mut Args) -> Result<StaffId, UsageError> {
+    args.parsed_option("by")?
+        .map(StaffId)
+        .ok_or_else(|| UsageError(String::from("missing --by STAFF")))
+}
+
+fn role(name: &str) -> Result<Role, UsageError> {
+    match name.to_ascii_lowercase().as_str() {
+        "host" => Ok(Role::Host),
+        "server" => Ok(Role::Server),
+        "cook" => Ok(Role::Cook),
+        "manager" => Ok(Role::Manager),
+        _ => Err(UsageError(format!(
+            "{} is not host, server, cook or manager",
+            name
+        ))),
+    }
+}
+
+fn area(name: &str) -> Result<SeatingArea, UsageError> {
+    match name.to_ascii_lowercase().as_str() {
+        "indoor" => Ok(SeatingArea::Indoor),
+        "outdoor" => Ok(SeatingArea::Outdoor),
+        "bar" => Ok(SeatingArea::Bar),
+        _ => Err(UsageError(format!(
+            "{} is not indoor, outdoor or bar",
+            name
+        ))),
+    }
+}
+
+fn station(name: &str) -> Result<Station, UsageError> {
+    STATIONS
+        .into_iter()
+        .find(|s| s.to_string().eq_ignore_ascii_case(name))
+        .ok_or_else(|| UsageError(format!("{} is not a station", name)))
+}
+
+fn dollars(cents: Cents) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+fn minutes(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}m{:02}s", secs / 60, secs % 60)
+}
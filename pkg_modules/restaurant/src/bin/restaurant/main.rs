@@ -0,0 +1,114 @@
+// The restaurant on the command line. Every run loads the state file,
+// replays its log, does one thing and saves the file again, so staff tools
+// can script a whole service one command at a time. Commands that create
+// something print just its number, to make that easy to capture.
+
+mod args;
+mod commands;
+
+use std::env;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use restaurant::time::Timestamp;
+
+use self::args::{Args, UsageError};
+
+const USAGE: &str = "\
+usage: restaurant [--state FILE] [--at SECONDS] COMMAND
+
+  init --floor FILE [--menu FILE] [--inventory FILE] [--manager NAME] [--force]
+  waitlist add NAME SIZE [--accessible] [--area indoor|outdoor|bar] [--priority]
+  waitlist list
+  waitlist seat
+  waitlist leave PARTY
+  table list
+  table vacate PARTY
+  table bus TABLE
+  order new TABLE
+  order add-item ORDER ITEM [--qty N] [--seat N] [--with MODIFIER]...
+  order share ORDER LINE [SEAT[:PARTS]]...
+  order send ORDER
+  order serve ORDER
+  order void ORDER --by STAFF
+  order show ORDER
+  kitchen queue [grill|fry|salad|dessert]
+  kitchen bump TICKET
+  bill show ORDER
+  bill split ORDER (--by-seat | --ways N)
+  bill check ORDER LABEL
+  bill merge ORDER FROM INTO
+  bill move ORDER FROM LINE TO
+  bill tip ORDER CHECK AMOUNT
+  bill pay ORDER CHECK cash AMOUNT
+  bill pay ORDER CHECK card LAST4 AMOUNT
+  report eod [--format text|csv|json]
+  staff list
+  staff hire NAME host|server|cook|manager --by STAFF
+  staff in STAFF
+  staff out STAFF
+
+The state file defaults to $RESTAURANT_STATE, or restaurant.json in the
+current directory. --at runs the command at a given Unix time instead of now.
+Amounts are in dollars, e.g. 12.50. Commands taking --by STAFF check that
+that member of staff is clocked in and allowed to; init --manager puts the
+first one on the roster. Card payments go through a simulated gateway: they
+are recorded on the bill but no card is charged.";
+
+/// What every command gets: where the state lives and when it is.
+pub struct Context {
+    pub state: PathBuf,
+    pub now: Timestamp,
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.is::<UsageError>() => {
+            eprintln!("restaurant: {}\n\n{}", err, USAGE);
+            ExitCode::from(2)
+        }
+        Err(err) => {
+            eprintln!("restaurant: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(words: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let mut args = Args::new(words);
+    if args.flag("help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let state = args
+        .option("state")?
+        .or_else(|| env::var("RESTAURANT_STATE").ok())
+        .unwrap_or_else(|| String::from("restaurant.json"));
+    let now = match args.parsed_option::<u64>("at")? {
+        Some(secs) => Timestamp::from_secs(secs),
+        None => Timestamp::now(),
+    };
+    let context = Context {
+        state: PathBuf::from(state),
+        now,
+    };
+
+    let command = args.next("command")?;
+    match command.as_str() {
+        "init" => commands::init(&context, args),
+        "waitlist" => commands::waitlist(&context, args),
+        "table" => commands::table(&context, args),
+        "order" => commands::order(&context, args),
+        "kitchen" => commands::kitchen(&context, args),
+        "bill" => commands::bill(&context, args),
+        "report" => commands::report(&context, args),
+        "staff" => commands::staff(&context, args),
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(UsageError(format!("unknown command {}", other)).into()),
+    }
+}
@@ -0,0 +1,205 @@
+// Loading and saving the parts of the restaurant that change without a
+// rebuild: the menu, the seasonal fruit table, the floor plan and the
+// walk-in. Every file can be written in TOML or JSON; the extension decides
+// which. Both point syntax errors at a line, but only TOML says where each
+// value was, so problems found after parsing (a table defined twice, say)
+// carry a line in TOML files and none in JSON ones.
+
+mod floor;
+mod inventory;
+mod menu;
+mod produce;
+mod spans;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub use self::floor::{FloorFile, TableEntry};
+pub use self::inventory::{IngredientEntry, InventoryFile, RecipeEntry};
+pub use self::menu::{MenuFile, MenuItemEntry, ModifierEntry, WindowEntry};
+pub use self::produce::ProduceFile;
+pub use self::spans::Spans;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// Picks the format from a `.toml` or `.json` extension.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Something wrong with a configuration file, pointing at where it is when
+/// that can be known.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: Option<PathBuf>,
+    /// 1-based line and column.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub(crate) fn new(message: impl Into<String>) -> ConfigError {
+        ConfigError {
+            path: None,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// An error about the value at `path` (see `Spans`), for problems serde
+    /// cannot see, like duplicates. Without a span there is no line.
+    pub(crate) fn at(spans: &Spans, path: &str, message: impl Into<String>) -> ConfigError {
+        let mut err = ConfigError::new(message);
+        if let Some((line, column)) = spans.at(path) {
+            err.line = Some(line);
+            err.column = Some(column);
+        }
+        err
+    }
+
+    fn in_file(mut self, path: &Path) -> ConfigError {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+        }
+        if self.path.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> ConfigError {
+        ConfigError::new(err.to_string())
+    }
+}
+
+/// A value that is stored in a configuration file. `Schema` is the shape of
+/// the file; converting from it is where everything serde cannot check for
+/// us gets validated.
+pub trait ConfigFile: Sized {
+    type Schema: Serialize + DeserializeOwned;
+
+    /// `spans` says where each value was in the file, for pointing errors
+    /// at lines.
+    fn from_schema(schema: Self::Schema, spans: &Spans) -> Result<Self, ConfigError>;
+
+    fn to_schema(&self) -> Self::Schema;
+}
+
+pub fn parse<T: ConfigFile>(source: &str, format: Format) -> Result<T, ConfigError> {
+    let (schema, spans) = match format {
+        Format::Toml => (
+            toml::from_str(source).map_err(|err| toml_error(source, err))?,
+            Spans::of_toml(source),
+        ),
+        // serde_json has no spans to give, see spans.rs
+        Format::Json => (
+            serde_json::from_str(source).map_err(json_error)?,
+            Spans::none(),
+        ),
+    };
+    T::from_schema(schema, &spans)
+}
+
+pub fn to_string<T: ConfigFile>(value: &T, format: Format) -> Result<String, ConfigError> {
+    let schema = value.to_schema();
+    match format {
+        Format::Toml => {
+            toml::to_string_pretty(&schema).map_err(|err| ConfigError::new(err.to_string()))
+        }
+        Format::Json => serde_json::to_string_pretty(&schema)
+            .map(|mut json| {
+                json.push('\n');
+                json
+            })
+            .map_err(|err| ConfigError::new(err.to_string())),
+    }
+}
+
+pub fn load<T: ConfigFile>(path: impl AsRef<Path>) -> Result<T, ConfigError> {
+    let path = path.as_ref();
+    let format = format_of(path)?;
+    let source = fs::read_to_string(path).map_err(|err| ConfigError::from(err).in_file(path))?;
+    parse(&source, format).map_err(|err| err.in_file(path))
+}
+
+pub fn save<T: ConfigFile>(value: &T, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+    let path = path.as_ref();
+    let format = format_of(path)?;
+    let text = to_string(value, format).map_err(|err| err.in_file(path))?;
+    fs::write(path, text).map_err(|err| ConfigError::from(err).in_file(path))
+}
+
+fn format_of(path: &Path) -> Result<Format, ConfigError> {
+    Format::from_path(path).ok_or_else(|| {
+        ConfigError::new("configuration files must end in .toml or .json").in_file(path)
+    })
+}
+
+fn toml_error(source: &str, err: toml::de::Error) -> ConfigError {
+    let mut config_err = ConfigError::new(err.message().trim());
+    if let Some(span) = err.span() {
+        let (line, column) = line_and_column(source, span.start);
+        config_err.line = Some(line);
+        config_err.column = Some(column);
+    }
+    config_err
+}
+
+fn json_error(err: serde_json::Error) -> ConfigError {
+    // serde_json appends " at line X column Y" to its messages; we keep the
+    // position in fields instead so both formats print the same way.
+    let message = err.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    let mut config_err = ConfigError::new(message);
+    if err.line() > 0 {
+        config_err.line = Some(err.line());
+        config_err.column = Some(err.column());
+    }
+    config_err
+}
+
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
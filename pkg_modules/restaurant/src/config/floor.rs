@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConfigError, ConfigFile, Spans};
+use crate::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+
+/// `[[tables]]` in TOML, a `"tables"` array in JSON. Only the layout is
+/// stored; every table starts the shift free.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FloorFile {
+    #[serde(default)]
+    pub tables: Vec<TableEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableEntry {
+    pub number: u16,
+    pub seats: u8,
+    pub area: SeatingArea,
+    #[serde(default)]
+    pub accessible: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub section: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combines_with: Vec<u16>,
+}
+
+impl ConfigFile for FloorPlan {
+    type Schema = FloorFile;
+
+    fn from_schema(schema: FloorFile, spans: &Spans) -> Result<FloorPlan, ConfigError> {
+        let numbers: BTreeSet<u16> = schema.tables.iter().map(|t| t.number).collect();
+        let mut seen = BTreeSet::new();
+        let mut floor = FloorPlan::new();
+
+        for (index, entry) in schema.tables.into_iter().enumerate() {
+            let at = |field: &str| format!("tables.{}.{}", index, field);
+            if !seen.insert(entry.number) {
+                return Err(ConfigError::at(
+                    spans,
+                    &at("number"),
+                    format!("table {} is defined twice", entry.number),
+                ));
+            }
+            if entry.seats == 0 {
+                return Err(ConfigError::at(
+                    spans,
+                    &at("seats"),
+                    format!("table {} has no seats", entry.number),
+                ));
+            }
+            for (position, neighbour) in entry.combines_with.iter().enumerate() {
+                let neighbour_at = at(&format!("combines_with.{}", position));
+                if *neighbour == entry.number {
+                    return Err(ConfigError::at(
+                        spans,
+                        &neighbour_at,
+                        format!("table {} cannot be joined with itself", entry.number),
+                    ));
+                }
+                if !numbers.contains(neighbour) {
+                    return Err(ConfigError::at(
+                        spans,
+                        &neighbour_at,
+                        format!(
+                            "table {} is joined with table {}, which does not exist",
+                            entry.number, neighbour
+                        ),
+                    ));
+                }
+            }
+
+            let mut table = Table::new(entry.number, entry.seats, entry.area)
+                .in_section(&entry.section)
+                .combines_with(&entry.combines_with);
+            table.accessible = entry.accessible;
+            floor
+                .add_table(table)
+                .expect("table numbers were checked to be unique");
+        }
+        Ok(floor)
+    }
+
+    fn to_schema(&self) -> FloorFile {
+        let tables = self
+            .tables()
+            .map(|table| {
+                let mut combines_with: Vec<u16> = table
+                    .combines_with
+                    .iter()
+                    .map(|TableNumber(n)| *n)
+                    .collect();
+                combines_with.sort();
+                TableEntry {
+                    number: table.number.0,
+                    seats: table.seats,
+                    area: table.area,
+                    accessible: table.accessible,
+                    section: table.section.clone(),
+                    combines_with,
+                }
+            })
+            .collect();
+        FloorFile { tables }
+    }
+}
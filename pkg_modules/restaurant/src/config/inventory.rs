@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConfigError, ConfigFile, Spans};
+use crate::inventory::{Ingredient, Inventory, Recipe, RecipeBook, Unit};
+
+/// `[[ingredients]]` and `[[recipes]]` tables in TOML, arrays of the same
+/// names in JSON: what is in the walk-in and what each dish takes out of it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InventoryFile {
+    #[serde(default)]
+    pub ingredients: Vec<IngredientEntry>,
+    #[serde(default)]
+    pub recipes: Vec<RecipeEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngredientEntry {
+    pub name: String,
+    pub unit: Unit,
+    pub on_hand: u32,
+    #[serde(default)]
+    pub low_stock: u32,
+    /// Left out, par is whatever is on hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub par: Option<u32>,
+}
+
+/// e.g. `item = "Salad"`, `uses = { lettuce = 120, tomato = 1 }` and
+/// `modifiers = { feta = { feta = 40 } }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeEntry {
+    pub item: String,
+    #[serde(default)]
+    pub uses: BTreeMap<String, u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modifiers: BTreeMap<String, BTreeMap<String, u32>>,
+}
+
+impl ConfigFile for Inventory {
+    type Schema = InventoryFile;
+
+    fn from_schema(schema: InventoryFile, spans: &Spans) -> Result<Inventory, ConfigError> {
+        let mut recipes = RecipeBook::new();
+        let mut items = BTreeSet::new();
+        for (index, entry) in schema.recipes.into_iter().enumerate() {
+            if !items.insert(entry.item.to_lowercase()) {
+                return Err(ConfigError::at(
+                    spans,
+                    &format!("recipes.{}.item", index),
+                    format!("there are two recipes for {}", entry.item),
+                ));
+            }
+            let mut recipe = Recipe::new();
+            for (ingredient, quantity) in &entry.uses {
+                recipe = recipe.uses(ingredient, *quantity);
+            }
+            for (modifier, uses) in &entry.modifiers {
+                for (ingredient, quantity) in uses {
+                    recipe = recipe.modifier_uses(modifier, ingredient, *quantity);
+                }
+            }
+            recipes.add(&entry.item, recipe);
+        }
+
+        let mut inventory = Inventory::new(recipes);
+        for (index, entry) in schema.ingredients.into_iter().enumerate() {
+            if inventory.ingredient(&entry.name).is_some() {
+                return Err(ConfigError::at(
+                    spans,
+                    &format!("ingredients.{}.name", index),
+                    format!("{} is stocked twice", entry.name),
+                ));
+            }
+            inventory.stock(
+                Ingredient::new(&entry.name, entry.unit, entry.on_hand)
+                    .low_at(entry.low_stock)
+                    .par(entry.par.unwrap_or(entry.on_hand)),
+            );
+        }
+        Ok(inventory)
+    }
+
+    fn to_schema(&self) -> InventoryFile {
+        let ingredients = self
+            .ingredients()
+            .map(|ingredient| IngredientEntry {
+                name: ingredient.name.clone(),
+                unit: ingredient.unit,
+                on_hand: ingredient.on_hand,
+                low_stock: ingredient.low_stock,
+                par: (ingredient.par != ingredient.on_hand).then_some(ingredient.par),
+            })
+            .collect();
+        let recipes = self
+            .recipes()
+            .iter()
+            .map(|(item, recipe)| {
+                let mut modifiers: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+                for (modifier, uses) in &recipe.modifiers {
+                    let entry = modifiers.entry(modifier.clone()).or_default();
+                    for (ingredient, quantity) in uses {
+                        *entry.entry(ingredient.clone()).or_default() += quantity;
+                    }
+                }
+                let mut uses = BTreeMap::new();
+                for (ingredient, quantity) in &recipe.ingredients {
+                    *uses.entry(ingredient.clone()).or_default() += quantity;
+                }
+                RecipeEntry {
+                    item: String::from(item),
+                    uses,
+                    modifiers,
+                }
+            })
+            .collect();
+        InventoryFile {
+            ingredients,
+            recipes,
+        }
+    }
+}
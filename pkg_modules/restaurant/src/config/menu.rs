@@ -0,0 +1,179 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConfigError, ConfigFile, Spans};
+use crate::menu::{Allergen, Category, DietaryTag, Menu, MenuItem};
+use crate::serving::{Cents, Modifier};
+use crate::time::{TimeOfDay, Window};
+
+/// `[[items]]` tables in TOML, an `"items"` array in JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuFile {
+    #[serde(default)]
+    pub items: Vec<MenuItemEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuItemEntry {
+    pub name: String,
+    pub category: Category,
+    /// In cents.
+    pub price: Cents,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allergens: Vec<Allergen>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dietary: Vec<DietaryTag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<ModifierEntry>,
+    /// Left out, the item follows its category: breakfasts until 11:00,
+    /// everything else all day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available: Option<WindowEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stock: Option<u32>,
+    /// How long the kitchen takes to make one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prep_minutes: Option<u64>,
+    /// Instead of `prep_minutes`, for a prep time that is not a whole
+    /// number of minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prep_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModifierEntry {
+    pub name: String,
+    #[serde(default)]
+    pub price_delta: i64,
+}
+
+/// Times are written "HH:MM".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowEntry {
+    pub from: String,
+    pub until: String,
+}
+
+impl ConfigFile for Menu {
+    type Schema = MenuFile;
+
+    fn from_schema(schema: MenuFile, spans: &Spans) -> Result<Menu, ConfigError> {
+        let mut menu = Menu::new();
+        let mut seen = BTreeSet::new();
+        for (index, entry) in schema.items.into_iter().enumerate() {
+            let at = |field: &str| format!("items.{}.{}", index, field);
+            if entry.name.trim().is_empty() {
+                return Err(ConfigError::at(
+                    spans,
+                    &at("name"),
+                    "menu items need a name",
+                ));
+            }
+            if !seen.insert(entry.name.to_lowercase()) {
+                return Err(ConfigError::at(
+                    spans,
+                    &at("name"),
+                    format!("{} is on the menu twice", entry.name),
+                ));
+            }
+
+            let mut item = MenuItem::new(&entry.name, entry.category, entry.price);
+            item.description = entry.description;
+            item.allergens = entry.allergens;
+            item.dietary = entry.dietary;
+            item.stock = entry.stock;
+            item.prep_time = match (entry.prep_minutes, entry.prep_seconds) {
+                (Some(_), Some(_)) => {
+                    return Err(ConfigError::at(
+                        spans,
+                        &at("prep_seconds"),
+                        format!("{} has both prep_minutes and prep_seconds", entry.name),
+                    ));
+                }
+                (Some(minutes), None) => {
+                    let secs = minutes.checked_mul(60).ok_or_else(|| {
+                        ConfigError::at(
+                            spans,
+                            &at("prep_minutes"),
+                            format!("{} minutes is too long to prep anything", minutes),
+                        )
+                    })?;
+                    Some(Duration::from_secs(secs))
+                }
+                (None, seconds) => seconds.map(Duration::from_secs),
+            };
+
+            let mut modifiers = BTreeSet::new();
+            for (position, modifier) in entry.modifiers.into_iter().enumerate() {
+                if !modifiers.insert(modifier.name.to_lowercase()) {
+                    return Err(ConfigError::at(
+                        spans,
+                        &at(&format!("modifiers.{}.name", position)),
+                        format!("{} lists the modifier {} twice", entry.name, modifier.name),
+                    ));
+                }
+                item.modifiers
+                    .push(Modifier::priced(&modifier.name, modifier.price_delta));
+            }
+
+            if let Some(window) = entry.available {
+                item.available = Some(Window::new(
+                    parse_time(spans, &at("available.from"), &window.from)?,
+                    parse_time(spans, &at("available.until"), &window.until)?,
+                ));
+            }
+            menu.add(item);
+        }
+        Ok(menu)
+    }
+
+    fn to_schema(&self) -> MenuFile {
+        let items = self
+            .items()
+            .map(|item| MenuItemEntry {
+                name: item.name.clone(),
+                category: item.category,
+                price: item.price,
+                description: item.description.clone(),
+                allergens: item.allergens.clone(),
+                dietary: item.dietary.clone(),
+                modifiers: item
+                    .modifiers
+                    .iter()
+                    .map(|m| ModifierEntry {
+                        name: m.name.clone(),
+                        price_delta: m.price_delta,
+                    })
+                    .collect(),
+                available: item.available.map(|window| WindowEntry {
+                    from: window.from.to_string(),
+                    until: window.until.to_string(),
+                }),
+                stock: item.stock,
+                prep_minutes: item
+                    .prep_time
+                    .map(|prep| prep.as_secs())
+                    .filter(|secs| secs % 60 == 0)
+                    .map(|secs| secs / 60),
+                prep_seconds: item
+                    .prep_time
+                    .map(|prep| prep.as_secs())
+                    .filter(|secs| secs % 60 != 0),
+            })
+            .collect();
+        MenuFile { items }
+    }
+}
+
+fn parse_time(spans: &Spans, path: &str, text: &str) -> Result<TimeOfDay, ConfigError> {
+    text.parse()
+        .map_err(|message| ConfigError::at(spans, path, message))
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ConfigError, ConfigFile, Spans};
+use crate::menu::{Season, SeasonalProduce};
+
+/// One key per season, e.g. `winter = "oranges"`. Missing seasons keep the
+/// house default.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProduceFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spring: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autumn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winter: Option<String>,
+}
+
+impl ConfigFile for SeasonalProduce {
+    type Schema = ProduceFile;
+
+    fn from_schema(schema: ProduceFile, spans: &Spans) -> Result<SeasonalProduce, ConfigError> {
+        let mut produce = SeasonalProduce::new();
+        for (season, fruit) in [
+            (Season::Spring, schema.spring),
+            (Season::Summer, schema.summer),
+            (Season::Autumn, schema.autumn),
+            (Season::Winter, schema.winter),
+        ] {
+            if let Some(fruit) = fruit {
+                if fruit.trim().is_empty() {
+                    return Err(ConfigError::at(
+                        spans,
+                        &season.to_string(),
+                        format!("the {} fruit cannot be empty", season),
+                    ));
+                }
+                produce = produce.set(season, &fruit);
+            }
+        }
+        Ok(produce)
+    }
+
+    fn to_schema(&self) -> ProduceFile {
+        let mut file = ProduceFile::default();
+        for (season, fruit) in self.entries() {
+            let slot = match season {
+                Season::Spring => &mut file.spring,
+                Season::Summer => &mut file.summer,
+                Season::Autumn => &mut file.autumn,
+                Season::Winter => &mut file.winter,
+            };
+            *slot = Some(String::from(fruit));
+        }
+        file
+    }
+}
@@ -0,0 +1,124 @@
+// Where each value in a configuration file starts, so errors found after
+// parsing can point at the right line. The TOML deserializer hands out
+// spans; serde_json does not, so JSON files have none and their errors
+// carry no line rather than a guessed one.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use toml::Spanned;
+
+use super::line_and_column;
+
+/// 1-based lines and columns by dotted path, e.g. `tables.2.seats` for the
+/// seats of the third table.
+#[derive(Debug, Clone, Default)]
+pub struct Spans {
+    starts: BTreeMap<String, (usize, usize)>,
+}
+
+impl Spans {
+    /// No positions at all.
+    pub fn none() -> Spans {
+        Spans::default()
+    }
+
+    pub fn of_toml(source: &str) -> Spans {
+        let mut spans = Spans::none();
+        if let Ok(tree) = toml::from_str::<Tree>(source) {
+            spans.collect(source, "", &tree);
+        }
+        spans
+    }
+
+    /// Line and column of the value at `path`, if known.
+    pub fn at(&self, path: &str) -> Option<(usize, usize)> {
+        self.starts.get(path).copied()
+    }
+
+    fn collect(&mut self, source: &str, prefix: &str, tree: &Tree) {
+        let mut visit = |key: String, child: &Spanned<Tree>| {
+            let path = if prefix.is_empty() {
+                key
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            self.starts
+                .insert(path.clone(), line_and_column(source, child.span().start));
+            self.collect(source, &path, child.get_ref());
+        };
+        match tree {
+            Tree::Value => {}
+            Tree::Table(entries) => {
+                for (key, child) in entries {
+                    visit(key.clone(), child);
+                }
+            }
+            Tree::Array(elements) => {
+                for (index, child) in elements.iter().enumerate() {
+                    visit(index.to_string(), child);
+                }
+            }
+        }
+    }
+}
+
+// The shape of a TOML document with a span on every value.
+enum Tree {
+    Value,
+    Table(Vec<(String, Spanned<Tree>)>),
+    Array(Vec<Spanned<Tree>>),
+}
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Tree, D::Error> {
+        deserializer.deserialize_any(TreeVisitor)
+    }
+}
+
+struct TreeVisitor;
+
+impl<'de> Visitor<'de> for TreeVisitor {
+    type Value = Tree;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "any TOML value")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Tree, E> {
+        Ok(Tree::Value)
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Tree, E> {
+        Ok(Tree::Value)
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Tree, E> {
+        Ok(Tree::Value)
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Tree, E> {
+        Ok(Tree::Value)
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<Tree, E> {
+        Ok(Tree::Value)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tree, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Tree::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Tree, A::Error> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = map.next_entry()? {
+            entries.push((key, value));
+        }
+        Ok(Tree::Table(entries))
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use super::loyalty::LoyaltyProgram;
+use super::profile::{CustomerId, Profile, Visit};
+use crate::billing::{Check, CheckId, GatewayError, Tender, TransactionId};
+use crate::serving::{Cents, Order, OrderId};
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CustomerError {
+    UnknownCustomer(CustomerId),
+    /// Points are only earned once the check is paid in full.
+    CheckNotSettled(CheckId),
+    AlreadyRecorded {
+        order: OrderId,
+        check: CheckId,
+    },
+}
+
+impl fmt::Display for CustomerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomerError::UnknownCustomer(id) => write!(f, "{} does not exist", id),
+            CustomerError::CheckNotSettled(check) => write!(f, "{} is not paid yet", check),
+            CustomerError::AlreadyRecorded { order, check } => {
+                write!(f, "{} on {} already earned points", check, order)
+            }
+        }
+    }
+}
+
+impl Error for CustomerError {}
+
+// Points taken for one payment, kept so a refund can give them back.
+#[derive(Debug, Clone)]
+struct Redemption {
+    customer: CustomerId,
+    points: u64,
+    refunded: u64,
+}
+
+/// Everyone who signed up for the loyalty programme.
+#[derive(Debug, Clone, Default)]
+pub struct CustomerBook {
+    profiles: BTreeMap<CustomerId, Profile>,
+    program: LoyaltyProgram,
+    redemptions: BTreeMap<String, Redemption>,
+    next_id: u32,
+    next_transaction: u32,
+}
+
+impl CustomerBook {
+    pub fn new(program: LoyaltyProgram) -> CustomerBook {
+        CustomerBook {
+            program,
+            ..CustomerBook::default()
+        }
+    }
+
+    pub fn program(&self) -> &LoyaltyProgram {
+        &self.program
+    }
+
+    /// Signs up a new member. They start with no visits and no points,
+    /// whatever `profile` came in with.
+    pub fn add(&mut self, mut profile: Profile) -> CustomerId {
+        profile.visits.clear();
+        profile.points = 0;
+        self.next_id += 1;
+        let id = CustomerId(self.next_id);
+        self.profiles.insert(id, profile);
+        id
+    }
+
+    pub fn get(&self, id: CustomerId) -> Result<&Profile, CustomerError> {
+        self.profiles
+            .get(&id)
+            .ok_or(CustomerError::UnknownCustomer(id))
+    }
+
+    pub fn get_mut(&mut self, id: CustomerId) -> Result<&mut Profile, CustomerError> {
+        self.profiles
+            .get_mut(&id)
+            .ok_or(CustomerError::UnknownCustomer(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CustomerId, &Profile)> {
+        self.profiles.iter().map(|(id, profile)| (*id, profile))
+    }
+
+    /// Looks a guest up by name or contact, ignoring case.
+    pub fn find(&self, query: &str) -> Option<CustomerId> {
+        self.iter()
+            .find(|(_, p)| {
+                p.name.eq_ignore_ascii_case(query)
+                    || p.contact
+                        .as_deref()
+                        .is_some_and(|c| c.eq_ignore_ascii_case(query))
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// Credits the guest for a paid check. Whatever was paid with points
+    /// does not earn more points, and neither does the tip.
+    pub fn record_visit(
+        &mut self,
+        customer: CustomerId,
+        order: &Order,
+        check: &Check,
+        now: Timestamp,
+    ) -> Result<Visit, CustomerError> {
+        if !check.is_settled() {
+            return Err(CustomerError::CheckNotSettled(check.id));
+        }
+        let recorded = self
+            .profiles
+            .values()
+            .flat_map(|p| p.visits.iter())
+            .any(|v| v.order == order.id && v.check == check.id);
+        if recorded {
+            return Err(CustomerError::AlreadyRecorded {
+                order: order.id,
+                check: check.id,
+            });
+        }
+
+        let paid: Cents = check
+            .payments
+            .iter()
+            .filter(|p| !matches!(p.tender, Tender::Points { .. }))
+            .map(|p| p.applied - p.refunded)
+            .sum();
+        let spent = paid.min(check.subtotal);
+        let visit = Visit {
+            at: now,
+            order: order.id,
+            check: check.id,
+            spent,
+            points: self.program.points_for(spent),
+            items: order.items().iter().map(|i| i.item.clone()).collect(),
+        };
+
+        let profile = self.get_mut(customer)?;
+        profile.points += visit.points;
+        profile.visits.push(visit.clone());
+        Ok(visit)
+    }
+
+    /// Takes the points for `amount` off the guest's balance.
+    pub(crate) fn redeem(
+        &mut self,
+        customer: CustomerId,
+        amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        let program = self.program;
+        let profile = self
+            .profiles
+            .get_mut(&customer)
+            .ok_or(GatewayError::UnknownCustomer(customer))?;
+        let needed = program.points_needed(amount);
+        if profile.points < program.minimum_redemption || profile.points < needed {
+            return Err(GatewayError::InsufficientPoints {
+                customer,
+                balance: profile.points,
+                needed: needed.max(program.minimum_redemption),
+            });
+        }
+        profile.points -= needed;
+
+        let id = self.next_transaction();
+        self.redemptions.insert(
+            id.clone(),
+            Redemption {
+                customer,
+                points: needed,
+                refunded: 0,
+            },
+        );
+        Ok(TransactionId(id))
+    }
+
+    /// Gives points back for a refunded redemption. `None` when the
+    /// transaction was not a redemption at all.
+    pub(crate) fn refund_redemption(
+        &mut self,
+        transaction: &TransactionId,
+        amount: Cents,
+    ) -> Option<Result<TransactionId, GatewayError>> {
+        let redemption = self.redemptions.get_mut(&transaction.0)?;
+        let profile = match self.profiles.get_mut(&redemption.customer) {
+            Some(profile) => profile,
+            None => return Some(Err(GatewayError::UnknownCustomer(redemption.customer))),
+        };
+        let points = self
+            .program
+            .points_needed(amount)
+            .min(redemption.points - redemption.refunded);
+        redemption.refunded += points;
+        profile.points += points;
+        Some(Ok(TransactionId(self.next_transaction())))
+    }
+
+    fn next_transaction(&mut self) -> String {
+        self.next_transaction += 1;
+        format!("pts-{}", self.next_transaction)
+    }
+}
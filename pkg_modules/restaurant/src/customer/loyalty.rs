@@ -0,0 +1,85 @@
+use super::book::CustomerBook;
+use super::profile::CustomerId;
+use crate::billing::{GatewayError, PaymentGateway, TransactionId};
+use crate::serving::Cents;
+
+/// How points are earned and what they are worth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoyaltyProgram {
+    /// Points for every whole dollar paid.
+    pub points_per_dollar: u64,
+    /// What one point takes off a check.
+    pub point_value: Cents,
+    /// Fewer points than this cannot be redeemed at all.
+    pub minimum_redemption: u64,
+}
+
+impl Default for LoyaltyProgram {
+    fn default() -> LoyaltyProgram {
+        LoyaltyProgram {
+            points_per_dollar: 1,
+            point_value: 5,
+            minimum_redemption: 100,
+        }
+    }
+}
+
+impl LoyaltyProgram {
+    pub fn points_for(&self, spent: Cents) -> u64 {
+        spent / 100 * self.points_per_dollar
+    }
+
+    /// Points needed to pay `amount`, rounding up to a whole point.
+    pub fn points_needed(&self, amount: Cents) -> u64 {
+        amount.div_ceil(self.point_value.max(1))
+    }
+
+    pub fn value_of(&self, points: u64) -> Cents {
+        points * self.point_value
+    }
+}
+
+/// Puts the loyalty programme in front of the real gateway: points are taken
+/// off the guest's balance here, and everything else goes through.
+pub struct LoyaltyGateway<'a> {
+    customers: &'a mut CustomerBook,
+    payments: &'a mut dyn PaymentGateway,
+}
+
+impl<'a> LoyaltyGateway<'a> {
+    pub fn new(customers: &'a mut CustomerBook, payments: &'a mut dyn PaymentGateway) -> Self {
+        LoyaltyGateway {
+            customers,
+            payments,
+        }
+    }
+}
+
+impl PaymentGateway for LoyaltyGateway<'_> {
+    fn charge_card(&mut self, last4: &str, amount: Cents) -> Result<TransactionId, GatewayError> {
+        self.payments.charge_card(last4, amount)
+    }
+
+    fn redeem_voucher(&mut self, code: &str, amount: Cents) -> Result<TransactionId, GatewayError> {
+        self.payments.redeem_voucher(code, amount)
+    }
+
+    fn redeem_points(
+        &mut self,
+        customer: CustomerId,
+        amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        self.customers.redeem(customer, amount)
+    }
+
+    fn refund(
+        &mut self,
+        transaction: &TransactionId,
+        amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        match self.customers.refund_redemption(transaction, amount) {
+            Some(refunded) => refunded,
+            None => self.payments.refund(transaction, amount),
+        }
+    }
+}
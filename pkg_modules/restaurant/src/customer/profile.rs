@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::billing::CheckId;
+use crate::menu::{Allergen, DietaryTag, Menu};
+use crate::serving::{Cents, LineItem, OrderId};
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CustomerId(pub u32);
+
+impl fmt::Display for CustomerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "customer {}", self.0)
+    }
+}
+
+/// An order the guest likes to have again, under a name they picked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Favourite {
+    pub name: String,
+    pub items: Vec<LineItem>,
+}
+
+/// One paid check that counted towards the loyalty programme.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Visit {
+    pub at: Timestamp,
+    pub order: OrderId,
+    pub check: CheckId,
+    /// What counted towards points: money paid, not points redeemed.
+    pub spent: Cents,
+    pub points: u64,
+    /// The dishes on the order, for working out what they usually have.
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Phone number or email, whatever they gave us.
+    pub contact: Option<String>,
+    pub allergens: Vec<Allergen>,
+    /// Diets every dish has to suit, e.g. vegan.
+    pub diet: Vec<DietaryTag>,
+    pub favourites: Vec<Favourite>,
+    pub(crate) visits: Vec<Visit>,
+    pub(crate) points: u64,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile {
+            name: String::from(name),
+            ..Profile::default()
+        }
+    }
+
+    pub fn contact(mut self, contact: &str) -> Profile {
+        self.contact = Some(String::from(contact));
+        self
+    }
+
+    pub fn allergic_to(mut self, allergen: Allergen) -> Profile {
+        if !self.allergens.contains(&allergen) {
+            self.allergens.push(allergen);
+        }
+        self
+    }
+
+    pub fn follows(mut self, diet: DietaryTag) -> Profile {
+        if !self.diet.contains(&diet) {
+            self.diet.push(diet);
+        }
+        self
+    }
+
+    /// Saves an order under `name`, replacing any favourite of that name.
+    pub fn add_favourite(&mut self, name: &str, items: Vec<LineItem>) {
+        self.favourites
+            .retain(|f| !f.name.eq_ignore_ascii_case(name));
+        self.favourites.push(Favourite {
+            name: String::from(name),
+            items,
+        });
+    }
+
+    pub fn favourite(&self, name: &str) -> Option<&Favourite> {
+        self.favourites
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn visits(&self) -> &[Visit] {
+        &self.visits
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    pub fn lifetime_spend(&self) -> Cents {
+        self.visits.iter().map(|v| v.spent).sum()
+    }
+
+    /// The dish they have ordered most often, if they have been before.
+    pub fn usual(&self) -> Option<&str> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for item in self.visits.iter().flat_map(|v| v.items.iter()) {
+            *counts.entry(item.as_str()).or_insert(0) += 1;
+        }
+        // max_by_key keeps the last of equals; reverse so ties go to the
+        // alphabetically first dish
+        counts
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(item, _)| item)
+    }
+
+    /// Everything on `items` the guest should be told about before it goes
+    /// to the kitchen.
+    pub fn warnings(&self, items: &[LineItem], menu: &Menu) -> Vec<DietaryWarning> {
+        let mut warnings = Vec::new();
+        for (line, item) in items.iter().enumerate() {
+            let dish = match menu.item(&item.item) {
+                Ok(dish) => dish,
+                Err(_) => {
+                    if !self.allergens.is_empty() || !self.diet.is_empty() {
+                        warnings.push(DietaryWarning::Unknown {
+                            line,
+                            item: item.item.clone(),
+                        });
+                    }
+                    continue;
+                }
+            };
+            for allergen in self.allergens.iter().filter(|a| dish.allergens.contains(a)) {
+                warnings.push(DietaryWarning::Allergen {
+                    line,
+                    item: dish.name.clone(),
+                    allergen: *allergen,
+                });
+            }
+            for diet in self.diet.iter().filter(|d| !dish.dietary.contains(d)) {
+                warnings.push(DietaryWarning::NotSuitable {
+                    line,
+                    item: dish.name.clone(),
+                    diet: *diet,
+                });
+            }
+        }
+        warnings
+    }
+}
+
+/// Something on an order that clashes with a guest's profile. `line` is the
+/// index into the order's items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DietaryWarning {
+    Allergen {
+        line: usize,
+        item: String,
+        allergen: Allergen,
+    },
+    NotSuitable {
+        line: usize,
+        item: String,
+        diet: DietaryTag,
+    },
+    /// Not on the menu, so nobody can vouch for what is in it.
+    Unknown { line: usize, item: String },
+}
+
+impl fmt::Display for DietaryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DietaryWarning::Allergen { item, allergen, .. } => {
+                write!(f, "{} contains {}", item, allergen)
+            }
+            DietaryWarning::NotSuitable { item, diet, .. } => {
+                write!(f, "{} is not {}", item, diet)
+            }
+            DietaryWarning::Unknown { item, .. } => {
+                write!(f, "{} is not on the menu, check with the kitchen", item)
+            }
+        }
+    }
+}
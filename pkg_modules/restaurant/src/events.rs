@@ -0,0 +1,488 @@
+// Everything that happens during a shift, in the order it happened. The log
+// is append-only and is stored one JSON object per line, so a crashed shift
+// loses at most the line that was being written.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::billing::{CheckId, Discount, PaymentId, Share, Tender, TransactionId};
+use crate::customer::{CustomerId, Profile};
+use crate::delivery::{CourierId, DeliveryId, Fulfilment};
+use crate::hosting::{Party, PartyId, ReservationId, ReservationRequest, TableNumber};
+use crate::kitchen::TicketId;
+use crate::serving::{Cents, LineItem, OrderId};
+use crate::staff::{Role, StaffId};
+use crate::time::Timestamp;
+
+/// One operation and what came out of it. Ids are recorded as well as
+/// inputs, so a replay can tell when it has drifted from the original.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    PartyJoined {
+        party: PartyId,
+        guest: Party,
+    },
+    PartyLeft {
+        party: PartyId,
+    },
+    TableSeated {
+        party: PartyId,
+        tables: Vec<TableNumber>,
+    },
+    TableVacated {
+        party: PartyId,
+        tables: Vec<TableNumber>,
+    },
+    TableBussed {
+        table: TableNumber,
+    },
+    ReservationBooked {
+        reservation: ReservationId,
+        request: ReservationRequest,
+    },
+    ReservationHeld {
+        reservation: ReservationId,
+    },
+    ReservationCheckedIn {
+        reservation: ReservationId,
+        party: PartyId,
+    },
+    ReservationCancelled {
+        reservation: ReservationId,
+    },
+    ReservationNoShow {
+        reservation: ReservationId,
+    },
+    OrderTaken {
+        order: OrderId,
+        table: TableNumber,
+        items: Vec<LineItem>,
+    },
+    ItemAdded {
+        order: OrderId,
+        line: usize,
+        item: LineItem,
+    },
+    ItemShared {
+        order: OrderId,
+        line: usize,
+        shares: Vec<Share>,
+    },
+    OrderSentToKitchen {
+        order: OrderId,
+    },
+    OrderFired {
+        order: OrderId,
+        tickets: Vec<TicketId>,
+    },
+    /// Some lines of an order were fired on their own, as a course.
+    CourseFired {
+        order: OrderId,
+        lines: Vec<usize>,
+        tickets: Vec<TicketId>,
+    },
+    TicketBumped {
+        ticket: TicketId,
+    },
+    TicketRecalled {
+        ticket: TicketId,
+    },
+    TicketRefired {
+        ticket: TicketId,
+        refire: TicketId,
+        reason: String,
+    },
+    OrderReady {
+        order: OrderId,
+    },
+    OrderServed {
+        order: OrderId,
+    },
+    OrderVoided {
+        order: OrderId,
+    },
+    BillOpened {
+        order: OrderId,
+    },
+    BillSplitBySeat {
+        order: OrderId,
+    },
+    BillSplitEvenly {
+        order: OrderId,
+        ways: usize,
+    },
+    CheckOpened {
+        order: OrderId,
+        check: CheckId,
+        label: String,
+    },
+    ChecksMerged {
+        order: OrderId,
+        from: CheckId,
+        into: CheckId,
+    },
+    LineTransferred {
+        order: OrderId,
+        from: CheckId,
+        line: usize,
+        to: CheckId,
+    },
+    TipAdded {
+        order: OrderId,
+        check: CheckId,
+        amount: Cents,
+    },
+    PaymentCaptured {
+        order: OrderId,
+        check: CheckId,
+        payment: PaymentId,
+        tender: Tender,
+        transaction: Option<TransactionId>,
+    },
+    PaymentRefunded {
+        order: OrderId,
+        payment: PaymentId,
+        amount: Cents,
+        transaction: Option<TransactionId>,
+    },
+    StockReceived {
+        ingredient: String,
+        quantity: u32,
+    },
+    DeliveryOpened {
+        delivery: DeliveryId,
+        order: OrderId,
+        customer: String,
+        fulfilment: Fulfilment,
+        promised: Timestamp,
+    },
+    DeliveryPacked {
+        delivery: DeliveryId,
+    },
+    CourierAssigned {
+        delivery: DeliveryId,
+        courier: CourierId,
+    },
+    OrderHandedOff {
+        order: OrderId,
+        delivery: DeliveryId,
+        courier: Option<CourierId>,
+    },
+    DeliveryCompleted {
+        delivery: DeliveryId,
+    },
+    DeliveryCancelled {
+        delivery: DeliveryId,
+    },
+    CustomerJoined {
+        customer: CustomerId,
+        profile: Profile,
+    },
+    VisitRecorded {
+        customer: CustomerId,
+        order: OrderId,
+        check: CheckId,
+        points: u64,
+    },
+    StaffHired {
+        staff: StaffId,
+        name: String,
+        role: Role,
+    },
+    StaffClockedIn {
+        staff: StaffId,
+    },
+    StaffClockedOut {
+        staff: StaffId,
+    },
+    SectionAssigned {
+        staff: StaffId,
+        section: Option<String>,
+    },
+    ItemComped {
+        order: OrderId,
+        line: usize,
+        by: StaffId,
+    },
+    DiscountApplied {
+        order: OrderId,
+        discount: Discount,
+        by: StaffId,
+    },
+    /// A paid order was voided once everything on it had been refunded.
+    CheckVoided {
+        order: OrderId,
+        by: StaffId,
+    },
+}
+
+impl Event {
+    /// The `type` tag the event is stored under, e.g. "order_taken".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::PartyJoined { .. } => "party_joined",
+            Event::PartyLeft { .. } => "party_left",
+            Event::TableSeated { .. } => "table_seated",
+            Event::TableVacated { .. } => "table_vacated",
+            Event::TableBussed { .. } => "table_bussed",
+            Event::ReservationBooked { .. } => "reservation_booked",
+            Event::ReservationHeld { .. } => "reservation_held",
+            Event::ReservationCheckedIn { .. } => "reservation_checked_in",
+            Event::ReservationCancelled { .. } => "reservation_cancelled",
+            Event::ReservationNoShow { .. } => "reservation_no_show",
+            Event::OrderTaken { .. } => "order_taken",
+            Event::ItemAdded { .. } => "item_added",
+            Event::ItemShared { .. } => "item_shared",
+            Event::OrderSentToKitchen { .. } => "order_sent_to_kitchen",
+            Event::OrderFired { .. } => "order_fired",
+            Event::CourseFired { .. } => "course_fired",
+            Event::TicketBumped { .. } => "ticket_bumped",
+            Event::TicketRecalled { .. } => "ticket_recalled",
+            Event::TicketRefired { .. } => "ticket_refired",
+            Event::OrderReady { .. } => "order_ready",
+            Event::OrderServed { .. } => "order_served",
+            Event::OrderVoided { .. } => "order_voided",
+            Event::BillOpened { .. } => "bill_opened",
+            Event::BillSplitBySeat { .. } => "bill_split_by_seat",
+            Event::BillSplitEvenly { .. } => "bill_split_evenly",
+            Event::CheckOpened { .. } => "check_opened",
+            Event::ChecksMerged { .. } => "checks_merged",
+            Event::LineTransferred { .. } => "line_transferred",
+            Event::TipAdded { .. } => "tip_added",
+            Event::PaymentCaptured { .. } => "payment_captured",
+            Event::PaymentRefunded { .. } => "payment_refunded",
+            Event::StockReceived { .. } => "stock_received",
+            Event::DeliveryOpened { .. } => "delivery_opened",
+            Event::DeliveryPacked { .. } => "delivery_packed",
+            Event::CourierAssigned { .. } => "courier_assigned",
+            Event::OrderHandedOff { .. } => "order_handed_off",
+            Event::DeliveryCompleted { .. } => "delivery_completed",
+            Event::DeliveryCancelled { .. } => "delivery_cancelled",
+            Event::CustomerJoined { .. } => "customer_joined",
+            Event::VisitRecorded { .. } => "visit_recorded",
+            Event::StaffHired { .. } => "staff_hired",
+            Event::StaffClockedIn { .. } => "staff_clocked_in",
+            Event::StaffClockedOut { .. } => "staff_clocked_out",
+            Event::SectionAssigned { .. } => "section_assigned",
+            Event::ItemComped { .. } => "item_comped",
+            Event::DiscountApplied { .. } => "discount_applied",
+            Event::CheckVoided { .. } => "check_voided",
+        }
+    }
+}
+
+/// An event as it sits in the log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// 1-based position in the log.
+    pub seq: u64,
+    pub at: Timestamp,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EventError {
+    Io(io::Error),
+    /// A line of the log that is not a record. `line` is 1-based.
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    /// Records must be numbered 1, 2, 3, ... with no gaps.
+    OutOfSequence {
+        expected: u64,
+        found: u64,
+    },
+    /// Replaying a record failed or produced a different outcome, so the
+    /// log does not belong to the state it was replayed onto.
+    Replay {
+        seq: u64,
+        message: String,
+    },
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::Io(err) => write!(f, "{}", err),
+            EventError::Parse {
+                path,
+                line,
+                message,
+            } => {
+                if let Some(path) = path {
+                    write!(f, "{}:", path.display())?;
+                }
+                write!(f, "{}: {}", line, message)
+            }
+            EventError::OutOfSequence { expected, found } => {
+                write!(f, "expected event {} but found event {}", expected, found)
+            }
+            EventError::Replay { seq, message } => {
+                write!(f, "replaying event {}: {}", seq, message)
+            }
+        }
+    }
+}
+
+impl Error for EventError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EventError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for EventError {
+    fn from(err: io::Error) -> EventError {
+        EventError::Io(err)
+    }
+}
+
+/// The append-only log. Kept in memory, and also written through to a file
+/// when it was opened with `EventLog::open`.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    records: Vec<Record>,
+    file: Option<File>,
+}
+
+impl EventLog {
+    pub fn new() -> EventLog {
+        EventLog::default()
+    }
+
+    /// Reads the records already in `path`, creating the file if needed,
+    /// and appends every new record to it.
+    pub fn open(path: impl AsRef<Path>) -> Result<EventLog, EventError> {
+        let path = path.as_ref();
+        let mut log = if path.exists() {
+            let reader = BufReader::new(File::open(path)?);
+            EventLog::read_ndjson(reader).map_err(|err| match err {
+                EventError::Parse { line, message, .. } => EventError::Parse {
+                    path: Some(path.to_path_buf()),
+                    line,
+                    message,
+                },
+                err => err,
+            })?
+        } else {
+            EventLog::new()
+        };
+        log.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        Ok(log)
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Adds an event to the end of the log. If writing it to the file fails
+    /// the log is left as it was.
+    pub fn append(&mut self, at: Timestamp, event: Event) -> Result<&Record, EventError> {
+        let record = Record {
+            seq: self.records.len() as u64 + 1,
+            at,
+            event,
+        };
+        if let Some(file) = &mut self.file {
+            write_record(file, &record)?;
+        }
+        self.records.push(record);
+        Ok(self.records.last().expect("a record was just pushed"))
+    }
+
+    pub fn read_ndjson(reader: impl BufRead) -> Result<EventLog, EventError> {
+        let mut log = EventLog::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record = serde_json::from_str(&line).map_err(|err| EventError::Parse {
+                path: None,
+                line: index + 1,
+                message: err.to_string(),
+            })?;
+            let expected = log.records.len() as u64 + 1;
+            if record.seq != expected {
+                return Err(EventError::OutOfSequence {
+                    expected,
+                    found: record.seq,
+                });
+            }
+            log.records.push(record);
+        }
+        Ok(log)
+    }
+
+    /// A log holding `records`, which must be numbered from 1 without gaps.
+    pub fn from_records(records: Vec<Record>) -> Result<EventLog, EventError> {
+        for (index, record) in records.iter().enumerate() {
+            let expected = index as u64 + 1;
+            if record.seq != expected {
+                return Err(EventError::OutOfSequence {
+                    expected,
+                    found: record.seq,
+                });
+            }
+        }
+        Ok(EventLog {
+            records,
+            file: None,
+        })
+    }
+
+    pub fn write_ndjson(&self, mut writer: impl Write) -> Result<(), EventError> {
+        for record in &self.records {
+            write_record(&mut writer, record)?;
+        }
+        Ok(())
+    }
+
+    pub fn from_ndjson(text: &str) -> Result<EventLog, EventError> {
+        EventLog::read_ndjson(text.as_bytes())
+    }
+
+    pub fn to_ndjson(&self) -> String {
+        let mut out = Vec::new();
+        self.write_ndjson(&mut out)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("serde_json writes UTF-8")
+    }
+
+    /// Writes the whole log to `path`, replacing whatever was there.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), EventError> {
+        fs::write(path, self.to_ndjson())?;
+        Ok(())
+    }
+}
+
+// One write for the whole line, so a record is never half on disk because
+// it could not be serialized.
+fn write_record(writer: &mut impl Write, record: &Record) -> Result<(), EventError> {
+    let mut line = serde_json::to_vec(record).map_err(io::Error::from)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()?;
+    Ok(())
+}
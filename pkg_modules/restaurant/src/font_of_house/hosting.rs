@@ -1,6 +0,0 @@
-// public module
-pub mod hosting {
-    pub fn add_to_waitlist() {}
-
-    pub fn seat_at_table() {}
-}
\ No newline at end of file
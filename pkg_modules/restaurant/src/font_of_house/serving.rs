@@ -1,10 +0,0 @@
-// private module
-mod serving {
-    pub fn take_order() {}
-
-    // public
-    pub fn serve_order() {}
-
-    // private
-    fn take_payment() {}
-}
@@ -1,2 +1,4 @@
+pub mod billing;
+pub mod delivery;
 pub mod hosting;
-pub mod serving;
\ No newline at end of file
+pub mod serving;
@@ -0,0 +1,48 @@
+// Everything between the food going out and the table being paid up: bills
+// and checks, pricing, splitting and taking payment. Like serving it is
+// re-exported from the crate root.
+
+mod payment;
+mod pricing;
+mod split;
+
+pub use self::payment::{
+    Bill, BillLine, Check, CheckId, GatewayError, InMemoryGateway, Payment, PaymentError,
+    PaymentGateway, PaymentId, Receipt, Refund, Surcharge, Tender, TransactionId,
+};
+pub use self::pricing::{
+    Adjustment, AdjustmentKind, AutoGratuity, Breakdown, Discount, DiscountAmount, DiscountScope,
+    Gratuity, HappyHour, PricedLine, PricingRules, Rate, TaxLine,
+};
+pub use self::split::{allocate, Share};
+// money is counted the same way on the menu, on orders and on bills
+pub use crate::serving::Cents;
+
+use crate::serving::{OrderBook, OrderStatus};
+use crate::time::Timestamp;
+
+// Closing a check is something the POS has to be able to do.
+// Once the last check on the bill is settled the order is marked paid.
+pub fn take_payment(
+    orders: &mut OrderBook,
+    bill: &mut Bill,
+    check: CheckId,
+    tender: Tender,
+    gateway: &mut dyn PaymentGateway,
+    now: Timestamp,
+) -> Result<Receipt, PaymentError> {
+    let order = orders
+        .get_mut(bill.order)
+        .map_err(|_| PaymentError::OrderNotPayable(bill.order))?;
+    if !order.status().can_become(OrderStatus::Paid) {
+        return Err(PaymentError::OrderNotPayable(order.id));
+    }
+
+    let receipt = bill.pay(check, tender, gateway)?;
+    if bill.is_settled() {
+        order
+            .mark_paid(now)
+            .expect("the order was checked to be payable");
+    }
+    Ok(receipt)
+}
@@ -0,0 +1,909 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::pricing::{describe, Breakdown};
+use super::split::{allocate, fraction, Share};
+use crate::customer::CustomerId;
+use crate::menu::Category;
+use crate::serving::{Cents, LineItem, Order, OrderId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CheckId(pub u32);
+
+impl fmt::Display for CheckId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "check {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PaymentId(pub u32);
+
+impl fmt::Display for PaymentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "payment {}", self.0)
+    }
+}
+
+/// The gateway's reference for a charge, redemption or refund.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TransactionId(pub String);
+
+/// How the guest is paying.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Tender {
+    Cash(Cents),
+    Card {
+        amount: Cents,
+        last4: String,
+    },
+    Voucher {
+        code: String,
+        amount: Cents,
+    },
+    /// Paid with loyalty points worth `amount`.
+    Points {
+        customer: CustomerId,
+        amount: Cents,
+    },
+}
+
+impl Tender {
+    pub fn amount(&self) -> Cents {
+        match self {
+            Tender::Cash(amount) => *amount,
+            Tender::Card { amount, .. }
+            | Tender::Voucher { amount, .. }
+            | Tender::Points { amount, .. } => *amount,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Tender::Cash(_) => "cash",
+            Tender::Card { .. } => "card",
+            Tender::Voucher { .. } => "voucher",
+            Tender::Points { .. } => "points",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GatewayError {
+    Declined(String),
+    UnknownVoucher(String),
+    InsufficientVoucherBalance {
+        code: String,
+        balance: Cents,
+    },
+    UnknownTransaction(TransactionId),
+    UnknownCustomer(CustomerId),
+    InsufficientPoints {
+        customer: CustomerId,
+        balance: u64,
+        needed: u64,
+    },
+    Unavailable,
+}
+
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatewayError::Declined(reason) => write!(f, "declined: {}", reason),
+            GatewayError::UnknownVoucher(code) => write!(f, "voucher {} does not exist", code),
+            GatewayError::InsufficientVoucherBalance { code, balance } => {
+                write!(f, "voucher {} only has {} cents left", code, balance)
+            }
+            GatewayError::UnknownTransaction(id) => write!(f, "no transaction {}", id.0),
+            GatewayError::UnknownCustomer(id) => write!(f, "{} is not a loyalty member", id),
+            GatewayError::InsufficientPoints {
+                customer,
+                balance,
+                needed,
+            } => write!(
+                f,
+                "{} has {} points but needs {}",
+                customer, balance, needed
+            ),
+            GatewayError::Unavailable => write!(f, "the payment gateway is unavailable"),
+        }
+    }
+}
+
+impl Error for GatewayError {}
+
+/// Whatever actually moves the money: a card terminal, a voucher service, or
+/// the in-memory fake below.
+pub trait PaymentGateway {
+    fn charge_card(&mut self, last4: &str, amount: Cents) -> Result<TransactionId, GatewayError>;
+
+    fn redeem_voucher(&mut self, code: &str, amount: Cents) -> Result<TransactionId, GatewayError>;
+
+    /// Pays with loyalty points. Most gateways know nothing about them;
+    /// `customer::LoyaltyGateway` does.
+    fn redeem_points(
+        &mut self,
+        _customer: CustomerId,
+        _amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        Err(GatewayError::Declined(String::from(
+            "loyalty points are not accepted",
+        )))
+    }
+
+    /// Gives back (part of) an earlier card charge or voucher redemption.
+    fn refund(
+        &mut self,
+        transaction: &TransactionId,
+        amount: Cents,
+    ) -> Result<TransactionId, GatewayError>;
+}
+
+/// A gateway that keeps everything in memory, for tests and demos.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryGateway {
+    vouchers: BTreeMap<String, Cents>,
+    declined_cards: Vec<String>,
+    // transaction id -> (voucher code if any, amount still refundable)
+    transactions: BTreeMap<String, (Option<String>, Cents)>,
+    next_id: u32,
+}
+
+impl InMemoryGateway {
+    pub fn new() -> InMemoryGateway {
+        InMemoryGateway::default()
+    }
+
+    pub fn add_voucher(&mut self, code: &str, balance: Cents) {
+        self.vouchers.insert(String::from(code), balance);
+    }
+
+    pub fn voucher_balance(&self, code: &str) -> Option<Cents> {
+        self.vouchers.get(code).copied()
+    }
+
+    /// Every charge on a card ending in `last4` will be declined.
+    pub fn decline_card(&mut self, last4: &str) {
+        self.declined_cards.push(String::from(last4));
+    }
+
+    /// Sum of all money taken and not refunded.
+    pub fn captured(&self) -> Cents {
+        self.transactions.values().map(|(_, amount)| amount).sum()
+    }
+
+    fn record(&mut self, voucher: Option<String>, amount: Cents) -> TransactionId {
+        self.next_id += 1;
+        let id = format!("tx-{}", self.next_id);
+        self.transactions.insert(id.clone(), (voucher, amount));
+        TransactionId(id)
+    }
+}
+
+impl PaymentGateway for InMemoryGateway {
+    fn charge_card(&mut self, last4: &str, amount: Cents) -> Result<TransactionId, GatewayError> {
+        if self.declined_cards.iter().any(|card| card == last4) {
+            return Err(GatewayError::Declined(format!("card ending {}", last4)));
+        }
+        Ok(self.record(None, amount))
+    }
+
+    fn redeem_voucher(&mut self, code: &str, amount: Cents) -> Result<TransactionId, GatewayError> {
+        let balance = self
+            .vouchers
+            .get_mut(code)
+            .ok_or_else(|| GatewayError::UnknownVoucher(String::from(code)))?;
+        if *balance < amount {
+            return Err(GatewayError::InsufficientVoucherBalance {
+                code: String::from(code),
+                balance: *balance,
+            });
+        }
+        *balance -= amount;
+        Ok(self.record(Some(String::from(code)), amount))
+    }
+
+    fn refund(
+        &mut self,
+        transaction: &TransactionId,
+        amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        let (voucher, refundable) = self
+            .transactions
+            .get_mut(&transaction.0)
+            .ok_or_else(|| GatewayError::UnknownTransaction(transaction.clone()))?;
+        if *refundable < amount {
+            return Err(GatewayError::Declined(format!(
+                "only {} cents can be refunded",
+                refundable
+            )));
+        }
+        *refundable -= amount;
+        if let Some(code) = voucher.clone() {
+            *self.vouchers.entry(code).or_insert(0) += amount;
+        }
+        // a refund is a transaction too, just not one that can be refunded
+        Ok(self.record(None, 0))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PaymentError {
+    UnknownCheck(CheckId),
+    UnknownPayment(PaymentId),
+    /// The order is not ready to be paid for, or already closed.
+    OrderNotPayable(OrderId),
+    /// Nothing is owed on this check any more.
+    AlreadySettled(CheckId),
+    /// Cards are charged for exactly what is put on them, so they cannot go
+    /// over the balance. Only cash gives change.
+    Overpayment {
+        check: CheckId,
+        balance: Cents,
+        tendered: Cents,
+    },
+    ZeroAmount,
+    /// Checks can only be re-split before anyone has paid.
+    SplitAfterPayment,
+    /// The bill cannot change under payments already taken against it.
+    PaymentsTaken(OrderId),
+    /// Lines can only be moved on or off checks nobody has paid on yet.
+    CheckHasPayments(CheckId),
+    NoSuchLine {
+        check: CheckId,
+        line: usize,
+    },
+    /// A check cannot be merged with itself or have lines moved onto itself.
+    SameCheck(CheckId),
+    InvalidSplit(usize),
+    RefundExceedsPayment {
+        payment: PaymentId,
+        refundable: Cents,
+        requested: Cents,
+    },
+    Gateway(GatewayError),
+}
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentError::UnknownCheck(check) => write!(f, "{} does not exist", check),
+            PaymentError::UnknownPayment(payment) => write!(f, "{} does not exist", payment),
+            PaymentError::OrderNotPayable(order) => write!(f, "{} cannot be paid now", order),
+            PaymentError::AlreadySettled(check) => write!(f, "{} is already settled", check),
+            PaymentError::Overpayment {
+                check,
+                balance,
+                tendered,
+            } => write!(
+                f,
+                "{} only has {} cents left, {} cents were tendered",
+                check, balance, tendered
+            ),
+            PaymentError::ZeroAmount => write!(f, "cannot pay nothing"),
+            PaymentError::SplitAfterPayment => {
+                write!(f, "the bill cannot be split once payments were taken")
+            }
+            PaymentError::PaymentsTaken(order) => {
+                write!(f, "{} already has payments against it", order)
+            }
+            PaymentError::CheckHasPayments(check) => {
+                write!(f, "{} already has payments against it", check)
+            }
+            PaymentError::NoSuchLine { check, line } => {
+                write!(f, "{} has no line {}", check, line)
+            }
+            PaymentError::SameCheck(check) => write!(f, "{} cannot be moved onto itself", check),
+            PaymentError::InvalidSplit(ways) => write!(f, "cannot split a bill {} ways", ways),
+            PaymentError::RefundExceedsPayment {
+                payment,
+                refundable,
+                requested,
+            } => write!(
+                f,
+                "{} only has {} cents refundable, {} were requested",
+                payment, refundable, requested
+            ),
+            PaymentError::Gateway(err) => write!(f, "payment gateway: {}", err),
+        }
+    }
+}
+
+impl Error for PaymentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PaymentError::Gateway(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<GatewayError> for PaymentError {
+    fn from(err: GatewayError) -> PaymentError {
+        PaymentError::Gateway(err)
+    }
+}
+
+/// One line of the itemised bill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillLine {
+    pub description: String,
+    pub seat: Option<u8>,
+    /// Seats splitting the line, as on the order.
+    pub shared: Vec<Share>,
+    pub quantity: u16,
+    pub amount: Cents,
+    /// The dish's category, when the bill was priced against a menu.
+    pub category: Option<Category>,
+    /// Set on tax and gratuity, which are added on top of the items.
+    pub surcharge: Option<Surcharge>,
+}
+
+/// What a line added on top of the items is for. Splitting by seat shares
+/// tax out over the seats that had the category it was charged on, and
+/// gratuity over every seat, in proportion to what each had.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Surcharge {
+    /// Tax on one category; `None` for items that are not on the menu.
+    Tax(Option<Category>),
+    Gratuity,
+}
+
+impl BillLine {
+    fn item(
+        item: &LineItem,
+        description: String,
+        category: Option<Category>,
+        amount: Cents,
+    ) -> BillLine {
+        BillLine {
+            description,
+            seat: item.seat,
+            shared: item.shared.clone(),
+            quantity: item.quantity,
+            amount,
+            category,
+            surcharge: None,
+        }
+    }
+
+    fn surcharge(description: String, surcharge: Surcharge, amount: Cents) -> BillLine {
+        BillLine {
+            description,
+            seat: None,
+            shared: Vec::new(),
+            quantity: 1,
+            amount,
+            category: None,
+            surcharge: Some(surcharge),
+        }
+    }
+
+    // Whether this line is one a share of `surcharge` is worked out on.
+    fn bears(&self, surcharge: Surcharge) -> bool {
+        match surcharge {
+            _ if self.surcharge.is_some() => false,
+            Surcharge::Tax(category) => self.category == category,
+            Surcharge::Gratuity => true,
+        }
+    }
+
+    // The line as it goes on each check when splitting by seat: whole on
+    // its own seat, or in pieces on the seats sharing it.
+    fn by_seat(&self) -> Vec<(Option<u8>, BillLine)> {
+        if self.shared.is_empty() {
+            return vec![(self.seat, self.clone())];
+        }
+        let parts: Vec<Cents> = self.shared.iter().map(|s| s.parts as Cents).collect();
+        let total: u32 = self.shared.iter().map(|s| s.parts).sum();
+        self.shared
+            .iter()
+            .zip(allocate(self.amount, &parts))
+            .map(|(share, amount)| {
+                let line = BillLine {
+                    description: format!("{} ({})", self.description, fraction(share.parts, total)),
+                    seat: Some(share.seat),
+                    shared: Vec::new(),
+                    amount,
+                    ..self.clone()
+                };
+                (Some(share.seat), line)
+            })
+            .collect()
+    }
+}
+
+/// A recorded payment against a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub id: PaymentId,
+    pub tender: Tender,
+    /// What went towards the check, i.e. the tender minus any change.
+    pub applied: Cents,
+    pub change: Cents,
+    pub transaction: Option<TransactionId>,
+    pub refunded: Cents,
+}
+
+/// What the guest walks away with after paying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub check: CheckId,
+    pub payment: PaymentId,
+    pub applied: Cents,
+    pub change: Cents,
+    pub balance: Cents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refund {
+    pub payment: PaymentId,
+    pub amount: Cents,
+    pub transaction: Option<TransactionId>,
+}
+
+/// A single check handed to one guest (or the whole table).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub id: CheckId,
+    pub label: String,
+    pub lines: Vec<BillLine>,
+    pub subtotal: Cents,
+    pub tip: Cents,
+    pub payments: Vec<Payment>,
+}
+
+impl Check {
+    fn new(id: u32, label: &str, lines: Vec<BillLine>) -> Check {
+        Check {
+            id: CheckId(id),
+            label: String::from(label),
+            subtotal: lines.iter().map(|line| line.amount).sum(),
+            lines,
+            tip: 0,
+            payments: Vec::new(),
+        }
+    }
+
+    pub fn total(&self) -> Cents {
+        self.subtotal + self.tip
+    }
+
+    /// What was paid towards the check. Refunds do not take away from it:
+    /// a check paid and then refunded stays settled rather than coming
+    /// due again.
+    pub fn paid(&self) -> Cents {
+        self.payments.iter().map(|p| p.applied).sum()
+    }
+
+    /// What has been given back on the check's payments since.
+    pub fn refunded(&self) -> Cents {
+        self.payments.iter().map(|p| p.refunded).sum()
+    }
+
+    pub fn balance(&self) -> Cents {
+        self.total().saturating_sub(self.paid())
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.balance() == 0
+    }
+}
+
+/// The itemised bill for one order and the checks it has been split into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bill {
+    pub order: OrderId,
+    pub lines: Vec<BillLine>,
+    checks: Vec<Check>,
+    next_payment: u32,
+}
+
+impl Bill {
+    /// Itemises `order` onto a single check for the whole table.
+    pub fn from_order(order: &Order) -> Bill {
+        let lines: Vec<BillLine> = order
+            .items()
+            .iter()
+            .map(|item| {
+                let mut description = describe(item);
+                if item.comped {
+                    description.push_str(" (comp)");
+                }
+                BillLine::item(item, description, None, item.total())
+            })
+            .collect();
+
+        Bill {
+            order: order.id,
+            checks: vec![Check::new(1, "table", lines.clone())],
+            lines,
+            next_payment: 1,
+        }
+    }
+
+    /// Bills `order` as `breakdown` priced it: each line at what is left
+    /// after comps and discounts, then tax and any gratuity for the table.
+    pub fn priced(order: &Order, breakdown: &Breakdown) -> Bill {
+        let mut lines: Vec<BillLine> = breakdown
+            .lines
+            .iter()
+            .zip(order.items())
+            .map(|(line, item)| {
+                let mut description = line.description.clone();
+                if item.comped {
+                    description.push_str(" (comp)");
+                }
+                BillLine::item(item, description, line.category, line.net)
+            })
+            .collect();
+        for tax in breakdown.taxes.iter().filter(|tax| tax.tax > 0) {
+            let category = tax
+                .category
+                .map_or_else(|| String::from("other"), |c| c.to_string());
+            lines.push(BillLine::surcharge(
+                format!("tax {} {}", category, tax.rate),
+                Surcharge::Tax(tax.category),
+                tax.tax,
+            ));
+        }
+        if let Some(gratuity) = &breakdown.gratuity {
+            lines.push(BillLine::surcharge(
+                format!("gratuity {}", gratuity.rate),
+                Surcharge::Gratuity,
+                gratuity.amount,
+            ));
+        }
+
+        Bill {
+            order: order.id,
+            checks: vec![Check::new(1, "table", lines.clone())],
+            lines,
+            next_payment: 1,
+        }
+    }
+
+    pub fn subtotal(&self) -> Cents {
+        self.lines.iter().map(|line| line.amount).sum()
+    }
+
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    pub fn check(&self, id: CheckId) -> Result<&Check, PaymentError> {
+        self.checks
+            .iter()
+            .find(|check| check.id == id)
+            .ok_or(PaymentError::UnknownCheck(id))
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.checks.iter().all(Check::is_settled)
+    }
+
+    /// One check per seat. Lines without a seat stay on a "table" check,
+    /// shared lines are divided between the seats sharing them. Tax on a
+    /// category is spread over the checks with items in that category and
+    /// gratuity over all of them, in proportion to their items. The checks
+    /// always add up to the bill, to the cent.
+    pub fn split_by_seat(&mut self) -> Result<&[Check], PaymentError> {
+        self.ensure_unpaid()?;
+        let mut by_seat: BTreeMap<Option<u8>, Vec<BillLine>> = BTreeMap::new();
+        for line in self.lines.iter().filter(|line| line.surcharge.is_none()) {
+            for (seat, line) in line.by_seat() {
+                by_seat.entry(seat).or_default().push(line);
+            }
+        }
+        let surcharges: Vec<(Surcharge, &BillLine)> = self
+            .lines
+            .iter()
+            .filter_map(|line| line.surcharge.map(|surcharge| (surcharge, line)))
+            .collect();
+        if by_seat.is_empty() && !surcharges.is_empty() {
+            by_seat.insert(None, Vec::new());
+        }
+        for (kind, surcharge) in surcharges {
+            let weights = surcharge_weights(&by_seat, kind);
+            let amounts = allocate(surcharge.amount, &weights);
+            for (lines, amount) in by_seat.values_mut().zip(amounts) {
+                if amount > 0 {
+                    lines.push(BillLine {
+                        amount,
+                        ..surcharge.clone()
+                    });
+                }
+            }
+        }
+
+        self.checks = by_seat
+            .into_iter()
+            .enumerate()
+            .map(|(index, (seat, lines))| {
+                let label = match seat {
+                    Some(seat) => format!("seat {}", seat),
+                    None => String::from("table"),
+                };
+                Check::new(index as u32 + 1, &label, lines)
+            })
+            .collect();
+        Ok(&self.checks)
+    }
+
+    /// `ways` checks of (nearly) equal size, each with its share of every
+    /// line and of any tips already added. Leftover cents go to the first
+    /// checks, one each, so the parts always add up to the bill.
+    pub fn split_evenly(&mut self, ways: usize) -> Result<&[Check], PaymentError> {
+        self.ensure_unpaid()?;
+        if ways == 0 {
+            return Err(PaymentError::InvalidSplit(ways));
+        }
+        let each = ways as Cents;
+        let mut checks: Vec<Check> = (0..ways)
+            .map(|index| {
+                Check::new(
+                    index as u32 + 1,
+                    &format!("{} of {}", index + 1, ways),
+                    Vec::new(),
+                )
+            })
+            .collect();
+        // a line's odd cents go to the checks after the ones the last line's
+        // went to, which adds up to the first checks getting a cent more
+        let mut next = 0;
+        for line in &self.lines {
+            let leftover = (line.amount % each) as usize;
+            let description = match ways {
+                1 => line.description.clone(),
+                _ => format!("{} ({})", line.description, fraction(1, ways as u32)),
+            };
+            for (index, check) in checks.iter_mut().enumerate() {
+                let extra = (index + ways - next) % ways < leftover;
+                check.lines.push(BillLine {
+                    description: description.clone(),
+                    shared: Vec::new(),
+                    amount: line.amount / each + Cents::from(extra),
+                    ..line.clone()
+                });
+            }
+            next = (next + leftover) % ways;
+        }
+        let tips: Vec<Cents> = self.checks.iter().map(|check| check.tip).collect();
+        let tips = allocate(tips.iter().sum(), &vec![1; ways]);
+        for (check, tip) in checks.iter_mut().zip(tips) {
+            check.subtotal = check.lines.iter().map(|line| line.amount).sum();
+            check.tip = tip;
+        }
+        self.checks = checks;
+        Ok(&self.checks)
+    }
+
+    /// Adds an empty check, for moving lines onto.
+    pub fn open_check(&mut self, label: &str) -> CheckId {
+        let id = self
+            .checks
+            .iter()
+            .map(|check| check.id.0)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.checks.push(Check::new(id, label, Vec::new()));
+        CheckId(id)
+    }
+
+    /// Moves everything on `from`, tip included, onto `into` and drops
+    /// `from`. Neither may have been paid on.
+    pub fn merge(&mut self, from: CheckId, into: CheckId) -> Result<&Check, PaymentError> {
+        self.ensure_movable(from, into)?;
+        let index = self
+            .checks
+            .iter()
+            .position(|check| check.id == from)
+            .ok_or(PaymentError::UnknownCheck(from))?;
+        let merged = self.checks.remove(index);
+        let target = self.check_mut(into)?;
+        target.lines.extend(merged.lines);
+        target.subtotal += merged.subtotal;
+        target.tip += merged.tip;
+        Ok(target)
+    }
+
+    /// Moves one line from one check to another. Neither may have been
+    /// paid on.
+    pub fn transfer(
+        &mut self,
+        from: CheckId,
+        line: usize,
+        to: CheckId,
+    ) -> Result<(), PaymentError> {
+        self.ensure_movable(from, to)?;
+        let source = self.check_mut(from)?;
+        if line >= source.lines.len() {
+            return Err(PaymentError::NoSuchLine { check: from, line });
+        }
+        let moved = source.lines.remove(line);
+        source.subtotal -= moved.amount;
+        let target = self.check_mut(to)?;
+        target.subtotal += moved.amount;
+        target.lines.push(moved);
+        Ok(())
+    }
+
+    pub fn add_tip(&mut self, check: CheckId, tip: Cents) -> Result<(), PaymentError> {
+        self.check_mut(check)?.tip += tip;
+        Ok(())
+    }
+
+    /// Applies one tender to a check. Cash may exceed the balance and
+    /// produces change; cards, vouchers and points may not.
+    pub fn pay(
+        &mut self,
+        check: CheckId,
+        tender: Tender,
+        gateway: &mut dyn PaymentGateway,
+    ) -> Result<Receipt, PaymentError> {
+        let amount = tender.amount();
+        if amount == 0 {
+            return Err(PaymentError::ZeroAmount);
+        }
+        let balance = self.check(check)?.balance();
+        if balance == 0 {
+            return Err(PaymentError::AlreadySettled(check));
+        }
+
+        let (applied, change, transaction) = match &tender {
+            Tender::Cash(_) => (amount.min(balance), amount.saturating_sub(balance), None),
+            Tender::Card { last4, .. } => {
+                self.ensure_within(check, balance, amount)?;
+                (amount, 0, Some(gateway.charge_card(last4, amount)?))
+            }
+            Tender::Voucher { code, .. } => {
+                self.ensure_within(check, balance, amount)?;
+                (amount, 0, Some(gateway.redeem_voucher(code, amount)?))
+            }
+            Tender::Points { customer, .. } => {
+                self.ensure_within(check, balance, amount)?;
+                (amount, 0, Some(gateway.redeem_points(*customer, amount)?))
+            }
+        };
+
+        let id = PaymentId(self.next_payment);
+        self.next_payment += 1;
+        let target = self.check_mut(check)?;
+        target.payments.push(Payment {
+            id,
+            tender,
+            applied,
+            change,
+            transaction,
+            refunded: 0,
+        });
+
+        Ok(Receipt {
+            check,
+            payment: id,
+            applied,
+            change,
+            balance: target.balance(),
+        })
+    }
+
+    /// Gives money back on an earlier payment, through the gateway for cards
+    /// and vouchers and out of the till for cash.
+    pub fn refund(
+        &mut self,
+        payment: PaymentId,
+        amount: Cents,
+        gateway: &mut dyn PaymentGateway,
+    ) -> Result<Refund, PaymentError> {
+        if amount == 0 {
+            return Err(PaymentError::ZeroAmount);
+        }
+        let record = self
+            .checks
+            .iter_mut()
+            .flat_map(|check| check.payments.iter_mut())
+            .find(|p| p.id == payment)
+            .ok_or(PaymentError::UnknownPayment(payment))?;
+
+        let refundable = record.applied - record.refunded;
+        if amount > refundable {
+            return Err(PaymentError::RefundExceedsPayment {
+                payment,
+                refundable,
+                requested: amount,
+            });
+        }
+        let transaction = match &record.transaction {
+            Some(original) => Some(gateway.refund(original, amount)?),
+            None => None,
+        };
+        record.refunded += amount;
+
+        Ok(Refund {
+            payment,
+            amount,
+            transaction,
+        })
+    }
+
+    /// The tenders used so far, less refunds, e.g. for closing out the till.
+    pub fn tenders_by_kind(&self) -> BTreeMap<&'static str, Cents> {
+        let mut totals = BTreeMap::new();
+        for payment in self.checks.iter().flat_map(|c| c.payments.iter()) {
+            *totals.entry(payment.tender.kind()).or_insert(0) += payment.applied - payment.refunded;
+        }
+        totals
+    }
+
+    fn check_mut(&mut self, id: CheckId) -> Result<&mut Check, PaymentError> {
+        self.checks
+            .iter_mut()
+            .find(|check| check.id == id)
+            .ok_or(PaymentError::UnknownCheck(id))
+    }
+
+    fn ensure_unpaid(&self) -> Result<(), PaymentError> {
+        if self.checks.iter().any(|check| !check.payments.is_empty()) {
+            return Err(PaymentError::SplitAfterPayment);
+        }
+        Ok(())
+    }
+
+    fn ensure_movable(&self, from: CheckId, to: CheckId) -> Result<(), PaymentError> {
+        if from == to {
+            return Err(PaymentError::SameCheck(from));
+        }
+        for id in [from, to] {
+            if !self.check(id)?.payments.is_empty() {
+                return Err(PaymentError::CheckHasPayments(id));
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_within(
+        &self,
+        check: CheckId,
+        balance: Cents,
+        tendered: Cents,
+    ) -> Result<(), PaymentError> {
+        if tendered > balance {
+            return Err(PaymentError::Overpayment {
+                check,
+                balance,
+                tendered,
+            });
+        }
+        Ok(())
+    }
+}
+
+// What each seat's check bears of `surcharge`: its items that the surcharge
+// was worked out on. Items comped down to nothing still count the seat in,
+// evenly, and with no such items at all it falls on every check.
+fn surcharge_weights(by_seat: &BTreeMap<Option<u8>, Vec<BillLine>>, kind: Surcharge) -> Vec<Cents> {
+    let bearing: Vec<Vec<&BillLine>> = by_seat
+        .values()
+        .map(|lines| lines.iter().filter(|line| line.bears(kind)).collect())
+        .collect();
+    let weights: Vec<Cents> = bearing
+        .iter()
+        .map(|lines| lines.iter().map(|line| line.amount).sum())
+        .collect();
+    if weights.iter().any(|&weight| weight > 0) {
+        return weights;
+    }
+    bearing
+        .iter()
+        .map(|lines| Cents::from(!lines.is_empty()))
+        .collect()
+}
@@ -0,0 +1,465 @@
+// What an order actually comes to once the house rules are applied. Every
+// amount is worked out in whole cents, in a fixed order, so the same order
+// always prices the same way:
+//
+//   1. each line starts at its menu price including modifiers
+//   2. comped lines come off in full
+//   3. the first happy hour running when the order was taken comes off the
+//      lines it covers, rounded per line
+//   4. discounts come off in the order they were given; percentages are
+//      rounded per line, fixed amounts are shared across the lines they
+//      cover in proportion to what is left on each, to the cent
+//   5. tax is charged per category on what is left, rounded once per
+//      category rather than per line
+//   6. automatic gratuity, for parties big enough, is charged on what is
+//      left before tax
+//
+// Rounding is always to the nearest cent, halves up.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::split::allocate;
+use crate::menu::{Category, Menu};
+use crate::serving::{Cents, LineItem, Order, OrderStatus};
+use crate::time::Window;
+
+/// A percentage, kept in thousandths of a percent so rates like 8.875%
+/// are exact: that one is `Rate(8875)`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Rate(pub u32);
+
+impl Rate {
+    pub const ZERO: Rate = Rate(0);
+
+    pub const fn percent(percent: u32) -> Rate {
+        Rate(percent * 1000)
+    }
+
+    /// This rate of `amount`, to the nearest cent.
+    pub fn of(self, amount: Cents) -> Cents {
+        let exact = amount as u128 * self.0 as u128;
+        ((exact + 50_000) / 100_000) as Cents
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / 1000;
+        let fraction = self.0 % 1000;
+        if fraction == 0 {
+            return write!(f, "{}%", whole);
+        }
+        let digits = format!("{:03}", fraction);
+        write!(f, "{}.{}%", whole, digits.trim_end_matches('0'))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DiscountAmount {
+    Percent(Rate),
+    Fixed(Cents),
+}
+
+/// Which lines a discount is taken off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DiscountScope {
+    Bill,
+    Category(Category),
+    Item(String),
+}
+
+/// Money off a bill, given by a manager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discount {
+    pub label: String,
+    pub amount: DiscountAmount,
+    pub scope: DiscountScope,
+}
+
+impl Discount {
+    /// A percentage off the whole bill. Over 100% is taken as 100%.
+    pub fn percent(label: &str, rate: Rate) -> Discount {
+        Discount {
+            label: String::from(label),
+            amount: DiscountAmount::Percent(rate),
+            scope: DiscountScope::Bill,
+        }
+    }
+
+    /// A fixed amount off the whole bill, never taking it below zero.
+    pub fn fixed(label: &str, amount: Cents) -> Discount {
+        Discount {
+            label: String::from(label),
+            amount: DiscountAmount::Fixed(amount),
+            scope: DiscountScope::Bill,
+        }
+    }
+
+    pub fn on_category(mut self, category: Category) -> Discount {
+        self.scope = DiscountScope::Category(category);
+        self
+    }
+
+    pub fn on_item(mut self, item: &str) -> Discount {
+        self.scope = DiscountScope::Item(item.to_lowercase());
+        self
+    }
+}
+
+/// Cheaper prices for part of the day, on some categories or on everything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HappyHour {
+    pub label: String,
+    pub window: Window,
+    pub rate: Rate,
+    /// Empty means every category.
+    pub categories: Vec<Category>,
+}
+
+impl HappyHour {
+    pub fn new(label: &str, window: Window, rate: Rate) -> HappyHour {
+        HappyHour {
+            label: String::from(label),
+            window,
+            rate,
+            categories: Vec::new(),
+        }
+    }
+
+    pub fn only(mut self, category: Category) -> HappyHour {
+        self.categories.push(category);
+        self
+    }
+
+    fn covers(&self, category: Option<Category>) -> bool {
+        self.categories.is_empty() || category.is_some_and(|c| self.categories.contains(&c))
+    }
+}
+
+/// A service charge added for parties of `min_party` or more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoGratuity {
+    pub min_party: u8,
+    pub rate: Rate,
+}
+
+/// How the house prices a bill. Items are put in categories by name, so
+/// the rules are made from the menu; anything not on it pays the default
+/// tax and only gets whole-bill discounts.
+#[derive(Debug, Clone, Default)]
+pub struct PricingRules {
+    categories: BTreeMap<String, Category>,
+    taxes: BTreeMap<Category, Rate>,
+    default_tax: Rate,
+    auto_gratuity: Option<AutoGratuity>,
+    happy_hours: Vec<HappyHour>,
+}
+
+impl PricingRules {
+    pub fn new(menu: &Menu) -> PricingRules {
+        PricingRules {
+            categories: menu
+                .items()
+                .map(|item| (item.name.to_lowercase(), item.category))
+                .collect(),
+            ..PricingRules::default()
+        }
+    }
+
+    pub fn tax(mut self, category: Category, rate: Rate) -> PricingRules {
+        self.taxes.insert(category, rate);
+        self
+    }
+
+    /// Tax for categories without a rate of their own.
+    pub fn default_tax(mut self, rate: Rate) -> PricingRules {
+        self.default_tax = rate;
+        self
+    }
+
+    pub fn auto_gratuity(mut self, min_party: u8, rate: Rate) -> PricingRules {
+        self.auto_gratuity = Some(AutoGratuity { min_party, rate });
+        self
+    }
+
+    pub fn happy_hour(mut self, happy_hour: HappyHour) -> PricingRules {
+        self.happy_hours.push(happy_hour);
+        self
+    }
+
+    pub fn category_of(&self, item: &str) -> Option<Category> {
+        self.categories.get(&item.to_lowercase()).copied()
+    }
+
+    pub fn tax_rate(&self, category: Option<Category>) -> Rate {
+        category
+            .and_then(|category| self.taxes.get(&category))
+            .copied()
+            .unwrap_or(self.default_tax)
+    }
+
+    /// Prices `order` for a party of `covers` with `discounts` given.
+    pub fn price(&self, order: &Order, covers: u8, discounts: &[Discount]) -> Breakdown {
+        let mut lines: Vec<PricedLine> = order
+            .items()
+            .iter()
+            .enumerate()
+            .map(|(index, item)| PricedLine::new(index, item, self.category_of(&item.item)))
+            .collect();
+
+        for line in lines.iter_mut().filter(|line| line.comped) {
+            line.take(AdjustmentKind::Comp, "comp", line.net);
+        }
+
+        let taken = order.reached(OrderStatus::Taken).map(|at| at.time_of_day());
+        let happy_hour = self
+            .happy_hours
+            .iter()
+            .find(|happy| taken.is_some_and(|time| happy.window.contains(time)));
+        if let Some(happy) = happy_hour {
+            let label = format!("{} {}", happy.label, happy.rate);
+            for line in lines.iter_mut().filter(|line| happy.covers(line.category)) {
+                let off = happy.rate.of(line.net).min(line.net);
+                line.take(AdjustmentKind::HappyHour, &label, off);
+            }
+        }
+
+        for discount in discounts {
+            apply_discount(&mut lines, discount);
+        }
+
+        let mut taxable: BTreeMap<Option<Category>, Cents> = BTreeMap::new();
+        for line in &lines {
+            *taxable.entry(line.category).or_insert(0) += line.net;
+        }
+        let taxes: Vec<TaxLine> = taxable
+            .into_iter()
+            .filter(|(_, taxable)| *taxable > 0)
+            .map(|(category, taxable)| {
+                let rate = self.tax_rate(category);
+                TaxLine {
+                    category,
+                    rate,
+                    taxable,
+                    tax: rate.of(taxable),
+                }
+            })
+            .filter(|line| line.tax > 0)
+            .collect();
+
+        let subtotal = lines.iter().map(|line| line.gross).sum();
+        let net: Cents = lines.iter().map(|line| line.net).sum();
+        let tax = taxes.iter().map(|line| line.tax).sum();
+        let gratuity = self
+            .auto_gratuity
+            .filter(|auto| covers >= auto.min_party && net > 0)
+            .map(|auto| Gratuity {
+                rate: auto.rate,
+                covers,
+                on: net,
+                amount: auto.rate.of(net),
+            });
+
+        Breakdown {
+            subtotal,
+            adjustments: subtotal - net,
+            net,
+            tax,
+            total: net + tax + gratuity.as_ref().map_or(0, |g| g.amount),
+            lines,
+            taxes,
+            gratuity,
+        }
+    }
+}
+
+fn apply_discount(lines: &mut [PricedLine], discount: &Discount) {
+    let mut eligible: Vec<&mut PricedLine> = lines
+        .iter_mut()
+        .filter(|line| line.net > 0)
+        .filter(|line| match &discount.scope {
+            DiscountScope::Bill => true,
+            DiscountScope::Category(category) => line.category == Some(*category),
+            DiscountScope::Item(item) => line.item.eq_ignore_ascii_case(item),
+        })
+        .collect();
+
+    match discount.amount {
+        DiscountAmount::Percent(rate) => {
+            let label = format!("{} {}", discount.label, rate);
+            for line in eligible {
+                let off = rate.of(line.net).min(line.net);
+                line.take(AdjustmentKind::Discount, &label, off);
+            }
+        }
+        DiscountAmount::Fixed(amount) => {
+            let weights: Vec<Cents> = eligible.iter().map(|line| line.net).collect();
+            let base: Cents = weights.iter().sum();
+            let shares = allocate(amount.min(base), &weights);
+            for (line, off) in eligible.iter_mut().zip(shares) {
+                line.take(AdjustmentKind::Discount, &discount.label, off);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AdjustmentKind {
+    Comp,
+    HappyHour,
+    Discount,
+}
+
+/// Money taken off one line, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Adjustment {
+    pub kind: AdjustmentKind,
+    pub label: String,
+    pub amount: Cents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PricedLine {
+    /// Index into the order's items.
+    pub line: usize,
+    pub item: String,
+    pub description: String,
+    pub category: Option<Category>,
+    pub seat: Option<u8>,
+    pub quantity: u16,
+    /// Before anything comes off.
+    pub gross: Cents,
+    pub adjustments: Vec<Adjustment>,
+    /// What is left to pay, before tax.
+    pub net: Cents,
+    #[serde(skip)]
+    comped: bool,
+}
+
+impl PricedLine {
+    fn new(line: usize, item: &LineItem, category: Option<Category>) -> PricedLine {
+        let gross = item.unit_total() * item.quantity as Cents;
+        PricedLine {
+            line,
+            item: item.item.clone(),
+            description: describe(item),
+            category,
+            seat: item.seat,
+            quantity: item.quantity,
+            gross,
+            adjustments: Vec::new(),
+            net: gross,
+            comped: item.comped,
+        }
+    }
+
+    fn take(&mut self, kind: AdjustmentKind, label: &str, amount: Cents) {
+        if amount == 0 {
+            return;
+        }
+        self.net -= amount;
+        self.adjustments.push(Adjustment {
+            kind,
+            label: String::from(label),
+            amount,
+        });
+    }
+}
+
+/// The item with its modifiers, as it goes on the bill.
+pub(super) fn describe(item: &LineItem) -> String {
+    let mut description = item.item.clone();
+    for modifier in &item.modifiers {
+        description.push_str(", ");
+        description.push_str(&modifier.name);
+    }
+    description
+}
+
+/// Tax on one category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaxLine {
+    /// `None` for items that are not on the menu.
+    pub category: Option<Category>,
+    pub rate: Rate,
+    pub taxable: Cents,
+    pub tax: Cents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Gratuity {
+    pub rate: Rate,
+    pub covers: u8,
+    /// The pre-tax amount it was worked out on.
+    pub on: Cents,
+    pub amount: Cents,
+}
+
+/// A priced order, with every adjustment on the line it was made to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Breakdown {
+    pub lines: Vec<PricedLine>,
+    /// Everything at menu price.
+    pub subtotal: Cents,
+    /// Comps, happy hour and discounts together.
+    pub adjustments: Cents,
+    pub net: Cents,
+    pub taxes: Vec<TaxLine>,
+    pub tax: Cents,
+    pub gratuity: Option<Gratuity>,
+    pub total: Cents,
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            let item = format!("{}x {}", line.quantity, line.description);
+            writeln!(f, "{:<34} {:>10}", item, dollars(line.gross))?;
+            for adjustment in &line.adjustments {
+                let label = format!("    {}", adjustment.label);
+                writeln!(f, "{:<34} {:>10}", label, credit(adjustment.amount))?;
+            }
+        }
+        writeln!(f, "{:<34} {:>10}", "subtotal", dollars(self.subtotal))?;
+        if self.adjustments > 0 {
+            writeln!(f, "{:<34} {:>10}", "adjustments", credit(self.adjustments))?;
+        }
+        for line in &self.taxes {
+            let category = line
+                .category
+                .map_or_else(|| String::from("other"), |c| c.to_string());
+            let label = format!(
+                "tax {} {} on {}",
+                category,
+                line.rate,
+                dollars(line.taxable)
+            );
+            writeln!(f, "{:<34} {:>10}", label, dollars(line.tax))?;
+        }
+        if let Some(gratuity) = &self.gratuity {
+            let label = format!("gratuity {}, party of {}", gratuity.rate, gratuity.covers);
+            writeln!(f, "{:<34} {:>10}", label, dollars(gratuity.amount))?;
+        }
+        writeln!(f, "{:<34} {:>10}", "total", dollars(self.total))
+    }
+}
+
+fn dollars(cents: Cents) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+fn credit(cents: Cents) -> String {
+    format!("-{}", dollars(cents))
+}
@@ -0,0 +1,69 @@
+// Dividing money between guests. Whatever is split, the pieces add back up
+// to exactly what was split: cents that do not divide evenly go one at a
+// time to whoever was closest to getting them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::serving::Cents;
+
+/// A seat's part of a shared line. A soup split three ways is three shares
+/// of one part each; one guest having twice as much of it as another is
+/// two parts against one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Share {
+    pub seat: u8,
+    pub parts: u32,
+}
+
+impl Share {
+    pub fn new(seat: u8, parts: u32) -> Share {
+        Share { seat, parts }
+    }
+}
+
+/// Splits `amount` in proportion to `weights`. Each gets its share rounded
+/// down, then the cents left over go one each to the largest remainders,
+/// earlier ones first on a tie. With no weight at all it is split evenly.
+pub fn allocate(amount: Cents, weights: &[Cents]) -> Vec<Cents> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let total: Cents = weights.iter().sum();
+    if total == 0 {
+        return allocate(amount, &vec![1; weights.len()]);
+    }
+
+    let exact: Vec<(Cents, Cents)> = weights
+        .iter()
+        .map(|&weight| {
+            let scaled = amount as u128 * weight as u128;
+            (
+                (scaled / total as u128) as Cents,
+                (scaled % total as u128) as Cents,
+            )
+        })
+        .collect();
+    let mut shares: Vec<Cents> = exact.iter().map(|(share, _)| *share).collect();
+    let left = amount - shares.iter().sum::<Cents>();
+
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(exact[index].1));
+    for &index in order.iter().take(left as usize) {
+        shares[index] += 1;
+    }
+    shares
+}
+
+/// `parts` out of `total`, in lowest terms, e.g. "1/3".
+pub(super) fn fraction(parts: u32, total: u32) -> String {
+    let divisor = gcd(parts, total).max(1);
+    format!("{}/{}", parts / divisor, total / divisor)
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
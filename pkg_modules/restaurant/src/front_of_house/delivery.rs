@@ -0,0 +1,17 @@
+// delivery is re-exported from the crate root next to hosting: it is the
+// front door for everyone who does not sit down.
+
+mod board;
+mod courier;
+
+pub use self::board::{
+    Address, Delivery, DeliveryBoard, DeliveryError, DeliveryId, DeliveryStatus, Fulfilment,
+};
+pub use self::courier::{Courier, CourierId, CourierPool};
+pub use crate::deliver_order;
+
+use crate::hosting::TableNumber;
+
+/// Delivery and takeout orders are rung up against this table, which no
+/// floor plan has.
+pub const COUNTER: TableNumber = TableNumber(0);
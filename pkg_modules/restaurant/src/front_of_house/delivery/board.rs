@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::courier::{CourierId, CourierPool};
+use crate::serving::OrderId;
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DeliveryId(pub u32);
+
+impl fmt::Display for DeliveryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "delivery {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+    pub postcode: String,
+    /// "Ring twice", "leave with the doorman", ...
+    pub instructions: Option<String>,
+}
+
+impl Address {
+    pub fn new(street: &str, city: &str, postcode: &str) -> Address {
+        Address {
+            street: String::from(street),
+            city: String::from(city),
+            postcode: String::from(postcode),
+            instructions: None,
+        }
+    }
+
+    pub fn instructions(mut self, instructions: &str) -> Address {
+        self.instructions = Some(String::from(instructions));
+        self
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {} {}", self.street, self.postcode, self.city)
+    }
+}
+
+/// How the food leaves the restaurant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Fulfilment {
+    /// The guest picks it up at the counter.
+    Takeout,
+    Delivery(Address),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeliveryStatus {
+    /// Still in the kitchen.
+    Preparing,
+    /// Bagged and waiting at the pass for a courier or the guest.
+    Packed,
+    OutForDelivery(CourierId),
+    Delivered,
+    PickedUp,
+    Cancelled,
+}
+
+impl DeliveryStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            DeliveryStatus::Delivered | DeliveryStatus::PickedUp | DeliveryStatus::Cancelled
+        )
+    }
+}
+
+impl fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryStatus::Preparing => write!(f, "preparing"),
+            DeliveryStatus::Packed => write!(f, "packed"),
+            DeliveryStatus::OutForDelivery(courier) => write!(f, "out with {}", courier),
+            DeliveryStatus::Delivered => write!(f, "delivered"),
+            DeliveryStatus::PickedUp => write!(f, "picked up"),
+            DeliveryStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub id: DeliveryId,
+    pub order: OrderId,
+    pub customer: String,
+    pub fulfilment: Fulfilment,
+    pub placed: Timestamp,
+    /// When we told the guest it would be with them (or ready to collect).
+    pub promised: Timestamp,
+    pub courier: Option<CourierId>,
+    pub status: DeliveryStatus,
+    pub packed_at: Option<Timestamp>,
+    pub handed_off_at: Option<Timestamp>,
+    pub delivered_at: Option<Timestamp>,
+}
+
+impl Delivery {
+    pub fn needs_courier(&self) -> bool {
+        matches!(self.fulfilment, Fulfilment::Delivery(_))
+    }
+
+    /// When the guest got the food: at the door for deliveries, at the
+    /// counter for takeout.
+    pub fn completed_at(&self) -> Option<Timestamp> {
+        match self.fulfilment {
+            Fulfilment::Takeout => self.handed_off_at,
+            Fulfilment::Delivery(_) => self.delivered_at,
+        }
+    }
+
+    /// How far past the promised time it is (or was, once completed).
+    pub fn lateness(&self, now: Timestamp) -> Duration {
+        if self.status == DeliveryStatus::Cancelled {
+            return Duration::ZERO;
+        }
+        self.completed_at()
+            .unwrap_or(now)
+            .saturating_duration_since(self.promised)
+    }
+
+    pub fn is_late(&self, now: Timestamp) -> bool {
+        self.lateness(now) > Duration::ZERO
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeliveryError {
+    UnknownDelivery(DeliveryId),
+    /// Nothing is being delivered or collected for the order.
+    NoDeliveryForOrder(OrderId),
+    /// The order already has a delivery that is not finished.
+    AlreadyOpen {
+        order: OrderId,
+        delivery: DeliveryId,
+    },
+    InvalidStatus {
+        delivery: DeliveryId,
+        status: DeliveryStatus,
+        action: &'static str,
+    },
+    /// Takeout orders are collected, not driven anywhere.
+    NotADelivery(DeliveryId),
+    NoCourierAssigned(DeliveryId),
+    NoCourierAvailable,
+    UnknownCourier(CourierId),
+    CourierUnavailable(CourierId),
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::UnknownDelivery(id) => write!(f, "{} does not exist", id),
+            DeliveryError::NoDeliveryForOrder(order) => {
+                write!(f, "{} is not a delivery or takeout order", order)
+            }
+            DeliveryError::AlreadyOpen { order, delivery } => {
+                write!(f, "{} already has {}", order, delivery)
+            }
+            DeliveryError::InvalidStatus {
+                delivery,
+                status,
+                action,
+            } => write!(f, "cannot {} {}, it is {}", action, delivery, status),
+            DeliveryError::NotADelivery(id) => write!(f, "{} is a takeout order", id),
+            DeliveryError::NoCourierAssigned(id) => write!(f, "{} has no courier", id),
+            DeliveryError::NoCourierAvailable => write!(f, "every courier is out or off shift"),
+            DeliveryError::UnknownCourier(id) => write!(f, "{} does not exist", id),
+            DeliveryError::CourierUnavailable(id) => write!(f, "{} is not available", id),
+        }
+    }
+}
+
+impl Error for DeliveryError {}
+
+/// The pass for everything that leaves by the front door: what is cooking,
+/// what is bagged, and who is out driving.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryBoard {
+    deliveries: BTreeMap<DeliveryId, Delivery>,
+    couriers: CourierPool,
+    next_id: u32,
+}
+
+impl DeliveryBoard {
+    pub fn new(couriers: CourierPool) -> DeliveryBoard {
+        DeliveryBoard {
+            deliveries: BTreeMap::new(),
+            couriers,
+            next_id: 0,
+        }
+    }
+
+    pub fn couriers(&self) -> &CourierPool {
+        &self.couriers
+    }
+
+    pub fn couriers_mut(&mut self) -> &mut CourierPool {
+        &mut self.couriers
+    }
+
+    pub fn open(
+        &mut self,
+        order: OrderId,
+        customer: &str,
+        fulfilment: Fulfilment,
+        promised: Timestamp,
+        now: Timestamp,
+    ) -> Result<DeliveryId, DeliveryError> {
+        if let Some(open) = self
+            .deliveries
+            .values()
+            .find(|d| d.order == order && !d.status.is_finished())
+        {
+            return Err(DeliveryError::AlreadyOpen {
+                order,
+                delivery: open.id,
+            });
+        }
+
+        self.next_id += 1;
+        let id = DeliveryId(self.next_id);
+        self.deliveries.insert(
+            id,
+            Delivery {
+                id,
+                order,
+                customer: String::from(customer),
+                fulfilment,
+                placed: now,
+                promised,
+                courier: None,
+                status: DeliveryStatus::Preparing,
+                packed_at: None,
+                handed_off_at: None,
+                delivered_at: None,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn get(&self, id: DeliveryId) -> Result<&Delivery, DeliveryError> {
+        self.deliveries
+            .get(&id)
+            .ok_or(DeliveryError::UnknownDelivery(id))
+    }
+
+    /// The order's latest delivery that was not cancelled.
+    pub fn for_order(&self, order: OrderId) -> Result<&Delivery, DeliveryError> {
+        self.deliveries
+            .values()
+            .rev()
+            .find(|d| d.order == order && d.status != DeliveryStatus::Cancelled)
+            .ok_or(DeliveryError::NoDeliveryForOrder(order))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Delivery> {
+        self.deliveries.values()
+    }
+
+    pub fn open_deliveries(&self) -> impl Iterator<Item = &Delivery> {
+        self.deliveries.values().filter(|d| !d.status.is_finished())
+    }
+
+    /// Everything still on its way that is already past its promised time.
+    pub fn late(&self, now: Timestamp) -> Vec<&Delivery> {
+        self.open_deliveries().filter(|d| d.is_late(now)).collect()
+    }
+
+    pub fn pack(&mut self, id: DeliveryId, now: Timestamp) -> Result<(), DeliveryError> {
+        let delivery = self.get_mut(id)?;
+        expect_status(delivery, DeliveryStatus::Preparing, "pack")?;
+        delivery.status = DeliveryStatus::Packed;
+        delivery.packed_at = Some(now);
+        Ok(())
+    }
+
+    /// Gives the delivery to whichever available courier has waited longest.
+    pub fn assign(&mut self, id: DeliveryId) -> Result<CourierId, DeliveryError> {
+        self.check_assignable(id)?;
+        let courier = self
+            .couriers
+            .next_available()
+            .ok_or(DeliveryError::NoCourierAvailable)?;
+        self.assign_to(id, courier)?;
+        Ok(courier)
+    }
+
+    pub fn assign_to(&mut self, id: DeliveryId, courier: CourierId) -> Result<(), DeliveryError> {
+        self.check_assignable(id)?;
+        let driver = self
+            .couriers
+            .get_mut(courier)
+            .ok_or(DeliveryError::UnknownCourier(courier))?;
+        if !driver.is_available() {
+            return Err(DeliveryError::CourierUnavailable(courier));
+        }
+        driver.delivering = Some(id);
+        self.get_mut(id)?.courier = Some(courier);
+        Ok(())
+    }
+
+    /// The bag leaves the pass: with the courier for deliveries, with the
+    /// guest for takeout.
+    pub fn hand_off(&mut self, id: DeliveryId, now: Timestamp) -> Result<(), DeliveryError> {
+        let delivery = self.get_mut(id)?;
+        expect_status(delivery, DeliveryStatus::Packed, "hand off")?;
+        delivery.status = match delivery.fulfilment {
+            Fulfilment::Takeout => DeliveryStatus::PickedUp,
+            Fulfilment::Delivery(_) => DeliveryStatus::OutForDelivery(
+                delivery
+                    .courier
+                    .ok_or(DeliveryError::NoCourierAssigned(id))?,
+            ),
+        };
+        delivery.handed_off_at = Some(now);
+        Ok(())
+    }
+
+    /// The courier is back and says it arrived.
+    pub fn complete(&mut self, id: DeliveryId, now: Timestamp) -> Result<(), DeliveryError> {
+        let delivery = self.get_mut(id)?;
+        let courier = match delivery.status {
+            DeliveryStatus::OutForDelivery(courier) => courier,
+            status => {
+                return Err(DeliveryError::InvalidStatus {
+                    delivery: id,
+                    status,
+                    action: "complete",
+                })
+            }
+        };
+        delivery.status = DeliveryStatus::Delivered;
+        delivery.delivered_at = Some(now);
+        self.free_courier(courier, now);
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, id: DeliveryId, now: Timestamp) -> Result<(), DeliveryError> {
+        let delivery = self.get_mut(id)?;
+        if delivery.status.is_finished() {
+            return Err(DeliveryError::InvalidStatus {
+                delivery: id,
+                status: delivery.status,
+                action: "cancel",
+            });
+        }
+        delivery.status = DeliveryStatus::Cancelled;
+        if let Some(courier) = delivery.courier {
+            self.free_courier(courier, now);
+        }
+        Ok(())
+    }
+
+    /// The kitchen is cooking the order again, so a bag that is still
+    /// waiting at the pass goes back to preparing.
+    pub fn remake(&mut self, order: OrderId) -> Option<DeliveryId> {
+        let delivery = self
+            .deliveries
+            .values_mut()
+            .find(|d| d.order == order && d.status == DeliveryStatus::Packed)?;
+        delivery.status = DeliveryStatus::Preparing;
+        delivery.packed_at = None;
+        Some(delivery.id)
+    }
+
+    fn check_assignable(&self, id: DeliveryId) -> Result<(), DeliveryError> {
+        let delivery = self.get(id)?;
+        if !delivery.needs_courier() {
+            return Err(DeliveryError::NotADelivery(id));
+        }
+        if delivery.courier.is_some()
+            || !matches!(
+                delivery.status,
+                DeliveryStatus::Preparing | DeliveryStatus::Packed
+            )
+        {
+            return Err(DeliveryError::InvalidStatus {
+                delivery: id,
+                status: delivery.status,
+                action: "assign a courier to",
+            });
+        }
+        Ok(())
+    }
+
+    fn free_courier(&mut self, courier: CourierId, now: Timestamp) {
+        if let Some(courier) = self.couriers.get_mut(courier) {
+            courier.delivering = None;
+            courier.idle_since = now;
+        }
+    }
+
+    fn get_mut(&mut self, id: DeliveryId) -> Result<&mut Delivery, DeliveryError> {
+        self.deliveries
+            .get_mut(&id)
+            .ok_or(DeliveryError::UnknownDelivery(id))
+    }
+}
+
+fn expect_status(
+    delivery: &Delivery,
+    expected: DeliveryStatus,
+    action: &'static str,
+) -> Result<(), DeliveryError> {
+    if delivery.status != expected {
+        return Err(DeliveryError::InvalidStatus {
+            delivery: delivery.id,
+            status: delivery.status,
+            action,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::board::DeliveryId;
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CourierId(pub u32);
+
+impl fmt::Display for CourierId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "courier {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Courier {
+    pub id: CourierId,
+    pub name: String,
+    pub on_shift: bool,
+    /// The run they are on, if any. Couriers take one delivery at a time.
+    pub delivering: Option<DeliveryId>,
+    /// When they last came back, so the one who has waited longest goes next.
+    pub idle_since: Timestamp,
+}
+
+impl Courier {
+    pub fn is_available(&self) -> bool {
+        self.on_shift && self.delivering.is_none()
+    }
+}
+
+/// The drivers and riders working this shift.
+#[derive(Debug, Clone, Default)]
+pub struct CourierPool {
+    couriers: Vec<Courier>,
+}
+
+impl CourierPool {
+    pub fn new() -> CourierPool {
+        CourierPool::default()
+    }
+
+    /// Adds a courier who is on shift and free from `now`.
+    pub fn add(&mut self, name: &str, now: Timestamp) -> CourierId {
+        let id = CourierId(self.couriers.len() as u32 + 1);
+        self.couriers.push(Courier {
+            id,
+            name: String::from(name),
+            on_shift: true,
+            delivering: None,
+            idle_since: now,
+        });
+        id
+    }
+
+    pub fn get(&self, id: CourierId) -> Option<&Courier> {
+        self.couriers.iter().find(|c| c.id == id)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: CourierId) -> Option<&mut Courier> {
+        self.couriers.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Courier> {
+        self.couriers.iter()
+    }
+
+    pub fn available(&self) -> impl Iterator<Item = &Courier> {
+        self.couriers.iter().filter(|c| c.is_available())
+    }
+
+    /// Whoever has been waiting for a run the longest.
+    pub fn next_available(&self) -> Option<CourierId> {
+        self.available()
+            .min_by_key(|c| (c.idle_since, c.id))
+            .map(|c| c.id)
+    }
+
+    /// Takes a courier off the rota. Anyone still out finishes their run.
+    pub fn clock_out(&mut self, id: CourierId) -> bool {
+        match self.get_mut(id) {
+            Some(courier) => {
+                courier.on_shift = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clock_in(&mut self, id: CourierId, now: Timestamp) -> bool {
+        match self.get_mut(id) {
+            Some(courier) => {
+                courier.on_shift = true;
+                courier.idle_since = now;
+                true
+            }
+            None => false,
+        }
+    }
+}
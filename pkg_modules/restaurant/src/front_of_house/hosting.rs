@@ -0,0 +1,66 @@
+// public module
+// the hosting module is what the rest of the world sees as restaurant::hosting,
+// so the types its functions need are re-exported here too.
+
+mod floor;
+mod reservations;
+mod table;
+mod waitlist;
+
+pub use self::floor::{FloorError, FloorPlan};
+pub use self::reservations::{
+    Reservation, ReservationBook, ReservationError, ReservationId, ReservationRequest,
+    ReservationStatus, TurnTimes, NO_SHOW_GRACE,
+};
+pub use self::table::{SeatingArea, Table, TableNumber, TableState};
+pub use self::waitlist::{
+    Party, PartyId, Priority, SeatError, Seated, Waitlist, WaitlistEntry, DEFAULT_PACE,
+};
+
+use crate::time::Timestamp;
+
+pub fn add_to_waitlist(waitlist: &mut Waitlist, party: Party) -> PartyId {
+    waitlist.add(party).id
+}
+
+/// Seats the first party in line that the floor can take right now, joining
+/// adjacent tables when no single one is big enough.
+pub fn seat_at_table(
+    waitlist: &mut Waitlist,
+    floor: &mut FloorPlan,
+    now: Timestamp,
+) -> Result<Seated, SeatError> {
+    if waitlist.is_empty() {
+        return Err(SeatError::EmptyWaitlist);
+    }
+
+    let mut too_large = None;
+    let mut choice = None;
+    for entry in waitlist.iter() {
+        match floor.find_tables(&entry.party) {
+            Ok(tables) => {
+                choice = Some((entry.id, tables));
+                break;
+            }
+            Err(FloorError::PartyTooLarge { size, max }) => {
+                too_large.get_or_insert(SeatError::PartyTooLarge {
+                    party: entry.id,
+                    size,
+                    max,
+                });
+            }
+            Err(_) => {}
+        }
+    }
+
+    let (id, tables) = match choice {
+        Some(choice) => choice,
+        None => return Err(too_large.unwrap_or(SeatError::NoTableAvailable)),
+    };
+    floor
+        .seat(&tables, id)
+        .expect("find_tables only returns free tables");
+    Ok(waitlist
+        .seat(id, tables, now)
+        .expect("the party was just found on the waitlist"))
+}
@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+
+use super::table::{Table, TableNumber, TableState};
+use super::waitlist::{Party, PartyId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FloorError {
+    UnknownTable(TableNumber),
+    /// A table with that number is already on the floor.
+    DuplicateTable(TableNumber),
+    /// The table is not in a state that allows the requested change.
+    InvalidState {
+        table: TableNumber,
+        state: TableState,
+        action: &'static str,
+    },
+    /// Even joining every table the party would accept is not enough.
+    PartyTooLarge {
+        size: u8,
+        max: u8,
+    },
+    /// The party would fit, just not right now.
+    NoTableAvailable {
+        size: u8,
+    },
+}
+
+impl fmt::Display for FloorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloorError::UnknownTable(table) => write!(f, "there is no table {}", table),
+            FloorError::DuplicateTable(table) => write!(f, "there already is a table {}", table),
+            FloorError::InvalidState {
+                table,
+                state,
+                action,
+            } => write!(f, "cannot {} table {}: it is {}", action, table, state),
+            FloorError::PartyTooLarge { size, max } => write!(
+                f,
+                "a party of {} cannot be seated, the largest possible table seats {}",
+                size, max
+            ),
+            FloorError::NoTableAvailable { size } => {
+                write!(f, "no table is free for a party of {}", size)
+            }
+        }
+    }
+}
+
+impl Error for FloorError {}
+
+/// Every table in the dining room and how they can be joined.
+#[derive(Debug, Clone, Default)]
+pub struct FloorPlan {
+    tables: BTreeMap<TableNumber, Table>,
+}
+
+impl FloorPlan {
+    pub fn new() -> FloorPlan {
+        FloorPlan::default()
+    }
+
+    /// Adds a table. Neighbour links are made symmetric, so declaring a
+    /// join on either side is enough. Table numbers have to be unique.
+    pub fn add_table(&mut self, table: Table) -> Result<(), FloorError> {
+        if self.tables.contains_key(&table.number) {
+            return Err(FloorError::DuplicateTable(table.number));
+        }
+        for neighbour in &table.combines_with {
+            if let Some(other) = self.tables.get_mut(neighbour) {
+                if !other.combines_with.contains(&table.number) {
+                    other.combines_with.push(table.number);
+                }
+            }
+        }
+        let mut table = table;
+        for other in self.tables.values() {
+            if other.combines_with.contains(&table.number)
+                && !table.combines_with.contains(&other.number)
+            {
+                table.combines_with.push(other.number);
+            }
+        }
+        self.tables.insert(table.number, table);
+        Ok(())
+    }
+
+    pub fn table(&self, number: TableNumber) -> Option<&Table> {
+        self.tables.get(&number)
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = &Table> {
+        self.tables.values()
+    }
+
+    pub fn tables_in_section<'a>(&'a self, section: &'a str) -> impl Iterator<Item = &'a Table> {
+        self.tables.values().filter(move |t| t.section == section)
+    }
+
+    /// The tables currently holding `party`.
+    pub fn tables_of(&self, party: PartyId) -> Vec<TableNumber> {
+        self.tables
+            .values()
+            .filter(|t| t.state == TableState::Occupied(party))
+            .map(|t| t.number)
+            .collect()
+    }
+
+    /// The biggest party that could ever sit here while keeping `party`'s
+    /// preferences, i.e. the largest group of joinable tables it accepts.
+    pub fn max_party_size(&self, party: &Party) -> u8 {
+        let mut seen = BTreeSet::new();
+        let mut max = 0u16;
+        for table in self.tables.values() {
+            if seen.contains(&table.number) || !party.accepts(table) {
+                continue;
+            }
+            let group = self.connected(table.number, |t| party.accepts(t));
+            let seats = group.iter().map(|n| self.tables[n].seats as u16).sum();
+            max = max.max(seats);
+            seen.extend(group);
+        }
+        max.min(u8::MAX as u16) as u8
+    }
+
+    /// Refuses parties the dining room can never hold.
+    pub fn check_party(&self, party: &Party) -> Result<(), FloorError> {
+        let max = self.max_party_size(party);
+        if party.size > max {
+            return Err(FloorError::PartyTooLarge {
+                size: party.size,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Picks the free tables to seat `party` at: the smallest single table
+    /// that fits, or failing that the smallest group of adjacent free tables.
+    pub fn find_tables(&self, party: &Party) -> Result<Vec<TableNumber>, FloorError> {
+        self.find_tables_where(party, Table::is_free)
+    }
+
+    /// Like `find_tables`, but `available` decides which tables may be used
+    /// instead of their current state, e.g. to plan for later in the day.
+    pub fn find_tables_where(
+        &self,
+        party: &Party,
+        available: impl Fn(&Table) -> bool,
+    ) -> Result<Vec<TableNumber>, FloorError> {
+        self.check_party(party)?;
+
+        let single = self
+            .tables
+            .values()
+            .filter(|t| available(t) && party.fits(t))
+            .min_by_key(|t| (t.seats, t.number));
+        if let Some(table) = single {
+            return Ok(vec![table.number]);
+        }
+
+        let mut best: Option<(u16, Vec<TableNumber>)> = None;
+        for start in self.tables.values() {
+            if !available(start) || !party.accepts(start) {
+                continue;
+            }
+            if let Some(group) = self.grow_group(start.number, party, &available) {
+                let seats = group.iter().map(|n| self.tables[n].seats as u16).sum();
+                let better = match &best {
+                    Some((best_seats, best_group)) => {
+                        (seats, group.len()) < (*best_seats, best_group.len())
+                    }
+                    None => true,
+                };
+                if better {
+                    best = Some((seats, group));
+                }
+            }
+        }
+
+        best.map(|(_, group)| group)
+            .ok_or(FloorError::NoTableAvailable { size: party.size })
+    }
+
+    /// Marks `tables` as occupied by `party`. Only free or reserved tables
+    /// can be sat.
+    pub fn seat(&mut self, tables: &[TableNumber], party: PartyId) -> Result<(), FloorError> {
+        for number in tables {
+            let table = self.get(*number)?;
+            if !matches!(table.state, TableState::Free | TableState::Reserved) {
+                return Err(FloorError::InvalidState {
+                    table: *number,
+                    state: table.state,
+                    action: "seat",
+                });
+            }
+        }
+        for number in tables {
+            self.get_mut(*number)?.state = TableState::Occupied(party);
+        }
+        Ok(())
+    }
+
+    /// The party got up; all of its tables need bussing.
+    pub fn vacate(&mut self, party: PartyId) -> Vec<TableNumber> {
+        let tables = self.tables_of(party);
+        for number in &tables {
+            if let Some(table) = self.tables.get_mut(number) {
+                table.state = TableState::Dirty;
+            }
+        }
+        tables
+    }
+
+    /// A dirty table has been cleaned and reset.
+    pub fn bus(&mut self, number: TableNumber) -> Result<(), FloorError> {
+        self.transition(number, TableState::Dirty, TableState::Free, "bus")
+    }
+
+    pub fn reserve(&mut self, number: TableNumber) -> Result<(), FloorError> {
+        self.transition(number, TableState::Free, TableState::Reserved, "reserve")
+    }
+
+    pub fn release(&mut self, number: TableNumber) -> Result<(), FloorError> {
+        self.transition(number, TableState::Reserved, TableState::Free, "release")
+    }
+
+    fn transition(
+        &mut self,
+        number: TableNumber,
+        from: TableState,
+        to: TableState,
+        action: &'static str,
+    ) -> Result<(), FloorError> {
+        let table = self.get_mut(number)?;
+        if table.state != from {
+            return Err(FloorError::InvalidState {
+                table: number,
+                state: table.state,
+                action,
+            });
+        }
+        table.state = to;
+        Ok(())
+    }
+
+    fn get(&self, number: TableNumber) -> Result<&Table, FloorError> {
+        self.tables
+            .get(&number)
+            .ok_or(FloorError::UnknownTable(number))
+    }
+
+    fn get_mut(&mut self, number: TableNumber) -> Result<&mut Table, FloorError> {
+        self.tables
+            .get_mut(&number)
+            .ok_or(FloorError::UnknownTable(number))
+    }
+
+    // All tables reachable from `start` through joins, walking only over
+    // tables that pass `keep`.
+    fn connected(&self, start: TableNumber, keep: impl Fn(&Table) -> bool) -> Vec<TableNumber> {
+        let mut group = vec![start];
+        let mut index = 0;
+        while index < group.len() {
+            let table = &self.tables[&group[index]];
+            for neighbour in &table.combines_with {
+                if group.contains(neighbour) {
+                    continue;
+                }
+                if let Some(other) = self.tables.get(neighbour) {
+                    if keep(other) {
+                        group.push(*neighbour);
+                    }
+                }
+            }
+            index += 1;
+        }
+        group
+    }
+
+    // Starting from one available table, keeps pulling in the largest
+    // available neighbour until the party fits or there is nothing to join.
+    fn grow_group(
+        &self,
+        start: TableNumber,
+        party: &Party,
+        available: &impl Fn(&Table) -> bool,
+    ) -> Option<Vec<TableNumber>> {
+        let mut group = vec![start];
+        let mut seats = self.tables[&start].seats as u16;
+        while seats < party.size as u16 {
+            let next = group
+                .iter()
+                .flat_map(|n| self.tables[n].combines_with.iter())
+                .filter(|n| !group.contains(n))
+                .filter_map(|n| self.tables.get(n))
+                .filter(|t| available(t) && party.accepts(t))
+                .max_by_key(|t| (t.seats, std::cmp::Reverse(t.number)))?;
+            seats += next.seats as u16;
+            group.push(next.number);
+        }
+        group.sort();
+        Some(group)
+    }
+}
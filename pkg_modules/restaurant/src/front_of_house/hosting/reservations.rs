@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::floor::{FloorError, FloorPlan};
+use super::table::{SeatingArea, TableNumber, TableState};
+use super::waitlist::{Party, PartyId, Priority, Waitlist};
+use crate::time::{Date, TimeOfDay, Timestamp};
+
+/// How late a party may be before the host can mark them as a no-show.
+pub const NO_SHOW_GRACE: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ReservationId(pub u32);
+
+impl fmt::Display for ReservationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reservation {}", self.0)
+    }
+}
+
+/// How long we expect a party to keep its table, by party size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnTimes {
+    // (largest party size, turn time), sorted by size
+    by_size: Vec<(u8, Duration)>,
+    largest: Duration,
+}
+
+impl Default for TurnTimes {
+    fn default() -> TurnTimes {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        TurnTimes::new(minutes(120))
+            .up_to(2, minutes(75))
+            .up_to(4, minutes(90))
+            .up_to(6, minutes(105))
+    }
+}
+
+impl TurnTimes {
+    /// `largest` is used for parties bigger than any size given with `up_to`.
+    pub fn new(largest: Duration) -> TurnTimes {
+        TurnTimes {
+            by_size: Vec::new(),
+            largest,
+        }
+    }
+
+    /// Parties of up to `size` guests stay for `turn`.
+    pub fn up_to(mut self, size: u8, turn: Duration) -> TurnTimes {
+        self.by_size.retain(|(s, _)| *s != size);
+        self.by_size.push((size, turn));
+        self.by_size.sort();
+        self
+    }
+
+    pub fn for_size(&self, size: u8) -> Duration {
+        self.by_size
+            .iter()
+            .find(|(max, _)| size <= *max)
+            .map_or(self.largest, |(_, turn)| *turn)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReservationStatus {
+    Booked,
+    /// The party arrived and is on the waitlist (or already seated).
+    CheckedIn(PartyId),
+    /// The checked-in party has left and their tables are free for others.
+    Finished,
+    NoShow,
+    Cancelled,
+}
+
+impl fmt::Display for ReservationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationStatus::Booked => write!(f, "booked"),
+            ReservationStatus::CheckedIn(party) => write!(f, "checked in as {}", party),
+            ReservationStatus::Finished => write!(f, "finished"),
+            ReservationStatus::NoShow => write!(f, "a no-show"),
+            ReservationStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// What the guest asks for on the phone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationRequest {
+    pub name: String,
+    pub size: u8,
+    pub at: Timestamp,
+    pub needs_accessible: bool,
+    pub area: Option<SeatingArea>,
+}
+
+impl ReservationRequest {
+    pub fn new(name: &str, size: u8, date: Date, time: TimeOfDay) -> ReservationRequest {
+        ReservationRequest {
+            name: String::from(name),
+            size,
+            at: date.at(time),
+            needs_accessible: false,
+            area: None,
+        }
+    }
+
+    pub fn needs_accessible(mut self) -> ReservationRequest {
+        self.needs_accessible = true;
+        self
+    }
+
+    pub fn prefers(mut self, area: SeatingArea) -> ReservationRequest {
+        self.area = Some(area);
+        self
+    }
+
+    // The party as the floor plan sees it, arriving at `arrived`.
+    fn party(&self, arrived: Timestamp) -> Party {
+        let mut party = Party::new(&self.name, self.size, arrived).priority(Priority::High);
+        party.needs_accessible = self.needs_accessible;
+        party.area = self.area;
+        party
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub request: ReservationRequest,
+    /// How long the tables are blocked for.
+    pub turn: Duration,
+    pub tables: Vec<TableNumber>,
+    pub status: ReservationStatus,
+}
+
+impl Reservation {
+    pub fn starts(&self) -> Timestamp {
+        self.request.at
+    }
+
+    pub fn ends(&self) -> Timestamp {
+        self.request.at + self.turn
+    }
+
+    /// Whether the party is still expected.
+    pub fn is_active(&self) -> bool {
+        self.status == ReservationStatus::Booked
+    }
+
+    /// Whether the booking still blocks its tables: until the party is
+    /// expected and, once checked in, until they leave. Either way only for
+    /// the turn it was booked for.
+    pub fn blocks_tables(&self) -> bool {
+        matches!(
+            self.status,
+            ReservationStatus::Booked | ReservationStatus::CheckedIn(_)
+        )
+    }
+
+    fn overlaps(&self, start: Timestamp, end: Timestamp) -> bool {
+        self.starts() < end && start < self.ends()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReservationError {
+    UnknownReservation(ReservationId),
+    /// The floor cannot hold the party at all.
+    PartyTooLarge {
+        size: u8,
+        max: u8,
+    },
+    /// Every table that would fit is booked around that time.
+    Conflict {
+        at: Timestamp,
+    },
+    InvalidStatus {
+        reservation: ReservationId,
+        status: ReservationStatus,
+    },
+    /// Too soon to give up on them.
+    NotLateYet {
+        reservation: ReservationId,
+        until: Timestamp,
+    },
+    Floor(FloorError),
+}
+
+impl fmt::Display for ReservationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReservationError::UnknownReservation(id) => write!(f, "{} does not exist", id),
+            ReservationError::PartyTooLarge { size, max } => write!(
+                f,
+                "a party of {} cannot be booked, the largest possible table seats {}",
+                size, max
+            ),
+            ReservationError::Conflict { at } => {
+                write!(
+                    f,
+                    "no table is free for that party on {} at {}",
+                    at.date(),
+                    at.time_of_day()
+                )
+            }
+            ReservationError::InvalidStatus {
+                reservation,
+                status,
+            } => write!(f, "{} is {}", reservation, status),
+            ReservationError::NotLateYet { reservation, until } => {
+                write!(
+                    f,
+                    "{} is not a no-show before {}",
+                    reservation,
+                    until.time_of_day()
+                )
+            }
+            ReservationError::Floor(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ReservationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReservationError::Floor(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FloorError> for ReservationError {
+    fn from(err: FloorError) -> ReservationError {
+        match err {
+            FloorError::PartyTooLarge { size, max } => {
+                ReservationError::PartyTooLarge { size, max }
+            }
+            err => ReservationError::Floor(err),
+        }
+    }
+}
+
+/// The host stand's book of reservations.
+#[derive(Debug, Clone, Default)]
+pub struct ReservationBook {
+    reservations: Vec<Reservation>,
+    turn_times: TurnTimes,
+    next_id: u32,
+    /// Which reservation each table on the floor was reserved for.
+    held: BTreeMap<TableNumber, ReservationId>,
+}
+
+impl ReservationBook {
+    pub fn new(turn_times: TurnTimes) -> ReservationBook {
+        ReservationBook {
+            reservations: Vec::new(),
+            turn_times,
+            next_id: 0,
+            held: BTreeMap::new(),
+        }
+    }
+
+    pub fn turn_times(&self) -> &TurnTimes {
+        &self.turn_times
+    }
+
+    /// Books the party onto tables that are not already promised to someone
+    /// else while they would be sitting there.
+    pub fn book(
+        &mut self,
+        floor: &FloorPlan,
+        request: ReservationRequest,
+    ) -> Result<ReservationId, ReservationError> {
+        let turn = self.turn_times.for_size(request.size);
+        let start = request.at;
+        let end = start + turn;
+
+        let party = request.party(start);
+        let tables = floor
+            .find_tables_where(&party, |table| {
+                self.conflicts(table.number, start, end).is_empty()
+            })
+            .map_err(|err| match err {
+                FloorError::NoTableAvailable { .. } => ReservationError::Conflict { at: start },
+                err => err.into(),
+            })?;
+
+        self.next_id += 1;
+        let id = ReservationId(self.next_id);
+        self.reservations.push(Reservation {
+            id,
+            request,
+            turn,
+            tables,
+            status: ReservationStatus::Booked,
+        });
+        Ok(id)
+    }
+
+    /// Bookings holding `table` at any point between `start` and `end`.
+    pub fn conflicts(
+        &self,
+        table: TableNumber,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Vec<ReservationId> {
+        self.reservations
+            .iter()
+            .filter(|r| r.blocks_tables() && r.tables.contains(&table) && r.overlaps(start, end))
+            .map(|r| r.id)
+            .collect()
+    }
+
+    pub fn get(&self, id: ReservationId) -> Result<&Reservation, ReservationError> {
+        self.reservations
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or(ReservationError::UnknownReservation(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Reservation> {
+        self.reservations.iter()
+    }
+
+    /// The day's bookings in time order.
+    pub fn on(&self, date: Date) -> Vec<&Reservation> {
+        let mut day: Vec<&Reservation> = self
+            .reservations
+            .iter()
+            .filter(|r| r.starts().date() == date)
+            .collect();
+        day.sort_by_key(|r| (r.starts(), r.id));
+        day
+    }
+
+    /// Still-booked parties expected within `within` from `now`.
+    pub fn upcoming(&self, now: Timestamp, within: Duration) -> Vec<&Reservation> {
+        let mut soon: Vec<&Reservation> = self
+            .reservations
+            .iter()
+            .filter(|r| r.is_active() && r.starts() >= now && r.starts() <= now + within)
+            .collect();
+        soon.sort_by_key(|r| (r.starts(), r.id));
+        soon
+    }
+
+    /// Marks the booked tables as reserved on the floor so nobody sits a
+    /// walk-in there. Tables that are still busy are left alone.
+    pub fn hold(
+        &mut self,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+    ) -> Result<(), ReservationError> {
+        for table in self.active(id)?.tables.clone() {
+            if floor.table(table).is_some_and(|t| t.is_free()) {
+                floor.reserve(table)?;
+                self.held.insert(table, id);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cancel(
+        &mut self,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+    ) -> Result<(), ReservationError> {
+        self.close(floor, id, ReservationStatus::Cancelled)
+    }
+
+    pub fn mark_no_show(
+        &mut self,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+        now: Timestamp,
+    ) -> Result<(), ReservationError> {
+        let until = self.active(id)?.starts() + NO_SHOW_GRACE;
+        if now < until {
+            return Err(ReservationError::NotLateYet {
+                reservation: id,
+                until,
+            });
+        }
+        self.close(floor, id, ReservationStatus::NoShow)
+    }
+
+    /// The party has arrived: their held tables are released and they go to
+    /// the front of the waitlist, so the next `seat_at_table` seats them.
+    pub fn check_in(
+        &mut self,
+        waitlist: &mut Waitlist,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+        now: Timestamp,
+    ) -> Result<PartyId, ReservationError> {
+        self.release_held(floor, id)?;
+        let reservation = self.active_mut(id)?;
+        let party = waitlist.add(reservation.request.party(now)).id;
+        reservation.status = ReservationStatus::CheckedIn(party);
+        Ok(party)
+    }
+
+    /// The party has left, so a checked-in booking no longer blocks its
+    /// tables for the rest of its turn.
+    pub fn vacated(&mut self, party: PartyId) {
+        if let Some(reservation) = self
+            .reservations
+            .iter_mut()
+            .find(|r| r.status == ReservationStatus::CheckedIn(party))
+        {
+            reservation.status = ReservationStatus::Finished;
+        }
+    }
+
+    fn close(
+        &mut self,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+        status: ReservationStatus,
+    ) -> Result<(), ReservationError> {
+        self.release_held(floor, id)?;
+        self.active_mut(id)?.status = status;
+        Ok(())
+    }
+
+    // Frees the tables reserved for `id`; a table reserved for someone
+    // else stays reserved.
+    fn release_held(
+        &mut self,
+        floor: &mut FloorPlan,
+        id: ReservationId,
+    ) -> Result<(), ReservationError> {
+        for table in self.active(id)?.tables.clone() {
+            if self.held.get(&table) != Some(&id) {
+                continue;
+            }
+            self.held.remove(&table);
+            if floor
+                .table(table)
+                .is_some_and(|t| t.state == TableState::Reserved)
+            {
+                floor.release(table)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn active(&self, id: ReservationId) -> Result<&Reservation, ReservationError> {
+        let reservation = self.get(id)?;
+        if !reservation.is_active() {
+            return Err(ReservationError::InvalidStatus {
+                reservation: id,
+                status: reservation.status,
+            });
+        }
+        Ok(reservation)
+    }
+
+    fn active_mut(&mut self, id: ReservationId) -> Result<&mut Reservation, ReservationError> {
+        self.active(id)?;
+        Ok(self
+            .reservations
+            .iter_mut()
+            .find(|r| r.id == id)
+            .expect("the reservation was just found"))
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::waitlist::PartyId;
+
+/// Where in the restaurant a table stands, and what a party can ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SeatingArea {
+    Indoor,
+    Outdoor,
+    Bar,
+}
+
+/// A number painted on the table so hosts and servers agree on which one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TableNumber(pub u16);
+
+impl fmt::Display for TableNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "T{}", self.0)
+    }
+}
+
+/// What a table is doing right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum TableState {
+    #[default]
+    Free,
+    Reserved,
+    Occupied(PartyId),
+    Dirty,
+}
+
+impl fmt::Display for TableState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableState::Free => write!(f, "free"),
+            TableState::Reserved => write!(f, "reserved"),
+            TableState::Occupied(party) => write!(f, "occupied by {}", party),
+            TableState::Dirty => write!(f, "dirty"),
+        }
+    }
+}
+
+/// One table on the floor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub number: TableNumber,
+    pub seats: u8,
+    pub area: SeatingArea,
+    pub accessible: bool,
+    /// The server section the table belongs to.
+    pub section: String,
+    /// Tables that can be pushed together with this one for a bigger party.
+    pub combines_with: Vec<TableNumber>,
+    pub state: TableState,
+}
+
+impl Table {
+    pub fn new(number: u16, seats: u8, area: SeatingArea) -> Table {
+        Table {
+            number: TableNumber(number),
+            seats,
+            area,
+            accessible: false,
+            section: String::new(),
+            combines_with: Vec::new(),
+            state: TableState::Free,
+        }
+    }
+
+    /// Marks the table as reachable and usable from a wheelchair.
+    pub fn accessible(mut self) -> Table {
+        self.accessible = true;
+        self
+    }
+
+    pub fn in_section(mut self, section: &str) -> Table {
+        self.section = String::from(section);
+        self
+    }
+
+    /// Declares which tables this one can be joined with.
+    pub fn combines_with(mut self, neighbours: &[u16]) -> Table {
+        self.combines_with = neighbours.iter().map(|&n| TableNumber(n)).collect();
+        self
+    }
+
+    pub fn is_free(&self) -> bool {
+        self.state == TableState::Free
+    }
+}
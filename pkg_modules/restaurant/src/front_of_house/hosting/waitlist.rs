@@ -0,0 +1,261 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::table::{SeatingArea, Table, TableNumber};
+use crate::time::Timestamp;
+
+/// How long we tell guests to wait for every party ahead of them, until the
+/// host tunes it for the night.
+pub const DEFAULT_PACE: Duration = Duration::from_secs(10 * 60);
+
+/// Handed out when a party joins the waitlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PartyId(pub u32);
+
+impl fmt::Display for PartyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Parties with a higher priority are called before anyone who arrived
+/// earlier with a lower one. Within a priority it is first come, first served.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Priority {
+    #[default]
+    Normal,
+    High,
+}
+
+/// A group of guests waiting together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Party {
+    pub name: String,
+    pub size: u8,
+    pub arrived: Timestamp,
+    pub needs_accessible: bool,
+    pub area: Option<SeatingArea>,
+    pub priority: Priority,
+}
+
+impl Party {
+    pub fn new(name: &str, size: u8, arrived: Timestamp) -> Party {
+        Party {
+            name: String::from(name),
+            size,
+            arrived,
+            needs_accessible: false,
+            area: None,
+            priority: Priority::Normal,
+        }
+    }
+
+    /// The party needs a wheelchair-accessible table.
+    pub fn needs_accessible(mut self) -> Party {
+        self.needs_accessible = true;
+        self
+    }
+
+    /// The party will only sit in this area.
+    pub fn prefers(mut self, area: SeatingArea) -> Party {
+        self.area = Some(area);
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Party {
+        self.priority = priority;
+        self
+    }
+
+    /// Whether `table` respects the party's preferences, ignoring size.
+    pub fn accepts(&self, table: &Table) -> bool {
+        (!self.needs_accessible || table.accessible)
+            && self.area.is_none_or(|area| area == table.area)
+    }
+
+    /// Whether the party can be sat at `table` on its own.
+    pub fn fits(&self, table: &Table) -> bool {
+        self.size <= table.seats && self.accepts(table)
+    }
+}
+
+/// A party on the waitlist, together with what the host told them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitlistEntry {
+    pub id: PartyId,
+    pub party: Party,
+    pub quoted_wait: Duration,
+}
+
+/// What the host gets back after successfully seating a party.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seated {
+    pub id: PartyId,
+    pub party: Party,
+    /// More than one table means they were pushed together.
+    pub tables: Vec<TableNumber>,
+    pub seated_at: Timestamp,
+    pub quoted_wait: Duration,
+    pub waited: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SeatError {
+    /// Nobody is waiting.
+    EmptyWaitlist,
+    /// People are waiting, but no table is free for any of them.
+    NoTableAvailable,
+    /// The first party that could not be seated is bigger than anything the
+    /// floor can offer, even with tables joined.
+    PartyTooLarge { party: PartyId, size: u8, max: u8 },
+}
+
+impl fmt::Display for SeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeatError::EmptyWaitlist => write!(f, "the waitlist is empty"),
+            SeatError::NoTableAvailable => write!(f, "no table is free for anyone waiting"),
+            SeatError::PartyTooLarge { party, size, max } => write!(
+                f,
+                "party {} of {} is larger than the largest possible table of {}",
+                party, size, max
+            ),
+        }
+    }
+}
+
+impl Error for SeatError {}
+
+/// The host stand's list of walk-ins, kept in the order they will be called.
+#[derive(Debug, Clone)]
+pub struct Waitlist {
+    entries: Vec<WaitlistEntry>,
+    // everyone who has been sat so far, oldest first
+    seated: Vec<Seated>,
+    next_id: u32,
+    pace: Duration,
+}
+
+impl Default for Waitlist {
+    fn default() -> Waitlist {
+        Waitlist::with_pace(DEFAULT_PACE)
+    }
+}
+
+impl Waitlist {
+    pub fn new() -> Waitlist {
+        Waitlist::default()
+    }
+
+    /// `pace` is how long, on average, it takes for one party ahead in the
+    /// line to be seated.
+    pub fn with_pace(pace: Duration) -> Waitlist {
+        Waitlist {
+            entries: Vec::new(),
+            seated: Vec::new(),
+            next_id: 1,
+            pace,
+        }
+    }
+
+    pub fn set_pace(&mut self, pace: Duration) {
+        self.pace = pace;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Waiting parties in the order they will be called.
+    pub fn iter(&self) -> impl Iterator<Item = &WaitlistEntry> {
+        self.entries.iter()
+    }
+
+    pub fn get(&self, id: PartyId) -> Option<&WaitlistEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Zero-based place in line.
+    pub fn position(&self, id: PartyId) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.id == id)
+    }
+
+    /// How long `party` would be told to wait if it joined right now.
+    pub fn quote(&self, party: &Party) -> Duration {
+        let ahead = self.insertion_index(party);
+        self.pace * (ahead as u32 + 1)
+    }
+
+    pub fn add(&mut self, party: Party) -> &WaitlistEntry {
+        let id = PartyId(self.next_id);
+        self.next_id += 1;
+
+        let quoted_wait = self.quote(&party);
+        let index = self.insertion_index(&party);
+        self.entries.insert(
+            index,
+            WaitlistEntry {
+                id,
+                party,
+                quoted_wait,
+            },
+        );
+        &self.entries[index]
+    }
+
+    /// Takes a party off the list without seating it, e.g. when they leave.
+    pub fn remove(&mut self, id: PartyId) -> Option<WaitlistEntry> {
+        let index = self.position(id)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Takes the party off the list because it is being sat at `tables`.
+    pub fn seat(
+        &mut self,
+        id: PartyId,
+        tables: Vec<TableNumber>,
+        now: Timestamp,
+    ) -> Option<Seated> {
+        let entry = self.remove(id)?;
+        let seated = Seated {
+            id: entry.id,
+            waited: now.saturating_duration_since(entry.party.arrived),
+            party: entry.party,
+            tables,
+            seated_at: now,
+            quoted_wait: entry.quoted_wait,
+        };
+        self.seated.push(seated.clone());
+        Some(seated)
+    }
+
+    /// Every party seated from this list, in the order they were sat.
+    pub fn seated(&self) -> &[Seated] {
+        &self.seated
+    }
+
+    // Entries stay sorted by priority first and arrival second, so the new
+    // party goes after everyone who would be called before it.
+    fn insertion_index(&self, party: &Party) -> usize {
+        self.entries
+            .iter()
+            .position(|entry| {
+                entry.party.priority < party.priority
+                    || (entry.party.priority == party.priority
+                        && entry.party.arrived > party.arrived)
+            })
+            .unwrap_or(self.entries.len())
+    }
+}
@@ -0,0 +1,32 @@
+// serving is re-exported from the crate root, so these are the functions the
+// POS integration calls. Bills and payments are in billing.
+
+mod order;
+
+pub use self::order::{
+    Cents, LineItem, Modifier, Order, OrderBook, OrderError, OrderId, OrderStatus, StatusChange,
+};
+
+use crate::hosting::TableNumber;
+use crate::time::Timestamp;
+
+pub fn take_order(
+    orders: &mut OrderBook,
+    table: TableNumber,
+    items: Vec<LineItem>,
+    now: Timestamp,
+) -> OrderId {
+    let id = orders.open(table, now);
+    let order = orders.get_mut(id).expect("the order was just opened");
+    for item in items {
+        order
+            .add_item(item)
+            .expect("a freshly taken order is editable");
+    }
+    id
+}
+
+// public
+pub fn serve_order(orders: &mut OrderBook, id: OrderId, now: Timestamp) -> Result<(), OrderError> {
+    orders.get_mut(id)?.serve(now)
+}
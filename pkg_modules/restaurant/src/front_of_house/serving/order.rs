@@ -0,0 +1,502 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::billing::Share;
+use crate::hosting::TableNumber;
+use crate::time::Timestamp;
+
+/// Money is always counted in cents so that totals never drift.
+pub type Cents = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OrderId(pub u32);
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "order {}", self.0)
+    }
+}
+
+/// A change to a dish, like "no onions" or "extra cheese".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifier {
+    pub name: String,
+    /// Added to the unit price, and may be negative.
+    pub price_delta: i64,
+}
+
+impl Modifier {
+    pub fn new(name: &str) -> Modifier {
+        Modifier {
+            name: String::from(name),
+            price_delta: 0,
+        }
+    }
+
+    pub fn priced(name: &str, price_delta: i64) -> Modifier {
+        Modifier {
+            name: String::from(name),
+            price_delta,
+        }
+    }
+}
+
+/// One line on the order pad.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineItem {
+    pub item: String,
+    pub quantity: u16,
+    pub unit_price: Cents,
+    /// The seat the dish goes to, counted clockwise from the host stand.
+    pub seat: Option<u8>,
+    /// Seats sharing the dish, e.g. an appetizer for the table. When set,
+    /// the bill splits the line between them instead of going by `seat`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shared: Vec<Share>,
+    pub modifiers: Vec<Modifier>,
+    pub notes: Option<String>,
+    /// On the house. The kitchen still makes it, the guest is not charged.
+    #[serde(default)]
+    pub comped: bool,
+}
+
+impl LineItem {
+    pub fn new(item: &str, unit_price: Cents) -> LineItem {
+        LineItem {
+            item: String::from(item),
+            quantity: 1,
+            unit_price,
+            seat: None,
+            shared: Vec::new(),
+            modifiers: Vec::new(),
+            notes: None,
+            comped: false,
+        }
+    }
+
+    pub fn quantity(mut self, quantity: u16) -> LineItem {
+        self.quantity = quantity;
+        self
+    }
+
+    pub fn for_seat(mut self, seat: u8) -> LineItem {
+        self.seat = Some(seat);
+        self
+    }
+
+    /// Split evenly between `seats`.
+    pub fn shared_by(mut self, seats: &[u8]) -> LineItem {
+        self.shared = seats.iter().map(|&seat| Share::new(seat, 1)).collect();
+        self
+    }
+
+    pub fn with_modifier(mut self, modifier: Modifier) -> LineItem {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> LineItem {
+        self.notes = Some(String::from(note));
+        self
+    }
+
+    /// Price of one portion including its modifiers, never below zero.
+    pub fn unit_total(&self) -> Cents {
+        let delta: i64 = self.modifiers.iter().map(|m| m.price_delta).sum();
+        (self.unit_price as i64 + delta).max(0) as Cents
+    }
+
+    pub fn total(&self) -> Cents {
+        if self.comped {
+            return 0;
+        }
+        self.unit_total() * self.quantity as Cents
+    }
+}
+
+/// Where an order is in its life. It only ever moves forward, except that it
+/// can be voided at any point before it is paid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OrderStatus {
+    Taken,
+    SentToKitchen,
+    Cooking,
+    Ready,
+    Served,
+    Paid,
+    Voided,
+}
+
+impl OrderStatus {
+    pub fn can_become(self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Taken, SentToKitchen)
+                | (SentToKitchen, Cooking)
+                | (Cooking, Ready)
+                | (Ready, Served)
+                | (Served, Paid)
+                | (Taken | SentToKitchen | Cooking | Ready | Served, Voided)
+        )
+    }
+
+    /// Paid and voided orders are closed for good.
+    pub fn is_closed(self) -> bool {
+        matches!(self, OrderStatus::Paid | OrderStatus::Voided)
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OrderStatus::Taken => "taken",
+            OrderStatus::SentToKitchen => "sent to kitchen",
+            OrderStatus::Cooking => "cooking",
+            OrderStatus::Ready => "ready",
+            OrderStatus::Served => "served",
+            OrderStatus::Paid => "paid",
+            OrderStatus::Voided => "voided",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrderError {
+    UnknownOrder(OrderId),
+    IllegalTransition {
+        order: OrderId,
+        from: OrderStatus,
+        to: OrderStatus,
+    },
+    /// Items can only be changed while the order is still on the pad.
+    NotEditable {
+        order: OrderId,
+        status: OrderStatus,
+    },
+    /// There is nothing to send to the kitchen.
+    EmptyOrder(OrderId),
+    NoSuchLine {
+        order: OrderId,
+        line: usize,
+    },
+    /// Shares need different seats and at least one part each.
+    InvalidShares {
+        order: OrderId,
+        line: usize,
+    },
+    /// The line is already on the rail. A dish that went out wrong is
+    /// refired from its ticket instead.
+    AlreadyFired {
+        order: OrderId,
+        line: usize,
+    },
+    /// Paid and voided orders are done with; nothing more is cooked for
+    /// them.
+    Closed {
+        order: OrderId,
+        status: OrderStatus,
+    },
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::UnknownOrder(order) => write!(f, "{} does not exist", order),
+            OrderError::IllegalTransition { order, from, to } => {
+                write!(f, "{} cannot go from {} to {}", order, from, to)
+            }
+            OrderError::NotEditable { order, status } => {
+                write!(f, "{} is {} and can no longer be changed", order, status)
+            }
+            OrderError::EmptyOrder(order) => write!(f, "{} has no items", order),
+            OrderError::NoSuchLine { order, line } => {
+                write!(f, "{} has no line {}", order, line)
+            }
+            OrderError::InvalidShares { order, line } => write!(
+                f,
+                "line {} of {} needs a different seat and at least one part per share",
+                line, order
+            ),
+            OrderError::AlreadyFired { order, line } => {
+                write!(f, "line {} of {} has already been fired", line, order)
+            }
+            OrderError::Closed { order, status } => {
+                write!(f, "{} is {} and closed", order, status)
+            }
+        }
+    }
+}
+
+impl Error for OrderError {}
+
+/// When an order reached a given status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusChange {
+    pub status: OrderStatus,
+    pub at: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub id: OrderId,
+    pub table: TableNumber,
+    pub notes: Vec<String>,
+    items: Vec<LineItem>,
+    status: OrderStatus,
+    history: Vec<StatusChange>,
+    fired: BTreeSet<usize>,
+}
+
+impl Order {
+    pub fn new(id: OrderId, table: TableNumber, now: Timestamp) -> Order {
+        Order {
+            id,
+            table,
+            items: Vec::new(),
+            notes: Vec::new(),
+            status: OrderStatus::Taken,
+            history: vec![StatusChange {
+                status: OrderStatus::Taken,
+                at: now,
+            }],
+            fired: BTreeSet::new(),
+        }
+    }
+
+    pub fn items(&self) -> &[LineItem] {
+        &self.items
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    /// Every status the order has been in, oldest first.
+    pub fn history(&self) -> &[StatusChange] {
+        &self.history
+    }
+
+    /// When the order entered `status`, if it ever did.
+    pub fn reached(&self, status: OrderStatus) -> Option<Timestamp> {
+        self.history
+            .iter()
+            .find(|change| change.status == status)
+            .map(|change| change.at)
+    }
+
+    /// Whether the kitchen has had `line` yet.
+    pub fn is_fired(&self, line: usize) -> bool {
+        self.fired.contains(&line)
+    }
+
+    pub(crate) fn mark_fired(&mut self, lines: &[usize]) {
+        self.fired.extend(lines);
+    }
+
+    pub fn subtotal(&self) -> Cents {
+        self.items.iter().map(LineItem::total).sum()
+    }
+
+    /// Adds a line and returns its index.
+    pub fn add_item(&mut self, item: LineItem) -> Result<usize, OrderError> {
+        self.ensure_editable()?;
+        self.items.push(item);
+        Ok(self.items.len() - 1)
+    }
+
+    pub fn remove_item(&mut self, line: usize) -> Result<LineItem, OrderError> {
+        self.ensure_editable()?;
+        if line >= self.items.len() {
+            return Err(OrderError::NoSuchLine {
+                order: self.id,
+                line,
+            });
+        }
+        Ok(self.items.remove(line))
+    }
+
+    /// Takes a line off the bill. Unlike other changes this is allowed
+    /// until the order is closed, since comps usually happen at the table.
+    pub fn comp_item(&mut self, line: usize) -> Result<&LineItem, OrderError> {
+        if self.status.is_closed() {
+            return Err(OrderError::NotEditable {
+                order: self.id,
+                status: self.status,
+            });
+        }
+        let order = self.id;
+        let item = self
+            .items
+            .get_mut(line)
+            .ok_or(OrderError::NoSuchLine { order, line })?;
+        item.comped = true;
+        Ok(item)
+    }
+
+    /// Splits a line between seats, or with no shares puts it back on its
+    /// own seat. Like comps, this can happen until the order is closed.
+    pub fn share_item(&mut self, line: usize, shares: Vec<Share>) -> Result<&LineItem, OrderError> {
+        if self.status.is_closed() {
+            return Err(OrderError::NotEditable {
+                order: self.id,
+                status: self.status,
+            });
+        }
+        let order = self.id;
+        let valid = shares.iter().enumerate().all(|(index, share)| {
+            share.parts > 0 && shares[..index].iter().all(|s| s.seat != share.seat)
+        });
+        let item = self
+            .items
+            .get_mut(line)
+            .ok_or(OrderError::NoSuchLine { order, line })?;
+        if !valid {
+            return Err(OrderError::InvalidShares { order, line });
+        }
+        item.shared = shares;
+        Ok(item)
+    }
+
+    pub fn add_note(&mut self, note: &str) {
+        self.notes.push(String::from(note));
+    }
+
+    pub fn send_to_kitchen(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        if self.items.is_empty() {
+            return Err(OrderError::EmptyOrder(self.id));
+        }
+        self.advance(OrderStatus::SentToKitchen, now)
+    }
+
+    pub fn start_cooking(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Cooking, now)
+    }
+
+    pub fn mark_ready(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Ready, now)
+    }
+
+    pub fn serve(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Served, now)
+    }
+
+    pub fn mark_paid(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Paid, now)
+    }
+
+    pub fn void(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        self.advance(OrderStatus::Voided, now)
+    }
+
+    /// Voids an order that was already paid. Everything on it has to be
+    /// refunded first, which is why this is not part of the normal
+    /// lifecycle.
+    pub(crate) fn void_paid(&mut self, now: Timestamp) -> Result<(), OrderError> {
+        if self.status != OrderStatus::Paid {
+            return Err(OrderError::IllegalTransition {
+                order: self.id,
+                from: self.status,
+                to: OrderStatus::Voided,
+            });
+        }
+        self.status = OrderStatus::Voided;
+        self.history.push(StatusChange {
+            status: OrderStatus::Voided,
+            at: now,
+        });
+        Ok(())
+    }
+
+    /// Moves the order to `next`, refusing anything the lifecycle forbids.
+    pub fn advance(&mut self, next: OrderStatus, now: Timestamp) -> Result<(), OrderError> {
+        if !self.status.can_become(next) {
+            return Err(OrderError::IllegalTransition {
+                order: self.id,
+                from: self.status,
+                to: next,
+            });
+        }
+        self.status = next;
+        self.history.push(StatusChange {
+            status: next,
+            at: now,
+        });
+        Ok(())
+    }
+
+    fn ensure_editable(&self) -> Result<(), OrderError> {
+        if self.status != OrderStatus::Taken {
+            return Err(OrderError::NotEditable {
+                order: self.id,
+                status: self.status,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Every order taken during the shift.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    orders: Vec<Order>,
+    next_id: u32,
+}
+
+impl Default for OrderBook {
+    fn default() -> OrderBook {
+        OrderBook {
+            orders: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl OrderBook {
+    pub fn new() -> OrderBook {
+        OrderBook::default()
+    }
+
+    pub fn open(&mut self, table: TableNumber, now: Timestamp) -> OrderId {
+        let id = OrderId(self.next_id);
+        self.next_id += 1;
+        self.orders.push(Order::new(id, table, now));
+        id
+    }
+
+    pub fn get(&self, id: OrderId) -> Result<&Order, OrderError> {
+        self.orders
+            .iter()
+            .find(|order| order.id == id)
+            .ok_or(OrderError::UnknownOrder(id))
+    }
+
+    pub fn get_mut(&mut self, id: OrderId) -> Result<&mut Order, OrderError> {
+        self.orders
+            .iter_mut()
+            .find(|order| order.id == id)
+            .ok_or(OrderError::UnknownOrder(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter()
+    }
+
+    /// Orders that are neither paid nor voided.
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders
+            .iter()
+            .filter(|order| !order.status().is_closed())
+    }
+
+    pub fn for_table(&self, table: TableNumber) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter(move |order| order.table == table)
+    }
+}
@@ -1,79 +1,129 @@
+mod back_of_house;
+pub mod config;
+pub mod events;
 mod front_of_house;
+pub mod prelude;
+pub mod report;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shift;
+pub mod simulation;
+pub mod staff;
+pub mod state;
+pub mod store;
+pub mod time;
 
 // [ TITLE ] Modules access modifier
 
 // Items in a parent module can’t use the private items inside child modules
 // but items in child modules can use the items in their ancestor modules
 
-fn deliver_order() {}
-
-mod back_of_house {
-
-    // if we make an enum public, all of its variants are then public.
-    // We only need the pub before the enum keyword,
-    pub enum Appetizer {
-        Soup,
-        Salad,
+// Sends an order whose food is up out of the door: it is bagged, given to
+// the next free courier if it is going somewhere, and handed over.
+pub fn deliver_order(
+    board: &mut delivery::DeliveryBoard,
+    order: serving::OrderId,
+    now: time::Timestamp,
+) -> std::result::Result<&delivery::Delivery, delivery::DeliveryError> {
+    let current = board.for_order(order)?;
+    let id = current.id;
+    let needs_courier = current.needs_courier() && current.courier.is_none();
+    if needs_courier && board.couriers().next_available().is_none() {
+        return Err(delivery::DeliveryError::NoCourierAvailable);
     }
 
-    // access modifier for struct and its fields are seprated.
-    // public struct
-    pub struct Breakfast {
-        pub toast: String,      // public field
-        seasonal_fruit: String, // private field
+    if current.status == delivery::DeliveryStatus::Preparing {
+        board.pack(id, now)?;
     }
-
-    impl Breakfast {
-        pub fn summer(toast: &str) -> Breakfast {
-            Breakfast {
-                toast: String::from(toast),
-                seasonal_fruit: String::from("peaches"),
-            }
-        }
-    }
-
-    fn fix_incorrect_order() {
-
-        cook_order();
-        // use super keyword to refers to deliver_order parent module's function .
-        super::deliver_order();
+    if needs_courier {
+        board.assign(id)?;
     }
-
-    fn cook_order() {}
+    board.hand_off(id, now)?;
+    board.get(id)
 }
 
 // use crate::front_of_house::hosting;
-// Before this change, external code would have to call the add_to_waitlist function 
+// Before this change, external code would have to call the add_to_waitlist function
 // by using the path restaurant::front_of_house::hosting::add_to_waitlist(),
 // which also would have required the front_of_house module to be marked as pub.
 // Now that this pub use has re-exported the hosting module from the root module,
 // external code can use the path restaurant::hosting::add_to_waitlist() instead.
 pub use crate::front_of_house::hosting;
+// delivery and takeout sit right next to it.
+pub use crate::front_of_house::delivery;
+// serving gets the same treatment now that the POS integration needs it,
+// and so does billing.
+pub use crate::front_of_house::billing;
+pub use crate::front_of_house::serving;
+// and the kitchen, even though the rest of back_of_house stays private.
+pub use crate::back_of_house::inventory;
+pub use crate::back_of_house::kitchen;
+pub use crate::back_of_house::menu;
+
+pub mod customer {
+    // a module declared inline can still keep its children in files:
+    // these live in src/customer/
+    mod book;
+    mod loyalty;
+    mod profile;
+
+    pub use self::book::{CustomerBook, CustomerError};
+    pub use self::loyalty::{LoyaltyGateway, LoyaltyProgram};
+    pub use self::profile::{CustomerId, DietaryWarning, Favourite, Profile, Visit};
+
+    use super::back_of_house;
+    use crate::hosting;
+    use crate::inventory::Inventory;
+    use crate::shift::Shift;
+    use crate::time::Timestamp;
 
-mod customer {
-    
     pub fn eat_at_restaurant() {
+        let mut floor = hosting::FloorPlan::new();
+        floor
+            .add_table(hosting::Table::new(1, 2, hosting::SeatingArea::Indoor).combines_with(&[2]))
+            .expect("the tables have different numbers");
+        floor
+            .add_table(hosting::Table::new(2, 2, hosting::SeatingArea::Indoor))
+            .expect("the tables have different numbers");
+        // everything goes through the shift, so it all ends up in its log
+        let mut shift = Shift::new(floor, Inventory::default());
+        let arrived = Timestamp::now();
+
         // abs path
-        // crate::front_of_house::hosting::add_to_waitlist();
+        // crate::front_of_house::hosting::Party::new(...);
         // because of use at top of this module.
-        add_to_waitlist();
+        let ferris = hosting::Party::new("Ferris", 2, arrived);
+        shift
+            .add_to_waitlist(ferris)
+            .expect("an in-memory log cannot fail");
 
         // relate path
-        front_of_house::hosting::add_to_waitlist();
-        
+        let corro = super::front_of_house::hosting::Party::new("Corro", 4, arrived);
+        shift
+            .add_to_waitlist(corro)
+            .expect("an in-memory log cannot fail");
+
+        while let Ok(seated) = shift.seat_next(Timestamp::now()) {
+            println!(
+                "{} party of {} to {:?}",
+                seated.party.name, seated.party.size, seated.tables
+            );
+        }
+
         // Order a breakfast in the summer with Rye toast
         let mut meal = back_of_house::Breakfast::summer("Rye");
         // Change our mind about what bread we'd like
-        meal.toast = String.from("Wheat");
+        meal.toast = String::from("Wheat");
         println!("I'd like {} toast please", meal.toast);
-        
+
         // The next line won't compile if we uncomment it; we're not allowed
-        // to see or modify the seasonal fruit that comes with the meal
+        // to modify the seasonal fruit that comes with the meal
         // meal.seasonal_fruit = String::from("blueberries");
+        // but we can ask what it is
+        println!("It comes with {}", meal.seasonal_fruit());
 
-        let order1 = back_of_house::Appetizer::Soup;
-        let order2 = back_of_house::Appetizer::Salad;
-
+        let _order1 = back_of_house::Appetizer::Soup;
+        let _order2 = back_of_house::Appetizer::Salad;
     }
 }
 
@@ -81,10 +131,14 @@ mod customer {
 use std::fmt::Result;
 use std::io::Result as IoResult;
 
+#[allow(dead_code)]
 fn function1() -> Result {
     // --snip--
+    Ok(())
 }
 
+#[allow(dead_code)]
 fn function2() -> IoResult<()> {
     // --snip--
-}
\ No newline at end of file
+    Ok(())
+}
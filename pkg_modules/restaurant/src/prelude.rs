@@ -0,0 +1,38 @@
+//! The types most code that runs a restaurant needs, for a glob import.
+//!
+//! ```
+//! use restaurant::prelude::*;
+//!
+//! let mut floor = FloorPlan::new();
+//! floor.add_table(Table::new(1, 4, SeatingArea::Indoor))?;
+//! let mut shift = Shift::new(floor, Inventory::default());
+//! let mut menu = Menu::standard();
+//! let now = Timestamp::from_secs(12 * 60 * 60);
+//!
+//! shift.add_to_waitlist(Party::new("Ferris", 2, now))?;
+//! let seated = shift.seat_next(now)?;
+//! let soup = menu.order("Soup", 2, &[], now)?;
+//! let order = shift.take_order(seated.tables[0], vec![soup], now)?;
+//! shift.send_to_kitchen(order, now)?;
+//! shift.cook_order(order, now)?;
+//! shift.mark_ready(order, now)?;
+//! shift.serve_order(order, now)?;
+//!
+//! let check = shift.open_bill(order, now)?.checks()[0].clone();
+//! let mut gateway = InMemoryGateway::new();
+//! shift.take_payment(order, check.id, Tender::Cash(check.total()), &mut gateway, now)?;
+//! assert_eq!(shift.orders().get(order)?.status(), OrderStatus::Paid);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use crate::billing::{
+    Bill, CheckId, Discount, InMemoryGateway, PaymentGateway, PricingRules, Rate, Tender,
+};
+pub use crate::hosting::{FloorPlan, Party, PartyId, SeatingArea, Table, TableNumber};
+pub use crate::inventory::Inventory;
+pub use crate::kitchen::{Course, CourseScheduler, KitchenQueue, Station, StationRouter, TicketId};
+pub use crate::menu::{Category, Menu, MenuItem};
+pub use crate::serving::{Cents, LineItem, Order, OrderId, OrderStatus};
+pub use crate::shift::{Shift, ShiftError};
+pub use crate::staff::{Role, Roster, StaffId};
+pub use crate::time::{Clock, ManualClock, SystemClock, Timestamp};
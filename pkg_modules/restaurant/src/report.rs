@@ -0,0 +1,304 @@
+// End-of-day numbers for the manager: what sold, how many people came in,
+// how fast tables turned, how honest the waitlist quotes were and how long
+// the kitchen took. Everything is worked out from a finished Shift, and the
+// report can be printed as plain-text tables, CSV or JSON.
+
+mod render;
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::events::Event;
+use crate::hosting::PartyId;
+use crate::kitchen::Station;
+use crate::menu::{Category, Menu};
+use crate::serving::{Cents, OrderStatus};
+use crate::shift::Shift;
+use crate::time::Timestamp;
+
+/// A quoted wait counts as accurate when the party was sat within this many
+/// seconds of it, either way.
+pub const ACCURATE_WITHIN: u64 = 5 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategorySales {
+    /// `None` for dishes that are no longer on the menu.
+    pub category: Option<Category>,
+    pub quantity: u32,
+    pub revenue: Cents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemSales {
+    pub item: String,
+    pub category: Option<Category>,
+    pub quantity: u32,
+    pub revenue: Cents,
+}
+
+/// Money taken on paid orders, as their bills priced them. Voided and
+/// unpaid orders are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Sales {
+    pub orders: usize,
+    /// Net of comps, happy hour and discounts, before tax.
+    pub revenue: Cents,
+    /// Comps, happy hour and discounts taken off menu prices.
+    pub adjustments: Cents,
+    pub tax: Cents,
+    /// Automatic gratuity added to large parties' bills.
+    pub gratuity: Cents,
+    pub tips: Cents,
+    pub by_category: Vec<CategorySales>,
+    /// Best sellers first.
+    pub by_item: Vec<ItemSales>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Checks {
+    pub count: usize,
+    /// Average check before tax, gratuity and tips.
+    pub average: Cents,
+    /// Revenue divided by covers.
+    pub per_cover: Cents,
+}
+
+/// How long parties sat, from being seated to leaving the table.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct TableTurns {
+    pub turns: usize,
+    pub average_secs: u64,
+    pub longest_secs: u64,
+}
+
+/// Quoted waits against what parties actually waited.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct WaitAccuracy {
+    pub parties: usize,
+    pub average_quoted_secs: u64,
+    pub average_waited_secs: u64,
+    /// Positive when parties waited longer than they were told.
+    pub average_error_secs: i64,
+    pub within_quote: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StationTimes {
+    pub station: Station,
+    pub tickets: usize,
+    pub average_secs: u64,
+    pub longest_secs: u64,
+}
+
+/// From a ticket being fired to it being bumped. Tickets still on the rail
+/// are not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct TicketTimes {
+    pub tickets: usize,
+    pub refires: usize,
+    pub average_secs: u64,
+    pub longest_secs: u64,
+    pub by_station: Vec<StationTimes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct ShiftReport {
+    pub sales: Sales,
+    pub covers: u32,
+    pub checks: Checks,
+    pub turns: TableTurns,
+    pub waits: WaitAccuracy,
+    pub tickets: TicketTimes,
+}
+
+impl ShiftReport {
+    /// `menu` is only used to put dishes into categories.
+    pub fn from_shift(shift: &Shift, menu: &Menu) -> ShiftReport {
+        let sales = sales(shift, menu);
+        let covers: u32 = shift
+            .waitlist()
+            .seated()
+            .iter()
+            .map(|s| u32::from(s.party.size))
+            .sum();
+        let checks = checks(shift, &sales, covers);
+        ShiftReport {
+            sales,
+            covers,
+            checks,
+            turns: turns(shift),
+            waits: waits(shift),
+            tickets: tickets(shift),
+        }
+    }
+
+    /// Aligned tables for printing or pasting into an email.
+    pub fn to_text(&self) -> String {
+        render::text(self)
+    }
+
+    /// One row per figure, as `section,name,quantity,cents,seconds`.
+    pub fn to_csv(&self) -> String {
+        render::csv(self)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a report always serializes")
+    }
+}
+
+fn sales(shift: &Shift, menu: &Menu) -> Sales {
+    let mut sales = Sales::default();
+    let mut items: BTreeMap<String, ItemSales> = BTreeMap::new();
+    for order in shift.orders().iter() {
+        if order.status() != OrderStatus::Paid {
+            continue;
+        }
+        let Ok(breakdown) = shift.breakdown(order.id) else {
+            continue;
+        };
+        sales.orders += 1;
+        sales.revenue += breakdown.net;
+        sales.adjustments += breakdown.adjustments;
+        sales.tax += breakdown.tax;
+        sales.gratuity += breakdown.gratuity.map_or(0, |g| g.amount);
+        if let Ok(bill) = shift.bill(order.id) {
+            sales.tips += bill.checks().iter().map(|c| c.tip).sum::<Cents>();
+        }
+        for line in breakdown.lines {
+            let entry = items.entry(line.item.clone()).or_insert_with(|| ItemSales {
+                category: menu.item(&line.item).ok().map(|dish| dish.category),
+                item: line.item,
+                quantity: 0,
+                revenue: 0,
+            });
+            entry.quantity += u32::from(line.quantity);
+            entry.revenue += line.net;
+        }
+    }
+
+    let mut categories: BTreeMap<Option<Category>, CategorySales> = BTreeMap::new();
+    for item in items.values() {
+        let entry = categories
+            .entry(item.category)
+            .or_insert_with(|| CategorySales {
+                category: item.category,
+                quantity: 0,
+                revenue: 0,
+            });
+        entry.quantity += item.quantity;
+        entry.revenue += item.revenue;
+    }
+    // None sorts first, but dishes off the menu belong at the bottom
+    let other = categories.remove(&None);
+    sales.by_category = categories.into_values().chain(other).collect();
+    sales.by_item = items.into_values().collect();
+    // stable sort keeps equal sellers alphabetical
+    sales.by_item.sort_by_key(|item| Reverse(item.revenue));
+    sales
+}
+
+// A check split evenly has no lines of its own, so the average is the
+// revenue over the checks rather than an average of their item lines.
+fn checks(shift: &Shift, sales: &Sales, covers: u32) -> Checks {
+    let count = shift
+        .orders()
+        .iter()
+        .filter(|o| o.status() == OrderStatus::Paid)
+        .filter_map(|o| shift.bill(o.id).ok())
+        .map(|bill| bill.checks().len())
+        .sum::<usize>();
+    Checks {
+        count,
+        average: sales.revenue.checked_div(count as u64).unwrap_or(0),
+        per_cover: sales.revenue.checked_div(u64::from(covers)).unwrap_or(0),
+    }
+}
+
+fn turns(shift: &Shift) -> TableTurns {
+    // seated and vacated events pair up by party
+    let mut seated: BTreeMap<PartyId, Timestamp> = BTreeMap::new();
+    let mut lengths = Vec::new();
+    for record in shift.log().records() {
+        match &record.event {
+            Event::TableSeated { party, .. } => {
+                seated.insert(*party, record.at);
+            }
+            Event::TableVacated { party, .. } => {
+                if let Some(at) = seated.remove(party) {
+                    lengths.push(record.at.saturating_duration_since(at).as_secs());
+                }
+            }
+            _ => {}
+        }
+    }
+    TableTurns {
+        turns: lengths.len(),
+        average_secs: average(&lengths),
+        longest_secs: lengths.iter().copied().max().unwrap_or(0),
+    }
+}
+
+fn waits(shift: &Shift) -> WaitAccuracy {
+    let seated = shift.waitlist().seated();
+    let quoted: Vec<u64> = seated.iter().map(|s| s.quoted_wait.as_secs()).collect();
+    let waited: Vec<u64> = seated.iter().map(|s| s.waited.as_secs()).collect();
+    let errors: i64 = quoted
+        .iter()
+        .zip(&waited)
+        .map(|(q, w)| *w as i64 - *q as i64)
+        .sum();
+    WaitAccuracy {
+        parties: seated.len(),
+        average_quoted_secs: average(&quoted),
+        average_waited_secs: average(&waited),
+        average_error_secs: errors.checked_div(seated.len() as i64).unwrap_or(0),
+        within_quote: quoted
+            .iter()
+            .zip(&waited)
+            .filter(|(q, w)| q.abs_diff(**w) <= ACCURATE_WITHIN)
+            .count(),
+    }
+}
+
+fn tickets(shift: &Shift) -> TicketTimes {
+    let mut all = Vec::new();
+    let mut stations: BTreeMap<Station, Vec<u64>> = BTreeMap::new();
+    let mut refires = 0;
+    for ticket in shift.kitchen().tickets() {
+        if ticket.refire.is_some() {
+            refires += 1;
+        }
+        let Some(bumped) = ticket.bumped_at else {
+            continue;
+        };
+        let secs = bumped.saturating_duration_since(ticket.fired_at).as_secs();
+        all.push(secs);
+        stations.entry(ticket.station).or_default().push(secs);
+    }
+    TicketTimes {
+        tickets: all.len(),
+        refires,
+        average_secs: average(&all),
+        longest_secs: all.iter().copied().max().unwrap_or(0),
+        by_station: stations
+            .into_iter()
+            .map(|(station, times)| StationTimes {
+                station,
+                tickets: times.len(),
+                average_secs: average(&times),
+                longest_secs: times.iter().copied().max().unwrap_or(0),
+            })
+            .collect(),
+    }
+}
+
+fn average(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .sum::<u64>()
+        .checked_div(values.len() as u64)
+        .unwrap_or(0)
+}
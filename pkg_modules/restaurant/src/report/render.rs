@@ -0,0 +1,342 @@
+use std::fmt::Write;
+
+use super::ShiftReport;
+use crate::serving::Cents;
+
+pub(super) fn text(report: &ShiftReport) -> String {
+    let sales = &report.sales;
+    let checks = &report.checks;
+    let turns = &report.turns;
+    let waits = &report.waits;
+    let tickets = &report.tickets;
+    let mut out = String::new();
+
+    table(
+        &mut out,
+        "Summary",
+        &["", ""],
+        vec![
+            row(&["orders paid", &sales.orders.to_string()]),
+            row(&["revenue", &dollars(sales.revenue)]),
+            row(&["comps and discounts", &dollars(sales.adjustments)]),
+            row(&["tax", &dollars(sales.tax)]),
+            row(&["gratuity", &dollars(sales.gratuity)]),
+            row(&["tips", &dollars(sales.tips)]),
+            row(&["covers", &report.covers.to_string()]),
+            row(&["checks", &checks.count.to_string()]),
+            row(&["average check", &dollars(checks.average)]),
+            row(&["per cover", &dollars(checks.per_cover)]),
+        ],
+    );
+    table(
+        &mut out,
+        "Sales by category",
+        &["category", "qty", "revenue"],
+        sales
+            .by_category
+            .iter()
+            .map(|c| {
+                let name = c.category.map_or("other".to_string(), |c| c.to_string());
+                row(&[&name, &c.quantity.to_string(), &dollars(c.revenue)])
+            })
+            .collect(),
+    );
+    table(
+        &mut out,
+        "Sales by item",
+        &["item", "qty", "revenue"],
+        sales
+            .by_item
+            .iter()
+            .map(|i| row(&[&i.item, &i.quantity.to_string(), &dollars(i.revenue)]))
+            .collect(),
+    );
+    table(
+        &mut out,
+        "Tables and waits",
+        &["", "count", "average", "longest"],
+        vec![
+            row(&[
+                "table turns",
+                &turns.turns.to_string(),
+                &clock(turns.average_secs),
+                &clock(turns.longest_secs),
+            ]),
+            row(&[
+                "quoted wait",
+                &waits.parties.to_string(),
+                &clock(waits.average_quoted_secs),
+                "",
+            ]),
+            row(&[
+                "actual wait",
+                &waits.parties.to_string(),
+                &clock(waits.average_waited_secs),
+                "",
+            ]),
+            row(&[
+                "vs quote",
+                &waits.parties.to_string(),
+                &signed_clock(waits.average_error_secs),
+                "",
+            ]),
+            row(&["within 5 minutes", &waits.within_quote.to_string(), "", ""]),
+        ],
+    );
+    let mut rows = vec![row(&[
+        "all",
+        &tickets.tickets.to_string(),
+        &clock(tickets.average_secs),
+        &clock(tickets.longest_secs),
+    ])];
+    rows.extend(tickets.by_station.iter().map(|s| {
+        row(&[
+            &s.station.to_string(),
+            &s.tickets.to_string(),
+            &clock(s.average_secs),
+            &clock(s.longest_secs),
+        ])
+    }));
+    rows.push(row(&["refires", &tickets.refires.to_string(), "", ""]));
+    table(
+        &mut out,
+        "Kitchen tickets",
+        &["station", "tickets", "average", "longest"],
+        rows,
+    );
+    out
+}
+
+pub(super) fn csv(report: &ShiftReport) -> String {
+    let sales = &report.sales;
+    let checks = &report.checks;
+    let turns = &report.turns;
+    let waits = &report.waits;
+    let tickets = &report.tickets;
+    let mut out = String::from("section,name,quantity,cents,seconds\n");
+    let mut line = |section: &str,
+                    name: &str,
+                    quantity: Option<u64>,
+                    cents: Option<Cents>,
+                    seconds: Option<i64>| {
+        let blank = |v: Option<String>| v.unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            section,
+            field(name),
+            blank(quantity.map(|q| q.to_string())),
+            blank(cents.map(|c| c.to_string())),
+            blank(seconds.map(|s| s.to_string())),
+        )
+        .expect("writing to a String cannot fail");
+    };
+
+    line(
+        "sales",
+        "revenue",
+        Some(sales.orders as u64),
+        Some(sales.revenue),
+        None,
+    );
+    line("sales", "adjustments", None, Some(sales.adjustments), None);
+    line("sales", "tax", None, Some(sales.tax), None);
+    line("sales", "gratuity", None, Some(sales.gratuity), None);
+    line("sales", "tips", None, Some(sales.tips), None);
+    for c in &sales.by_category {
+        let name = c.category.map_or("other".to_string(), |c| c.to_string());
+        line(
+            "category",
+            &name,
+            Some(u64::from(c.quantity)),
+            Some(c.revenue),
+            None,
+        );
+    }
+    for i in &sales.by_item {
+        line(
+            "item",
+            &i.item,
+            Some(u64::from(i.quantity)),
+            Some(i.revenue),
+            None,
+        );
+    }
+    line(
+        "covers",
+        "covers",
+        Some(u64::from(report.covers)),
+        None,
+        None,
+    );
+    line(
+        "checks",
+        "average",
+        Some(checks.count as u64),
+        Some(checks.average),
+        None,
+    );
+    line(
+        "checks",
+        "per_cover",
+        Some(u64::from(report.covers)),
+        Some(checks.per_cover),
+        None,
+    );
+    line(
+        "turns",
+        "average",
+        Some(turns.turns as u64),
+        None,
+        Some(turns.average_secs as i64),
+    );
+    line(
+        "turns",
+        "longest",
+        Some(turns.turns as u64),
+        None,
+        Some(turns.longest_secs as i64),
+    );
+    let parties = Some(waits.parties as u64);
+    line(
+        "waits",
+        "quoted",
+        parties,
+        None,
+        Some(waits.average_quoted_secs as i64),
+    );
+    line(
+        "waits",
+        "waited",
+        parties,
+        None,
+        Some(waits.average_waited_secs as i64),
+    );
+    line(
+        "waits",
+        "error",
+        parties,
+        None,
+        Some(waits.average_error_secs),
+    );
+    line(
+        "waits",
+        "within_quote",
+        Some(waits.within_quote as u64),
+        None,
+        None,
+    );
+    let count = Some(tickets.tickets as u64);
+    line(
+        "tickets",
+        "average",
+        count,
+        None,
+        Some(tickets.average_secs as i64),
+    );
+    line(
+        "tickets",
+        "longest",
+        count,
+        None,
+        Some(tickets.longest_secs as i64),
+    );
+    line(
+        "tickets",
+        "refires",
+        Some(tickets.refires as u64),
+        None,
+        None,
+    );
+    for s in &tickets.by_station {
+        let count = Some(s.tickets as u64);
+        let name = s.station.to_string();
+        line(
+            "station",
+            &format!("{}_average", name),
+            count,
+            None,
+            Some(s.average_secs as i64),
+        );
+        line(
+            "station",
+            &format!("{}_longest", name),
+            count,
+            None,
+            Some(s.longest_secs as i64),
+        );
+    }
+    out
+}
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|c| c.to_string()).collect()
+}
+
+// The first column is left-aligned, numbers line up on the right. A header
+// of all empty strings is not printed.
+fn table(out: &mut String, title: &str, headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    if !out.is_empty() {
+        // blank line between tables
+        out.push('\n');
+    }
+    out.push_str(title);
+    out.push('\n');
+    if rows.is_empty() {
+        out.push_str("  (none)\n");
+        return;
+    }
+
+    let mut print = |cells: &[String]| {
+        let mut line = String::from("  ");
+        for (i, (cell, width)) in cells.iter().zip(&widths).enumerate() {
+            if i == 0 {
+                let _ = write!(line, "{:<width$}", cell, width = width);
+            } else {
+                let _ = write!(line, "  {:>width$}", cell, width = width);
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    };
+    if headers.iter().any(|h| !h.is_empty()) {
+        print(&row(headers));
+    }
+    for row in &rows {
+        print(row);
+    }
+}
+
+fn dollars(cents: Cents) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+// h:mm:ss once it runs past the hour, m:ss before that
+fn clock(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+fn signed_clock(secs: i64) -> String {
+    let sign = if secs < 0 { "-" } else { "+" };
+    format!("{}{}", sign, clock(secs.unsigned_abs()))
+}
+
+// quotes a CSV field if it has to be
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,284 @@
+// The restaurant over HTTP, for the tablets on the floor and the screens in
+// the kitchen. Requests are JSON in and JSON out, one at a time against a
+// single shift behind a lock, and every change is saved to the state file
+// before the response goes back. GET /events streams ticket changes as
+// server-sent events so kitchen screens do not have to poll.
+//
+// Everything is plain std: a thread per connection is plenty for one
+// restaurant's worth of devices.
+//
+// There is no authentication. Requests act on the shift directly rather than
+// through `Shift::on_duty`, so anyone who can reach the port can do anything
+// a manager can, voids included. Bind it to the restaurant's own network.
+
+mod client;
+mod http;
+mod routes;
+
+use std::io::{self, BufReader, BufWriter};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::billing::InMemoryGateway;
+use crate::events::Record;
+use crate::state::StateFile;
+use crate::time::Timestamp;
+
+pub use self::client::{Client, ClientError, EventStream, ServerEvent};
+
+use self::http::{Request, Response};
+
+/// The events GET /events sends: anything that changes what is on a
+/// kitchen screen or at the pass.
+pub const TICKET_EVENTS: [&str; 6] = [
+    "order_fired",
+    "course_fired",
+    "ticket_bumped",
+    "ticket_recalled",
+    "ticket_refired",
+    "order_ready",
+];
+
+// How long a connection may take to send its request. Shutdown waits for
+// connections in flight, so this also bounds how long that can take.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// What the lock protects.
+struct Service {
+    state: StateFile,
+    gateway: InMemoryGateway,
+    save_to: Option<PathBuf>,
+}
+
+struct Shared {
+    service: Mutex<Service>,
+    subscribers: Mutex<Vec<Sender<Record>>>,
+    stopping: AtomicBool,
+}
+
+impl Shared {
+    fn service(&self) -> MutexGuard<'_, Service> {
+        // A handler that panicked leaves the shift as it was before the
+        // failed call, since every operation checks before it changes
+        // anything, so carry on with it.
+        self.service
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn subscribers(&self) -> MutexGuard<'_, Vec<Sender<Record>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn publish(&self, records: &[Record]) {
+        let mut subscribers = self.subscribers();
+        for record in records {
+            if TICKET_EVENTS.contains(&record.event.kind()) {
+                subscribers.retain(|subscriber| subscriber.send(record.clone()).is_ok());
+            }
+        }
+    }
+}
+
+pub struct Server {
+    service: Service,
+}
+
+impl Server {
+    /// Serves `state` from memory only; nothing is written to disk.
+    pub fn new(state: StateFile) -> Server {
+        Server {
+            service: Service {
+                state,
+                gateway: InMemoryGateway::new(),
+                save_to: None,
+            },
+        }
+    }
+
+    /// Saves the state to `path` after every request that changes it.
+    pub fn save_to(mut self, path: impl Into<PathBuf>) -> Server {
+        self.service.save_to = Some(path.into());
+        self
+    }
+
+    /// Starts listening and returns straight away. Bind to port 0 to get a
+    /// free port, e.g. in tests; `Running::addr` says which one it was.
+    pub fn bind(self, addr: impl ToSocketAddrs) -> io::Result<Running> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            service: Mutex::new(self.service),
+            subscribers: Mutex::new(Vec::new()),
+            stopping: AtomicBool::new(false),
+        });
+        let accepting = Arc::clone(&shared);
+        let thread = thread::spawn(move || accept(listener, accepting));
+        Ok(Running {
+            addr,
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// A server that is up. Dropping it shuts it down.
+pub struct Running {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Running {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// A client for this server, over loopback.
+    pub fn client(&self) -> Client {
+        Client::new(self.addr)
+    }
+
+    /// Blocks until the server stops, which for the binary is never.
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Stops accepting connections, ends every event stream and returns
+    /// the state as it was left.
+    pub fn shutdown(mut self) -> StateFile {
+        self.stop();
+        let shared = Arc::clone(&self.shared);
+        drop(self);
+        // Connection threads still hold the shared state until they finish
+        // their current request.
+        let mut shared = shared;
+        loop {
+            match Arc::try_unwrap(shared) {
+                Ok(shared) => {
+                    let service = shared
+                        .service
+                        .into_inner()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    return service.state;
+                }
+                Err(still_shared) => {
+                    shared = still_shared;
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        if self.shared.stopping.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.shared.subscribers().clear();
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept(listener: TcpListener, shared: Arc<Shared>) {
+    for stream in listener.incoming() {
+        if shared.stopping.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            let _ = connection(stream, &shared);
+        });
+    }
+}
+
+fn connection(stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream.try_clone()?);
+    let request = match http::read_request(&mut reader) {
+        Ok(Some(request)) => request,
+        Ok(None) => return Ok(()),
+        Err(err) => return Response::error(400, &err.to_string()).write_to(&mut writer),
+    };
+
+    if request.segments() == ["events"] && request.method == "GET" {
+        let (sender, receiver) = mpsc::channel();
+        shared.subscribers().push(sender);
+        http::write_stream_head(&mut writer)?;
+        return stream_events(receiver, &mut writer);
+    }
+
+    let response = respond(shared, &request);
+    response.write_to(&mut writer)?;
+    stream.shutdown(Shutdown::Write)
+}
+
+// Runs one request and, if it changed the shift, saves it and tells the
+// event streams. A request that fails, or cannot be saved, leaves nothing
+// behind: the shift and menu go back to how they were before it, so stock
+// taken for an item the order then refused is put back.
+fn respond(shared: &Shared, request: &Request) -> Response {
+    let mut service = shared.service();
+    let before = service.state.shift.log().len();
+    let menu = service.state.menu.clone();
+    let response = routes::handle(&mut service, request, Timestamp::now());
+
+    let changed = service.state.shift.log().len() > before;
+    if response.status >= 400 {
+        if changed {
+            service
+                .state
+                .rewind(before, menu)
+                .expect("the log replayed up to here before");
+        } else {
+            service.state.menu = menu;
+        }
+        return response;
+    }
+    if !changed {
+        return response;
+    }
+    if let Some(path) = service.save_to.clone() {
+        if let Err(err) = service.state.save(&path) {
+            service
+                .state
+                .rewind(before, menu)
+                .expect("the log replayed up to here before");
+            return Response::error(500, &err.to_string());
+        }
+    }
+    shared.publish(&service.state.shift.log().records()[before..]);
+    response
+}
+
+fn stream_events(receiver: Receiver<Record>, writer: &mut impl io::Write) -> io::Result<()> {
+    // Ends when the server drops the sender on shutdown, or when a write
+    // fails because the screen went away.
+    for record in receiver {
+        let data = serde_json::to_string(&record).map_err(io::Error::other)?;
+        http::write_event(writer, record.seq, record.event.kind(), &data)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+// A small blocking client for the API, for scripts and for driving a server
+// from the same process in tests. Responses come back as JSON values; the
+// API's own types stay private to the server.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ClientError {
+    Io(io::Error),
+    /// The server answered, but not with a 2xx.
+    Status {
+        status: u16,
+        message: String,
+    },
+    /// The server sent something that is not the JSON it should have.
+    Invalid(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "{}", err),
+            ClientError::Status { status, message } => write!(f, "{}: {}", status, message),
+            ClientError::Invalid(message) => write!(f, "invalid response: {}", message),
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientError::Io(err) => Some(err),
+            ClientError::Status { .. } | ClientError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> ClientError {
+        ClientError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    addr: SocketAddr,
+}
+
+impl Client {
+    pub fn new(addr: SocketAddr) -> Client {
+        Client { addr }
+    }
+
+    pub fn get(&self, path: &str) -> Result<Value, ClientError> {
+        self.send("GET", path, None)
+    }
+
+    pub fn post(&self, path: &str, body: &impl Serialize) -> Result<Value, ClientError> {
+        let body = serde_json::to_vec(body).map_err(|err| ClientError::Invalid(err.to_string()))?;
+        self.send("POST", path, Some(&body))
+    }
+
+    /// A POST with nothing to say, e.g. `/tickets/3/bump`.
+    pub fn action(&self, path: &str) -> Result<Value, ClientError> {
+        self.send("POST", path, None)
+    }
+
+    pub fn delete(&self, path: &str) -> Result<Value, ClientError> {
+        self.send("DELETE", path, None)
+    }
+
+    /// Opens GET /events. The stream blocks waiting for the next event and
+    /// ends when the server shuts down.
+    pub fn events(&self) -> Result<EventStream, ClientError> {
+        let stream = self.request("GET", "/events", None)?;
+        let mut reader = BufReader::new(stream);
+        let (status, _) = read_head(&mut reader)?;
+        if status != 200 {
+            return Err(ClientError::Status {
+                status,
+                message: String::from("could not open the event stream"),
+            });
+        }
+        Ok(EventStream { reader })
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<&[u8]>) -> io::Result<TcpStream> {
+        let mut stream = TcpStream::connect(self.addr)?;
+        let body = body.unwrap_or_default();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            method,
+            path,
+            self.addr,
+            body.len()
+        )?;
+        stream.write_all(body)?;
+        stream.flush()?;
+        Ok(stream)
+    }
+
+    // 204s come back as `null`.
+    fn send(&self, method: &str, path: &str, body: Option<&[u8]>) -> Result<Value, ClientError> {
+        let stream = self.request(method, path, body)?;
+        let mut reader = BufReader::new(stream);
+        let (status, length) = read_head(&mut reader)?;
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+
+        let value = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body).map_err(|err| ClientError::Invalid(err.to_string()))?
+        };
+        if (200..300).contains(&status) {
+            return Ok(value);
+        }
+        let message = value["error"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| super::http::reason(status).to_string());
+        Err(ClientError::Status { status, message })
+    }
+}
+
+// The status and the Content-Length, if any.
+fn read_head(reader: &mut impl BufRead) -> Result<(u16, usize), ClientError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let status = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| ClientError::Invalid(format!("bad status line {:?}", line.trim_end())))?;
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(ClientError::Invalid(String::from("headers ended early")));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            return Ok((status, length));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| ClientError::Invalid(String::from("bad Content-Length")))?;
+            }
+        }
+    }
+}
+
+/// One server-sent event; `data` is the event log record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEvent {
+    pub id: u64,
+    pub kind: String,
+    pub data: Value,
+}
+
+pub struct EventStream {
+    reader: BufReader<TcpStream>,
+}
+
+impl EventStream {
+    /// Gives up waiting after `timeout`; `next` then fails with a timed
+    /// out I/O error rather than blocking for ever.
+    pub fn with_timeout(self, timeout: std::time::Duration) -> io::Result<EventStream> {
+        self.reader.get_ref().set_read_timeout(Some(timeout))?;
+        Ok(self)
+    }
+}
+
+impl Iterator for EventStream {
+    type Item = Result<ServerEvent, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut id = 0;
+        let mut kind = String::new();
+        let mut data = String::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(err) => return Some(Err(err.into())),
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if data.is_empty() {
+                    continue;
+                }
+                let data = serde_json::from_str(&data)
+                    .map_err(|err| ClientError::Invalid(err.to_string()));
+                return Some(data.map(|data| ServerEvent { id, kind, data }));
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "id" => id = value.parse().unwrap_or(0),
+                "event" => kind = value.to_string(),
+                "data" => data.push_str(value),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Just enough HTTP/1.1 for tablets on the LAN: one request per connection,
+// bodies sized by Content-Length, no chunking and no keep-alive.
+
+use std::io::{self, BufRead, Write};
+
+use serde::Serialize;
+
+// Nothing the API accepts comes close to this.
+const MAX_BODY: usize = 1 << 20;
+
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The path split on `/`, without the empty leading segment.
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+}
+
+/// Reads one request. `Ok(None)` means the client hung up without sending
+/// anything, which the server's own shutdown wake-up does.
+pub fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let method = method.to_string();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_string();
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_string(), value.to_string())
+        })
+        .collect();
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid("headers ended early"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("bad Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(invalid("request body is too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(Request {
+        method,
+        path,
+        query,
+        body,
+    }))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+impl Response {
+    pub fn json(status: u16, value: &impl Serialize) -> Response {
+        match serde_json::to_string(value) {
+            Ok(body) => Response { status, body },
+            Err(err) => Response::error(500, &err.to_string()),
+        }
+    }
+
+    pub fn ok(value: &impl Serialize) -> Response {
+        Response::json(200, value)
+    }
+
+    pub fn no_content() -> Response {
+        Response {
+            status: 204,
+            body: String::new(),
+        }
+    }
+
+    /// `{"error": message}`.
+    pub fn error(status: u16, message: &str) -> Response {
+        Response {
+            status,
+            body: serde_json::to_string(&ErrorBody { error: message })
+                .expect("a string always serialises"),
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            reason(self.status)
+        )?;
+        if !self.body.is_empty() {
+            write!(writer, "Content-Type: application/json\r\n")?;
+        }
+        write!(
+            writer,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        )?;
+        writer.flush()
+    }
+}
+
+/// The headers that open a server-sent event stream. The connection then
+/// stays open and events are written to it as they happen.
+pub fn write_stream_head(writer: &mut impl Write) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+    writer.flush()
+}
+
+pub fn write_event(writer: &mut impl Write, id: u64, kind: &str, data: &str) -> io::Result<()> {
+    write!(writer, "id: {}\nevent: {}\ndata: {}\n\n", id, kind, data)?;
+    writer.flush()
+}
+
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,745 @@
+// The REST side of the API. Every handler takes the request and the service
+// under its lock and returns a response; errors from the shift are turned
+// into 404s for things that do not exist and 409s for everything else.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::http::{Request, Response};
+use super::Service;
+use crate::billing::{Bill, Check, CheckId, PaymentError, PaymentId, Receipt, Share, Tender};
+use crate::hosting::{
+    FloorError, Party, PartyId, Priority, SeatingArea, Table, TableNumber, WaitlistEntry,
+};
+use crate::kitchen::{KitchenError, Station, Ticket, TicketId};
+use crate::menu::MenuError;
+use crate::report::ShiftReport;
+use crate::serving::{Cents, LineItem, Order, OrderError, OrderId, OrderStatus};
+use crate::shift::ShiftError;
+use crate::time::Timestamp;
+
+type Handled = Result<Response, Response>;
+
+pub(super) fn handle(service: &mut Service, request: &Request, now: Timestamp) -> Response {
+    route(service, request, now).unwrap_or_else(|response| response)
+}
+
+fn route(service: &mut Service, request: &Request, now: Timestamp) -> Handled {
+    let segments = request.segments();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["waitlist"]) => waitlist(service, now),
+        ("POST", ["waitlist"]) => join_waitlist(service, request, now),
+        ("POST", ["waitlist", "seat"]) => seat_next(service, now),
+        ("DELETE", ["waitlist", party]) => leave_waitlist(service, PartyId(id(party)?), now),
+        ("GET", ["tables"]) => tables(service),
+        ("POST", ["parties", party, "vacate"]) => vacate(service, PartyId(id(party)?), now),
+        ("POST", ["tables", table, "bus"]) => bus(service, TableNumber(id(table)?), now),
+        ("GET", ["orders"]) => orders(service),
+        ("POST", ["orders"]) => take_order(service, request, now),
+        ("GET", ["orders", order]) => show_order(service, OrderId(id(order)?)),
+        ("POST", ["orders", order, "items"]) => {
+            add_item(service, OrderId(id(order)?), request, now)
+        }
+        ("POST", ["orders", order, "items", line, "share"]) => {
+            share(service, OrderId(id(order)?), id(line)?, request, now)
+        }
+        ("POST", ["orders", order, "send"]) => send(service, OrderId(id(order)?), now),
+        ("POST", ["orders", order, "serve"]) => serve(service, OrderId(id(order)?), now),
+        ("POST", ["orders", order, "void"]) => void(service, OrderId(id(order)?), now),
+        ("GET", ["tickets"]) => tickets(service, request, now),
+        ("GET", ["tickets", ticket]) => show_ticket(service, TicketId(id(ticket)?), now),
+        ("POST", ["tickets", ticket, "bump"]) => bump(service, TicketId(id(ticket)?), now),
+        ("POST", ["tickets", ticket, "recall"]) => recall(service, TicketId(id(ticket)?), now),
+        ("GET", ["orders", order, "bill"]) => bill(service, OrderId(id(order)?)),
+        ("POST", ["orders", order, "bill"]) => open_bill(service, OrderId(id(order)?), now),
+        ("POST", ["orders", order, "bill", "split"]) => {
+            split(service, OrderId(id(order)?), request, now)
+        }
+        ("POST", ["orders", order, "bill", "checks"]) => {
+            open_check(service, OrderId(id(order)?), request, now)
+        }
+        ("POST", ["orders", order, "bill", "checks", check, "merge"]) => merge(
+            service,
+            OrderId(id(order)?),
+            CheckId(id(check)?),
+            request,
+            now,
+        ),
+        ("POST", ["orders", order, "bill", "checks", check, "transfer"]) => transfer(
+            service,
+            OrderId(id(order)?),
+            CheckId(id(check)?),
+            request,
+            now,
+        ),
+        ("POST", ["orders", order, "bill", "checks", check, "tip"]) => tip(
+            service,
+            OrderId(id(order)?),
+            CheckId(id(check)?),
+            request,
+            now,
+        ),
+        ("POST", ["orders", order, "bill", "checks", check, "payments"]) => pay(
+            service,
+            OrderId(id(order)?),
+            CheckId(id(check)?),
+            request,
+            now,
+        ),
+        ("GET", ["report"]) => report(service),
+        (_, [..]) if known_path(&segments) => Err(Response::error(405, "method not allowed")),
+        _ => Err(Response::error(404, "no such endpoint")),
+    }
+}
+
+// Whether some method would have matched, so a wrong one gets a 405.
+fn known_path(segments: &[&str]) -> bool {
+    matches!(
+        segments,
+        ["waitlist"]
+            | ["waitlist", _]
+            | ["tables"]
+            | ["tables", _, "bus"]
+            | ["parties", _, "vacate"]
+            | ["orders"]
+            | ["orders", _]
+            | ["orders", _, "items" | "send" | "serve" | "void" | "bill"]
+            | ["orders", _, "items", _, "share"]
+            | ["orders", _, "bill", "split" | "checks"]
+            | [
+                "orders",
+                _,
+                "bill",
+                "checks",
+                _,
+                "tip" | "payments" | "merge" | "transfer"
+            ]
+            | ["tickets"]
+            | ["tickets", _]
+            | ["tickets", _, "bump" | "recall"]
+            | ["report"]
+            | ["events"]
+    )
+}
+
+// Ids in paths are the bare numbers, e.g. /orders/3.
+fn id<T: std::str::FromStr>(segment: &str) -> Result<T, Response> {
+    segment
+        .parse()
+        .map_err(|_| Response::error(404, &format!("{} is not an id", segment)))
+}
+
+fn body<T: DeserializeOwned>(request: &Request) -> Result<T, Response> {
+    serde_json::from_slice(&request.body).map_err(|err| Response::error(400, &err.to_string()))
+}
+
+impl From<ShiftError> for Response {
+    fn from(err: ShiftError) -> Response {
+        let missing = matches!(
+            err,
+            ShiftError::UnknownParty(_)
+                | ShiftError::NoBill(_)
+                | ShiftError::Order(OrderError::UnknownOrder(_))
+                | ShiftError::Kitchen(KitchenError::UnknownTicket(_))
+                | ShiftError::Floor(FloorError::UnknownTable(_))
+                | ShiftError::Payment(PaymentError::UnknownCheck(_))
+                | ShiftError::Payment(PaymentError::UnknownPayment(_))
+                | ShiftError::Payment(PaymentError::NoSuchLine { .. })
+                | ShiftError::Order(OrderError::NoSuchLine { .. })
+        );
+        let status = match err {
+            _ if missing => 404,
+            ShiftError::Log(_) => 500,
+            _ => 409,
+        };
+        Response::error(status, &err.to_string())
+    }
+}
+
+impl From<MenuError> for Response {
+    fn from(err: MenuError) -> Response {
+        let status = match err {
+            MenuError::UnknownItem(_) | MenuError::UnknownModifier { .. } => 400,
+            _ => 409,
+        };
+        Response::error(status, &err.to_string())
+    }
+}
+
+// What goes over the wire. The domain types keep their fields private or
+// hold durations, so each gets a flat view here.
+
+#[derive(Serialize)]
+struct WaitingParty {
+    party: PartyId,
+    name: String,
+    size: u8,
+    arrived: Timestamp,
+    quoted_secs: u64,
+    waited_secs: u64,
+}
+
+impl WaitingParty {
+    fn new(entry: &WaitlistEntry, now: Timestamp) -> WaitingParty {
+        WaitingParty {
+            party: entry.id,
+            name: entry.party.name.clone(),
+            size: entry.party.size,
+            arrived: entry.party.arrived,
+            quoted_secs: entry.quoted_wait.as_secs(),
+            waited_secs: now.saturating_duration_since(entry.party.arrived).as_secs(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TableView {
+    number: TableNumber,
+    seats: u8,
+    area: SeatingArea,
+    accessible: bool,
+    section: String,
+    state: String,
+}
+
+impl TableView {
+    fn new(table: &Table) -> TableView {
+        TableView {
+            number: table.number,
+            seats: table.seats,
+            area: table.area,
+            accessible: table.accessible,
+            section: table.section.clone(),
+            state: table.state.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OrderView {
+    id: OrderId,
+    table: TableNumber,
+    status: String,
+    items: Vec<LineItem>,
+    subtotal: Cents,
+}
+
+impl OrderView {
+    fn new(order: &Order) -> OrderView {
+        OrderView {
+            id: order.id,
+            table: order.table,
+            status: order.status().to_string(),
+            items: order.items().to_vec(),
+            subtotal: order.subtotal(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TicketView {
+    id: TicketId,
+    order: OrderId,
+    table: TableNumber,
+    station: Station,
+    items: Vec<LineItem>,
+    fired_at: Timestamp,
+    bumped_at: Option<Timestamp>,
+    elapsed_secs: u64,
+    refire_of: Option<TicketId>,
+}
+
+impl TicketView {
+    fn new(ticket: &Ticket, now: Timestamp) -> TicketView {
+        TicketView {
+            id: ticket.id,
+            order: ticket.order,
+            table: ticket.table,
+            station: ticket.station,
+            items: ticket.items.clone(),
+            fired_at: ticket.fired_at,
+            bumped_at: ticket.bumped_at,
+            elapsed_secs: ticket.elapsed(now).as_secs(),
+            refire_of: ticket.refire.as_ref().map(|refire| refire.original),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BillLineView {
+    description: String,
+    seat: Option<u8>,
+    quantity: u16,
+    amount: Cents,
+}
+
+#[derive(Serialize)]
+struct CheckView {
+    id: CheckId,
+    label: String,
+    lines: Vec<BillLineView>,
+    subtotal: Cents,
+    tip: Cents,
+    total: Cents,
+    paid: Cents,
+    refunded: Cents,
+    balance: Cents,
+}
+
+impl CheckView {
+    fn new(check: &Check) -> CheckView {
+        CheckView {
+            id: check.id,
+            label: check.label.clone(),
+            lines: check
+                .lines
+                .iter()
+                .map(|line| BillLineView {
+                    description: line.description.clone(),
+                    seat: line.seat,
+                    quantity: line.quantity,
+                    amount: line.amount,
+                })
+                .collect(),
+            subtotal: check.subtotal,
+            tip: check.tip,
+            total: check.total(),
+            paid: check.paid(),
+            refunded: check.refunded(),
+            balance: check.balance(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BillView {
+    order: OrderId,
+    checks: Vec<CheckView>,
+}
+
+impl BillView {
+    fn new(bill: &Bill) -> BillView {
+        BillView {
+            order: bill.order,
+            checks: bill.checks().iter().map(CheckView::new).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReceiptView {
+    check: CheckId,
+    payment: PaymentId,
+    applied: Cents,
+    change: Cents,
+    balance: Cents,
+}
+
+impl ReceiptView {
+    fn new(receipt: &Receipt) -> ReceiptView {
+        ReceiptView {
+            check: receipt.check,
+            payment: receipt.payment,
+            applied: receipt.applied,
+            change: receipt.change,
+            balance: receipt.balance,
+        }
+    }
+}
+
+// hosting
+
+#[derive(Deserialize)]
+struct NewParty {
+    name: String,
+    size: u8,
+    #[serde(default)]
+    accessible: bool,
+    area: Option<SeatingArea>,
+    #[serde(default)]
+    priority: Priority,
+}
+
+#[derive(Serialize)]
+struct Joined {
+    party: PartyId,
+    quoted_secs: u64,
+}
+
+#[derive(Serialize)]
+struct SeatedView {
+    party: PartyId,
+    name: String,
+    tables: Vec<TableNumber>,
+    waited_secs: u64,
+}
+
+#[derive(Serialize)]
+struct Vacated {
+    tables: Vec<TableNumber>,
+}
+
+fn waitlist(service: &Service, now: Timestamp) -> Handled {
+    let waiting: Vec<WaitingParty> = service
+        .state
+        .shift
+        .waitlist()
+        .iter()
+        .map(|entry| WaitingParty::new(entry, now))
+        .collect();
+    Ok(Response::ok(&waiting))
+}
+
+fn join_waitlist(service: &mut Service, request: &Request, now: Timestamp) -> Handled {
+    let new: NewParty = body(request)?;
+    let mut party = Party::new(&new.name, new.size, now).priority(new.priority);
+    if new.accessible {
+        party = party.needs_accessible();
+    }
+    if let Some(area) = new.area {
+        party = party.prefers(area);
+    }
+    let shift = &mut service.state.shift;
+    let quoted = shift.waitlist().quote(&party);
+    let party = shift.add_to_waitlist(party)?;
+    Ok(Response::json(
+        201,
+        &Joined {
+            party,
+            quoted_secs: quoted.as_secs(),
+        },
+    ))
+}
+
+fn seat_next(service: &mut Service, now: Timestamp) -> Handled {
+    let seated = service.state.shift.seat_next(now)?;
+    Ok(Response::ok(&SeatedView {
+        party: seated.id,
+        name: seated.party.name,
+        tables: seated.tables,
+        waited_secs: seated.waited.as_secs(),
+    }))
+}
+
+fn leave_waitlist(service: &mut Service, party: PartyId, now: Timestamp) -> Handled {
+    service.state.shift.leave_waitlist(party, now)?;
+    Ok(Response::no_content())
+}
+
+fn tables(service: &Service) -> Handled {
+    let tables: Vec<TableView> = service
+        .state
+        .shift
+        .floor()
+        .tables()
+        .map(TableView::new)
+        .collect();
+    Ok(Response::ok(&tables))
+}
+
+fn vacate(service: &mut Service, party: PartyId, now: Timestamp) -> Handled {
+    let tables = service.state.shift.vacate(party, now)?;
+    Ok(Response::ok(&Vacated { tables }))
+}
+
+fn bus(service: &mut Service, table: TableNumber, now: Timestamp) -> Handled {
+    service.state.shift.bus(table, now)?;
+    Ok(Response::no_content())
+}
+
+// orders
+
+#[derive(Deserialize)]
+struct NewOrder {
+    table: TableNumber,
+}
+
+#[derive(Deserialize)]
+struct NewItem {
+    item: String,
+    #[serde(default = "one")]
+    quantity: u16,
+    seat: Option<u8>,
+    #[serde(default)]
+    modifiers: Vec<String>,
+}
+
+fn one() -> u16 {
+    1
+}
+
+#[derive(Serialize)]
+struct Added {
+    line: usize,
+    item: LineItem,
+}
+
+#[derive(Serialize)]
+struct Fired {
+    tickets: Vec<TicketId>,
+}
+
+fn orders(service: &Service) -> Handled {
+    let orders: Vec<OrderView> = service
+        .state
+        .shift
+        .orders()
+        .iter()
+        .map(OrderView::new)
+        .collect();
+    Ok(Response::ok(&orders))
+}
+
+fn take_order(service: &mut Service, request: &Request, now: Timestamp) -> Handled {
+    let new: NewOrder = body(request)?;
+    let order = service.state.shift.take_order(new.table, Vec::new(), now)?;
+    let order = service
+        .state
+        .shift
+        .orders()
+        .get(order)
+        .map_err(ShiftError::from)?;
+    Ok(Response::json(201, &OrderView::new(order)))
+}
+
+fn show_order(service: &Service, order: OrderId) -> Handled {
+    let order = service
+        .state
+        .shift
+        .orders()
+        .get(order)
+        .map_err(ShiftError::from)?;
+    Ok(Response::ok(&OrderView::new(order)))
+}
+
+fn add_item(service: &mut Service, order: OrderId, request: &Request, now: Timestamp) -> Handled {
+    let new: NewItem = body(request)?;
+    let modifiers: Vec<&str> = new.modifiers.iter().map(String::as_str).collect();
+    let mut item = service
+        .state
+        .menu
+        .order(&new.item, new.quantity, &modifiers, now)?;
+    if let Some(seat) = new.seat {
+        item = item.for_seat(seat);
+    }
+    let line = service.state.shift.add_item(order, item.clone(), now)?;
+    Ok(Response::json(201, &Added { line, item }))
+}
+
+#[derive(Deserialize)]
+struct Shares {
+    shares: Vec<Share>,
+}
+
+fn share(
+    service: &mut Service,
+    order: OrderId,
+    line: usize,
+    request: &Request,
+    now: Timestamp,
+) -> Handled {
+    let shares: Shares = body(request)?;
+    service
+        .state
+        .shift
+        .share_item(order, line, shares.shares, now)?;
+    show_order(service, order)
+}
+
+fn send(service: &mut Service, order: OrderId, now: Timestamp) -> Handled {
+    service.state.shift.send_to_kitchen(order, now)?;
+    let tickets = service.state.shift.cook_order(order, now)?;
+    Ok(Response::ok(&Fired { tickets }))
+}
+
+fn serve(service: &mut Service, order: OrderId, now: Timestamp) -> Handled {
+    service.state.shift.serve_order(order, now)?;
+    Ok(Response::no_content())
+}
+
+// Unchecked like every other route; see the note on the server.
+fn void(service: &mut Service, order: OrderId, now: Timestamp) -> Handled {
+    service.state.shift.void(order, now)?;
+    Ok(Response::no_content())
+}
+
+// kitchen
+
+fn tickets(service: &Service, request: &Request, now: Timestamp) -> Handled {
+    let kitchen = service.state.shift.kitchen();
+    let tickets: Vec<TicketView> = match request.query("station") {
+        Some(name) => {
+            let station: Station = serde_json::from_value(name.into())
+                .map_err(|_| Response::error(400, &format!("{} is not a station", name)))?;
+            kitchen
+                .queue(station)
+                .into_iter()
+                .map(|ticket| TicketView::new(ticket, now))
+                .collect()
+        }
+        None => kitchen
+            .tickets()
+            .filter(|ticket| !ticket.is_bumped())
+            .map(|ticket| TicketView::new(ticket, now))
+            .collect(),
+    };
+    Ok(Response::ok(&tickets))
+}
+
+fn show_ticket(service: &Service, ticket: TicketId, now: Timestamp) -> Handled {
+    let ticket = service
+        .state
+        .shift
+        .kitchen()
+        .ticket(ticket)
+        .map_err(ShiftError::from)?;
+    Ok(Response::ok(&TicketView::new(ticket, now)))
+}
+
+// Bumping the last ticket for an order marks the order ready, as the pass
+// would. A recalled ticket bumped again finds it ready already.
+fn bump(service: &mut Service, ticket: TicketId, now: Timestamp) -> Handled {
+    let shift = &mut service.state.shift;
+    shift.bump(ticket, now)?;
+    let order = shift
+        .kitchen()
+        .ticket(ticket)
+        .map_err(ShiftError::from)?
+        .order;
+    let cooking = shift
+        .orders()
+        .get(order)
+        .map_err(ShiftError::from)?
+        .status()
+        == OrderStatus::Cooking;
+    if cooking && shift.kitchen().is_order_done(order) {
+        shift.mark_ready(order, now)?;
+    }
+    Ok(Response::no_content())
+}
+
+fn recall(service: &mut Service, ticket: TicketId, now: Timestamp) -> Handled {
+    service.state.shift.recall(ticket, now)?;
+    Ok(Response::no_content())
+}
+
+// billing
+
+#[derive(Deserialize)]
+struct Split {
+    #[serde(default)]
+    by_seat: bool,
+    ways: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Tip {
+    amount: Cents,
+}
+
+fn bill(service: &Service, order: OrderId) -> Handled {
+    let bill = service.state.shift.bill(order)?;
+    Ok(Response::ok(&BillView::new(bill)))
+}
+
+fn open_bill(service: &mut Service, order: OrderId, now: Timestamp) -> Handled {
+    let bill = service.state.shift.open_bill(order, now)?;
+    Ok(Response::json(201, &BillView::new(bill)))
+}
+
+fn split(service: &mut Service, order: OrderId, request: &Request, now: Timestamp) -> Handled {
+    let split: Split = body(request)?;
+    let shift = &mut service.state.shift;
+    match (split.by_seat, split.ways) {
+        (true, None) => shift.split_by_seat(order, now)?,
+        (false, Some(ways)) => shift.split_evenly(order, ways, now)?,
+        _ => return Err(Response::error(400, "give exactly one of by_seat and ways")),
+    }
+    bill(service, order)
+}
+
+#[derive(Deserialize)]
+struct NewCheck {
+    label: String,
+}
+
+fn open_check(service: &mut Service, order: OrderId, request: &Request, now: Timestamp) -> Handled {
+    let new: NewCheck = body(request)?;
+    service.state.shift.open_check(order, &new.label, now)?;
+    let bill = service.state.shift.bill(order)?;
+    Ok(Response::json(201, &BillView::new(bill)))
+}
+
+#[derive(Deserialize)]
+struct Merge {
+    into: CheckId,
+}
+
+fn merge(
+    service: &mut Service,
+    order: OrderId,
+    check: CheckId,
+    request: &Request,
+    now: Timestamp,
+) -> Handled {
+    let merge: Merge = body(request)?;
+    service
+        .state
+        .shift
+        .merge_checks(order, check, merge.into, now)?;
+    bill(service, order)
+}
+
+#[derive(Deserialize)]
+struct Transfer {
+    line: usize,
+    to: CheckId,
+}
+
+fn transfer(
+    service: &mut Service,
+    order: OrderId,
+    check: CheckId,
+    request: &Request,
+    now: Timestamp,
+) -> Handled {
+    let transfer: Transfer = body(request)?;
+    service
+        .state
+        .shift
+        .transfer_line(order, check, transfer.line, transfer.to, now)?;
+    bill(service, order)
+}
+
+fn tip(
+    service: &mut Service,
+    order: OrderId,
+    check: CheckId,
+    request: &Request,
+    now: Timestamp,
+) -> Handled {
+    let tip: Tip = body(request)?;
+    service.state.shift.add_tip(order, check, tip.amount, now)?;
+    bill(service, order)
+}
+
+fn pay(
+    service: &mut Service,
+    order: OrderId,
+    check: CheckId,
+    request: &Request,
+    now: Timestamp,
+) -> Handled {
+    let tender: Tender = body(request)?;
+    let receipt =
+        service
+            .state
+            .shift
+            .take_payment(order, check, tender, &mut service.gateway, now)?;
+    Ok(Response::json(201, &ReceiptView::new(&receipt)))
+}
+
+fn report(service: &Service) -> Handled {
+    let report = ShiftReport::from_shift(&service.state.shift, &service.state.menu);
+    Ok(Response::ok(&report))
+}
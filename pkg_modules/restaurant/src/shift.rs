@@ -0,0 +1,1293 @@
+// One service, front and back of house together. Every operation goes
+// through here so that it lands in the event log, and a log can be replayed
+// onto the opening state to get the same shift back. An operation whose
+// record cannot be written is undone by replaying the log onto that opening
+// state, so the shift never holds anything the log does not.
+
+mod on_duty;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+use crate::billing::{
+    self, Bill, Breakdown, CheckId, Discount, GatewayError, PaymentError, PaymentGateway,
+    PaymentId, PricingRules, Receipt, Refund, Share, Tender, TransactionId,
+};
+use crate::customer::{CustomerBook, CustomerError, CustomerId, LoyaltyGateway, Profile, Visit};
+use crate::delivery::{
+    self, CourierId, Delivery, DeliveryBoard, DeliveryError, DeliveryId, Fulfilment,
+};
+use crate::events::{Event, EventError, EventLog, Record};
+use crate::hosting::{
+    self, FloorError, FloorPlan, Party, PartyId, ReservationBook, ReservationError, ReservationId,
+    ReservationRequest, SeatError, Seated, TableNumber, Waitlist, WaitlistEntry,
+};
+use crate::inventory::{Inventory, InventoryError};
+use crate::kitchen::{self, KitchenError, KitchenQueue, StationRouter, TicketId};
+use crate::serving::{self, Cents, LineItem, Order, OrderBook, OrderError, OrderId, OrderStatus};
+use crate::staff::{Role, Roster, StaffError, StaffId};
+use crate::time::Timestamp;
+
+pub use self::on_duty::OnDuty;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ShiftError {
+    Seat(SeatError),
+    Floor(FloorError),
+    Reservation(ReservationError),
+    Order(OrderError),
+    Kitchen(KitchenError),
+    Payment(PaymentError),
+    Inventory(InventoryError),
+    Delivery(DeliveryError),
+    Customer(CustomerError),
+    Staff(StaffError),
+    UnknownParty(PartyId),
+    /// `open_bill` has not been called for the order yet.
+    NoBill(OrderId),
+    /// The operation went through but could not be written to the log.
+    Log(EventError),
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShiftError::Seat(err) => write!(f, "{}", err),
+            ShiftError::Floor(err) => write!(f, "{}", err),
+            ShiftError::Reservation(err) => write!(f, "{}", err),
+            ShiftError::Order(err) => write!(f, "{}", err),
+            ShiftError::Kitchen(err) => write!(f, "{}", err),
+            ShiftError::Payment(err) => write!(f, "{}", err),
+            ShiftError::Inventory(err) => write!(f, "{}", err),
+            ShiftError::Delivery(err) => write!(f, "{}", err),
+            ShiftError::Customer(err) => write!(f, "{}", err),
+            ShiftError::Staff(err) => write!(f, "{}", err),
+            ShiftError::UnknownParty(party) => write!(f, "party {} is not waiting", party),
+            ShiftError::NoBill(order) => write!(f, "{} has no bill yet", order),
+            ShiftError::Log(err) => write!(f, "could not write the event log: {}", err),
+        }
+    }
+}
+
+impl Error for ShiftError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShiftError::Seat(err) => Some(err),
+            ShiftError::Floor(err) => Some(err),
+            ShiftError::Reservation(err) => Some(err),
+            ShiftError::Order(err) => Some(err),
+            ShiftError::Kitchen(err) => Some(err),
+            ShiftError::Payment(err) => Some(err),
+            ShiftError::Inventory(err) => Some(err),
+            ShiftError::Delivery(err) => Some(err),
+            ShiftError::Customer(err) => Some(err),
+            ShiftError::Staff(err) => Some(err),
+            ShiftError::Log(err) => Some(err),
+            ShiftError::UnknownParty(_) | ShiftError::NoBill(_) => None,
+        }
+    }
+}
+
+impl From<SeatError> for ShiftError {
+    fn from(err: SeatError) -> ShiftError {
+        ShiftError::Seat(err)
+    }
+}
+
+impl From<FloorError> for ShiftError {
+    fn from(err: FloorError) -> ShiftError {
+        ShiftError::Floor(err)
+    }
+}
+
+impl From<ReservationError> for ShiftError {
+    fn from(err: ReservationError) -> ShiftError {
+        ShiftError::Reservation(err)
+    }
+}
+
+impl From<OrderError> for ShiftError {
+    fn from(err: OrderError) -> ShiftError {
+        ShiftError::Order(err)
+    }
+}
+
+impl From<KitchenError> for ShiftError {
+    fn from(err: KitchenError) -> ShiftError {
+        ShiftError::Kitchen(err)
+    }
+}
+
+impl From<PaymentError> for ShiftError {
+    fn from(err: PaymentError) -> ShiftError {
+        ShiftError::Payment(err)
+    }
+}
+
+impl From<InventoryError> for ShiftError {
+    fn from(err: InventoryError) -> ShiftError {
+        ShiftError::Inventory(err)
+    }
+}
+
+impl From<DeliveryError> for ShiftError {
+    fn from(err: DeliveryError) -> ShiftError {
+        ShiftError::Delivery(err)
+    }
+}
+
+impl From<CustomerError> for ShiftError {
+    fn from(err: CustomerError) -> ShiftError {
+        ShiftError::Customer(err)
+    }
+}
+
+impl From<StaffError> for ShiftError {
+    fn from(err: StaffError) -> ShiftError {
+        ShiftError::Staff(err)
+    }
+}
+
+impl From<EventError> for ShiftError {
+    fn from(err: EventError) -> ShiftError {
+        ShiftError::Log(err)
+    }
+}
+
+/// A service in progress.
+///
+/// The everyday operations here (seating, orders, the kitchen, bills and
+/// payment) do not ask who is doing them. They are for callers that answer
+/// for that themselves, like the simulation, a till with its own login or
+/// a test. To act as one member of staff, with their role and section
+/// checked on every call, go through `on_duty`. Comps, discounts, refunds,
+/// voiding a paid check and managing staff are only there.
+#[derive(Debug, Default)]
+pub struct Shift {
+    waitlist: Waitlist,
+    floor: FloorPlan,
+    reservations: ReservationBook,
+    orders: OrderBook,
+    bills: BTreeMap<OrderId, Bill>,
+    kitchen: KitchenQueue,
+    inventory: Inventory,
+    deliveries: DeliveryBoard,
+    customers: CustomerBook,
+    roster: Roster,
+    pricing: PricingRules,
+    discounts: BTreeMap<OrderId, Vec<Discount>>,
+    log: EventLog,
+    // The shift as the builders left it, before anything was logged; `None`
+    // is the default shift.
+    opening: Option<Box<Shift>>,
+}
+
+impl Shift {
+    /// A shift that opens with this floor and this walk-in.
+    pub fn new(floor: FloorPlan, inventory: Inventory) -> Shift {
+        Shift {
+            floor,
+            inventory,
+            ..Shift::default()
+        }
+        .opened()
+    }
+
+    pub fn with_router(mut self, router: StationRouter) -> Shift {
+        self.kitchen = KitchenQueue::new(router);
+        self.opened()
+    }
+
+    pub fn with_reservations(mut self, reservations: ReservationBook) -> Shift {
+        self.reservations = reservations;
+        self.opened()
+    }
+
+    pub fn with_deliveries(mut self, deliveries: DeliveryBoard) -> Shift {
+        self.deliveries = deliveries;
+        self.opened()
+    }
+
+    pub fn with_customers(mut self, customers: CustomerBook) -> Shift {
+        self.customers = customers;
+        self.opened()
+    }
+
+    /// The staff on the books when the shift opens.
+    pub fn with_roster(mut self, roster: Roster) -> Shift {
+        self.roster = roster;
+        self.opened()
+    }
+
+    /// Tax, gratuity and happy hours for every bill. Without them bills
+    /// come to the menu price and nothing more.
+    pub fn with_pricing(mut self, pricing: PricingRules) -> Shift {
+        self.pricing = pricing;
+        self.opened()
+    }
+
+    /// Writes to `log` from now on, e.g. one opened on a file. The log should
+    /// be empty; use `replay` to pick up a log that already has records.
+    pub fn with_log(mut self, log: EventLog) -> Shift {
+        self.log = log;
+        self
+    }
+
+    pub fn waitlist(&self) -> &Waitlist {
+        &self.waitlist
+    }
+
+    pub fn floor(&self) -> &FloorPlan {
+        &self.floor
+    }
+
+    pub fn reservations(&self) -> &ReservationBook {
+        &self.reservations
+    }
+
+    pub fn orders(&self) -> &OrderBook {
+        &self.orders
+    }
+
+    pub fn bill(&self, order: OrderId) -> Result<&Bill, ShiftError> {
+        self.bills.get(&order).ok_or(ShiftError::NoBill(order))
+    }
+
+    pub fn kitchen(&self) -> &KitchenQueue {
+        &self.kitchen
+    }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn deliveries(&self) -> &DeliveryBoard {
+        &self.deliveries
+    }
+
+    pub fn customers(&self) -> &CustomerBook {
+        &self.customers
+    }
+
+    pub fn roster(&self) -> &Roster {
+        &self.roster
+    }
+
+    pub fn log(&self) -> &EventLog {
+        &self.log
+    }
+
+    // Remembers the shift as it stands as the one to undo back to.
+    fn opened(mut self) -> Shift {
+        self.opening = Some(Box::new(self.unlogged()));
+        self
+    }
+
+    // A copy of everything but the log and the opening.
+    fn unlogged(&self) -> Shift {
+        Shift {
+            waitlist: self.waitlist.clone(),
+            floor: self.floor.clone(),
+            reservations: self.reservations.clone(),
+            orders: self.orders.clone(),
+            bills: self.bills.clone(),
+            kitchen: self.kitchen.clone(),
+            inventory: self.inventory.clone(),
+            deliveries: self.deliveries.clone(),
+            customers: self.customers.clone(),
+            roster: self.roster.clone(),
+            pricing: self.pricing.clone(),
+            discounts: self.discounts.clone(),
+            log: EventLog::new(),
+            opening: None,
+        }
+    }
+
+    // Puts the shift back to what its log says, after an operation changed
+    // it but could not log the change.
+    fn rewind(&mut self) -> Result<(), EventError> {
+        let mut rebuilt = self
+            .opening
+            .as_deref()
+            .map_or_else(Shift::default, Shift::unlogged);
+        for record in self.log.records() {
+            rebuilt
+                .apply(record)
+                .map_err(|message| EventError::Replay {
+                    seq: record.seq,
+                    message,
+                })?;
+        }
+        rebuilt.log = mem::take(&mut self.log);
+        rebuilt.opening = self.opening.take();
+        *self = rebuilt;
+        Ok(())
+    }
+
+    /// Applies every record in `log` to this (opening) state, then keeps
+    /// writing to `log`. Fails if any record does not replay to exactly the
+    /// same outcome.
+    pub fn replay(mut self, log: EventLog) -> Result<Shift, EventError> {
+        self.log = EventLog::new();
+        for record in log.records() {
+            self.apply(record).map_err(|message| EventError::Replay {
+                seq: record.seq,
+                message,
+            })?;
+        }
+        self.log = log;
+        Ok(self)
+    }
+
+    fn apply(&mut self, record: &Record) -> Result<(), String> {
+        let at = record.at;
+        let before = self.log.len();
+        let applied = match record.event.clone() {
+            Event::PartyJoined { guest, .. } => self.add_to_waitlist(guest).map(drop),
+            Event::PartyLeft { party } => self.leave_waitlist(party, at).map(drop),
+            Event::TableSeated { .. } => self.seat_next(at).map(drop),
+            Event::TableVacated { party, .. } => self.vacate(party, at).map(drop),
+            Event::TableBussed { table } => self.bus(table, at),
+            Event::ReservationBooked { request, .. } => self.book(request, at).map(drop),
+            Event::ReservationHeld { reservation } => self.hold(reservation, at),
+            Event::ReservationCheckedIn { reservation, .. } => {
+                self.check_in(reservation, at).map(drop)
+            }
+            Event::ReservationCancelled { reservation } => self.cancel(reservation, at),
+            Event::ReservationNoShow { reservation } => self.mark_no_show(reservation, at),
+            Event::OrderTaken { table, items, .. } => self.take_order(table, items, at).map(drop),
+            Event::ItemAdded { order, item, .. } => self.add_item(order, item, at).map(drop),
+            Event::ItemShared {
+                order,
+                line,
+                shares,
+            } => self.share_item(order, line, shares, at),
+            Event::OrderSentToKitchen { order } => self.send_to_kitchen(order, at),
+            Event::OrderFired { order, .. } => self.cook_order(order, at).map(drop),
+            Event::CourseFired { order, lines, .. } => {
+                self.fire_course(order, &lines, at).map(drop)
+            }
+            Event::TicketBumped { ticket } => self.bump(ticket, at),
+            Event::TicketRecalled { ticket } => self.recall(ticket, at),
+            Event::TicketRefired { ticket, reason, .. } => {
+                self.fix_incorrect_order(ticket, &reason, at).map(drop)
+            }
+            Event::OrderReady { order } => self.mark_ready(order, at),
+            Event::OrderServed { order } => self.serve_order(order, at),
+            Event::OrderVoided { order } => self.void(order, at),
+            Event::BillOpened { order } => self.open_bill(order, at).map(drop),
+            Event::BillSplitBySeat { order } => self.split_by_seat(order, at),
+            Event::BillSplitEvenly { order, ways } => self.split_evenly(order, ways, at),
+            Event::CheckOpened { order, label, .. } => self.open_check(order, &label, at).map(drop),
+            Event::ChecksMerged { order, from, into } => self.merge_checks(order, from, into, at),
+            Event::LineTransferred {
+                order,
+                from,
+                line,
+                to,
+            } => self.transfer_line(order, from, line, to, at),
+            Event::TipAdded {
+                order,
+                check,
+                amount,
+            } => self.add_tip(order, check, amount, at),
+            Event::PaymentCaptured {
+                order,
+                check,
+                tender,
+                transaction,
+                ..
+            } => self
+                .take_payment(order, check, tender, &mut Recorded(transaction), at)
+                .map(drop),
+            Event::PaymentRefunded {
+                order,
+                payment,
+                amount,
+                transaction,
+            } => self
+                .refund(order, payment, amount, &mut Recorded(transaction), at)
+                .map(drop),
+            Event::StockReceived {
+                ingredient,
+                quantity,
+            } => self.receive_stock(&ingredient, quantity, at),
+            Event::DeliveryOpened {
+                order,
+                customer,
+                fulfilment,
+                promised,
+                ..
+            } => self
+                .open_delivery(order, &customer, fulfilment, promised, at)
+                .map(drop),
+            Event::DeliveryPacked { delivery } => self.pack_delivery(delivery, at),
+            Event::CourierAssigned { delivery, .. } => self.assign_courier(delivery, at).map(drop),
+            Event::OrderHandedOff { order, .. } => self.deliver_order(order, at).map(drop),
+            Event::DeliveryCompleted { delivery } => self.complete_delivery(delivery, at),
+            Event::DeliveryCancelled { delivery } => self.cancel_delivery(delivery, at),
+            Event::CustomerJoined { profile, .. } => self.join_loyalty(profile, at).map(drop),
+            Event::VisitRecorded {
+                customer,
+                order,
+                check,
+                ..
+            } => self.record_visit(customer, order, check, at).map(drop),
+            Event::StaffHired { name, role, .. } => self.hire(&name, role, at).map(drop),
+            Event::StaffClockedIn { staff } => self.clock_in(staff, at),
+            Event::StaffClockedOut { staff } => self.clock_out(staff, at).map(drop),
+            Event::SectionAssigned { staff, section } => {
+                self.assign_section(staff, section.as_deref(), at)
+            }
+            Event::ItemComped { order, line, by } => self.comp_item(order, line, by, at),
+            Event::DiscountApplied {
+                order,
+                discount,
+                by,
+            } => self.apply_discount(order, discount, by, at),
+            Event::CheckVoided { order, by } => self.close_voided_check(order, by, at),
+        };
+        applied.map_err(|err| err.to_string())?;
+
+        let replayed: Vec<&Event> = self.log.records()[before..]
+            .iter()
+            .map(|r| &r.event)
+            .collect();
+        if replayed != [&record.event] {
+            return Err(format!(
+                "the log has {:?} but replaying gave {:?}",
+                record.event, replayed
+            ));
+        }
+        Ok(())
+    }
+
+    // Logs what an operation did. If the record cannot be written the
+    // operation is undone, and the error is the one from writing it (or from
+    // undoing it, should that fail too).
+    fn record(&mut self, at: Timestamp, event: Event) -> Result<(), ShiftError> {
+        if let Err(err) = self.log.append(at, event) {
+            self.rewind()?;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    fn bill_mut(&mut self, order: OrderId) -> Result<&mut Bill, ShiftError> {
+        self.bills.get_mut(&order).ok_or(ShiftError::NoBill(order))
+    }
+
+    // hosting
+
+    pub fn add_to_waitlist(&mut self, party: Party) -> Result<PartyId, ShiftError> {
+        let at = party.arrived;
+        let id = hosting::add_to_waitlist(&mut self.waitlist, party.clone());
+        self.record(
+            at,
+            Event::PartyJoined {
+                party: id,
+                guest: party,
+            },
+        )?;
+        Ok(id)
+    }
+
+    /// The party got tired of waiting.
+    pub fn leave_waitlist(
+        &mut self,
+        party: PartyId,
+        now: Timestamp,
+    ) -> Result<WaitlistEntry, ShiftError> {
+        let entry = self
+            .waitlist
+            .remove(party)
+            .ok_or(ShiftError::UnknownParty(party))?;
+        self.record(now, Event::PartyLeft { party })?;
+        Ok(entry)
+    }
+
+    pub fn seat_next(&mut self, now: Timestamp) -> Result<Seated, ShiftError> {
+        let seated = hosting::seat_at_table(&mut self.waitlist, &mut self.floor, now)?;
+        self.record(
+            now,
+            Event::TableSeated {
+                party: seated.id,
+                tables: seated.tables.clone(),
+            },
+        )?;
+        Ok(seated)
+    }
+
+    /// The party has left; their tables need bussing.
+    pub fn vacate(
+        &mut self,
+        party: PartyId,
+        now: Timestamp,
+    ) -> Result<Vec<TableNumber>, ShiftError> {
+        let tables = self.floor.vacate(party);
+        self.reservations.vacated(party);
+        self.record(
+            now,
+            Event::TableVacated {
+                party,
+                tables: tables.clone(),
+            },
+        )?;
+        Ok(tables)
+    }
+
+    pub fn bus(&mut self, table: TableNumber, now: Timestamp) -> Result<(), ShiftError> {
+        self.floor.bus(table)?;
+        self.record(now, Event::TableBussed { table })
+    }
+
+    pub fn book(
+        &mut self,
+        request: ReservationRequest,
+        now: Timestamp,
+    ) -> Result<ReservationId, ShiftError> {
+        let id = self.reservations.book(&self.floor, request.clone())?;
+        self.record(
+            now,
+            Event::ReservationBooked {
+                reservation: id,
+                request,
+            },
+        )?;
+        Ok(id)
+    }
+
+    pub fn hold(&mut self, reservation: ReservationId, now: Timestamp) -> Result<(), ShiftError> {
+        self.reservations.hold(&mut self.floor, reservation)?;
+        self.record(now, Event::ReservationHeld { reservation })
+    }
+
+    pub fn check_in(
+        &mut self,
+        reservation: ReservationId,
+        now: Timestamp,
+    ) -> Result<PartyId, ShiftError> {
+        let party =
+            self.reservations
+                .check_in(&mut self.waitlist, &mut self.floor, reservation, now)?;
+        self.record(now, Event::ReservationCheckedIn { reservation, party })?;
+        Ok(party)
+    }
+
+    pub fn cancel(&mut self, reservation: ReservationId, now: Timestamp) -> Result<(), ShiftError> {
+        self.reservations.cancel(&mut self.floor, reservation)?;
+        self.record(now, Event::ReservationCancelled { reservation })
+    }
+
+    pub fn mark_no_show(
+        &mut self,
+        reservation: ReservationId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.reservations
+            .mark_no_show(&mut self.floor, reservation, now)?;
+        self.record(now, Event::ReservationNoShow { reservation })
+    }
+
+    // serving
+
+    pub fn take_order(
+        &mut self,
+        table: TableNumber,
+        items: Vec<LineItem>,
+        now: Timestamp,
+    ) -> Result<OrderId, ShiftError> {
+        let order = serving::take_order(&mut self.orders, table, items.clone(), now);
+        self.record(
+            now,
+            Event::OrderTaken {
+                order,
+                table,
+                items,
+            },
+        )?;
+        Ok(order)
+    }
+
+    /// Adds a line to an order still on the pad and returns its index.
+    pub fn add_item(
+        &mut self,
+        order: OrderId,
+        item: LineItem,
+        now: Timestamp,
+    ) -> Result<usize, ShiftError> {
+        let line = self.orders.get_mut(order)?.add_item(item.clone())?;
+        self.record(now, Event::ItemAdded { order, line, item })?;
+        Ok(line)
+    }
+
+    /// Splits a line between seats for the bill; see `Order::share_item`.
+    /// An open bill is printed again, so this has to come before payment.
+    pub fn share_item(
+        &mut self,
+        order: OrderId,
+        line: usize,
+        shares: Vec<Share>,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.ensure_no_payments(order)?;
+        self.orders
+            .get_mut(order)?
+            .share_item(line, shares.clone())?;
+        self.reprint_bill(order)?;
+        self.record(
+            now,
+            Event::ItemShared {
+                order,
+                line,
+                shares,
+            },
+        )
+    }
+
+    pub fn send_to_kitchen(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.orders.get_mut(order)?.send_to_kitchen(now)?;
+        self.record(now, Event::OrderSentToKitchen { order })
+    }
+
+    pub fn mark_ready(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.orders.get_mut(order)?.mark_ready(now)?;
+        self.record(now, Event::OrderReady { order })
+    }
+
+    pub fn serve_order(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        serving::serve_order(&mut self.orders, order, now)?;
+        self.record(now, Event::OrderServed { order })
+    }
+
+    pub(crate) fn void(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.orders.get_mut(order)?.void(now)?;
+        self.record(now, Event::OrderVoided { order })
+    }
+
+    // kitchen
+
+    pub fn cook_order(
+        &mut self,
+        order: OrderId,
+        now: Timestamp,
+    ) -> Result<Vec<TicketId>, ShiftError> {
+        let tickets = kitchen::cook_order(
+            &mut self.kitchen,
+            &mut self.inventory,
+            self.orders.get_mut(order)?,
+            now,
+        )?;
+        self.record(
+            now,
+            Event::OrderFired {
+                order,
+                tickets: tickets.clone(),
+            },
+        )?;
+        Ok(tickets)
+    }
+
+    /// Fires some lines of a sent order, e.g. when a `CourseScheduler`
+    /// says their course is due. Lines already fired are refused; a dish
+    /// that went out wrong goes back through `fix_incorrect_order`.
+    pub fn fire_course(
+        &mut self,
+        order: OrderId,
+        lines: &[usize],
+        now: Timestamp,
+    ) -> Result<Vec<TicketId>, ShiftError> {
+        let tickets = kitchen::cook_lines(
+            &mut self.kitchen,
+            &mut self.inventory,
+            self.orders.get_mut(order)?,
+            lines,
+            now,
+        )?;
+        self.record(
+            now,
+            Event::CourseFired {
+                order,
+                lines: lines.to_vec(),
+                tickets: tickets.clone(),
+            },
+        )?;
+        Ok(tickets)
+    }
+
+    pub fn bump(&mut self, ticket: TicketId, now: Timestamp) -> Result<(), ShiftError> {
+        self.kitchen.bump(ticket, now)?;
+        self.record(now, Event::TicketBumped { ticket })
+    }
+
+    pub fn recall(&mut self, ticket: TicketId, now: Timestamp) -> Result<(), ShiftError> {
+        self.kitchen.recall(ticket)?;
+        self.record(now, Event::TicketRecalled { ticket })
+    }
+
+    pub fn fix_incorrect_order(
+        &mut self,
+        ticket: TicketId,
+        reason: &str,
+        now: Timestamp,
+    ) -> Result<TicketId, ShiftError> {
+        let refire =
+            kitchen::fix_incorrect_order(&mut self.kitchen, &self.orders, ticket, reason, now)?;
+        let order = self.kitchen.ticket(refire)?.order;
+        self.deliveries.remake(order);
+        self.record(
+            now,
+            Event::TicketRefired {
+                ticket,
+                refire,
+                reason: String::from(reason),
+            },
+        )?;
+        Ok(refire)
+    }
+
+    pub fn receive_stock(
+        &mut self,
+        ingredient: &str,
+        quantity: u32,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.inventory.receive(ingredient, quantity)?;
+        self.record(
+            now,
+            Event::StockReceived {
+                ingredient: String::from(ingredient),
+                quantity,
+            },
+        )
+    }
+
+    // delivery and takeout
+
+    /// Starts a delivery or takeout for an order, which is usually taken
+    /// against `delivery::COUNTER`.
+    pub fn open_delivery(
+        &mut self,
+        order: OrderId,
+        customer: &str,
+        fulfilment: Fulfilment,
+        promised: Timestamp,
+        now: Timestamp,
+    ) -> Result<DeliveryId, ShiftError> {
+        self.orders.get(order)?;
+        let delivery = self
+            .deliveries
+            .open(order, customer, fulfilment.clone(), promised, now)?;
+        self.record(
+            now,
+            Event::DeliveryOpened {
+                delivery,
+                order,
+                customer: String::from(customer),
+                fulfilment,
+                promised,
+            },
+        )?;
+        Ok(delivery)
+    }
+
+    pub fn pack_delivery(
+        &mut self,
+        delivery: DeliveryId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.deliveries.pack(delivery, now)?;
+        self.record(now, Event::DeliveryPacked { delivery })
+    }
+
+    /// Books a courier ahead of time, e.g. while the food is still cooking.
+    pub fn assign_courier(
+        &mut self,
+        delivery: DeliveryId,
+        now: Timestamp,
+    ) -> Result<CourierId, ShiftError> {
+        let courier = self.deliveries.assign(delivery)?;
+        self.record(now, Event::CourierAssigned { delivery, courier })?;
+        Ok(courier)
+    }
+
+    pub fn deliver_order(
+        &mut self,
+        order: OrderId,
+        now: Timestamp,
+    ) -> Result<Delivery, ShiftError> {
+        let handed_off = delivery::deliver_order(&mut self.deliveries, order, now)?.clone();
+        self.record(
+            now,
+            Event::OrderHandedOff {
+                order,
+                delivery: handed_off.id,
+                courier: handed_off.courier,
+            },
+        )?;
+        Ok(handed_off)
+    }
+
+    pub fn complete_delivery(
+        &mut self,
+        delivery: DeliveryId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.deliveries.complete(delivery, now)?;
+        self.record(now, Event::DeliveryCompleted { delivery })
+    }
+
+    pub fn cancel_delivery(
+        &mut self,
+        delivery: DeliveryId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.deliveries.cancel(delivery, now)?;
+        self.record(now, Event::DeliveryCancelled { delivery })
+    }
+
+    // customers
+
+    pub fn join_loyalty(
+        &mut self,
+        profile: Profile,
+        now: Timestamp,
+    ) -> Result<CustomerId, ShiftError> {
+        let customer = self.customers.add(profile);
+        let profile = self.customers.get(customer)?.clone();
+        self.record(now, Event::CustomerJoined { customer, profile })?;
+        Ok(customer)
+    }
+
+    /// Credits a loyalty member with the points for a paid check.
+    pub fn record_visit(
+        &mut self,
+        customer: CustomerId,
+        order: OrderId,
+        check: CheckId,
+        now: Timestamp,
+    ) -> Result<Visit, ShiftError> {
+        let bill = self.bills.get(&order).ok_or(ShiftError::NoBill(order))?;
+        let check = bill.check(check)?;
+        let visit = self
+            .customers
+            .record_visit(customer, self.orders.get(order)?, check, now)?;
+        self.record(
+            now,
+            Event::VisitRecorded {
+                customer,
+                order,
+                check: visit.check,
+                points: visit.points,
+            },
+        )?;
+        Ok(visit)
+    }
+
+    // staff
+
+    /// Acts on the shift as `staff`, with their permissions checked on every
+    /// call. They have to be clocked in.
+    pub fn on_duty(&mut self, staff: StaffId) -> Result<OnDuty<'_>, ShiftError> {
+        if !self.roster.get(staff)?.is_on_shift() {
+            return Err(StaffError::NotClockedIn(staff).into());
+        }
+        Ok(OnDuty::new(self, staff))
+    }
+
+    pub fn clock_in(&mut self, staff: StaffId, now: Timestamp) -> Result<(), ShiftError> {
+        self.roster.clock_in(staff, now)?;
+        self.record(now, Event::StaffClockedIn { staff })
+    }
+
+    /// Returns how long the stint they just finished was.
+    pub fn clock_out(&mut self, staff: StaffId, now: Timestamp) -> Result<Duration, ShiftError> {
+        let stint = self.roster.clock_out(staff, now)?;
+        self.record(now, Event::StaffClockedOut { staff })?;
+        Ok(stint)
+    }
+
+    // Only reachable through OnDuty, which checks the permissions first.
+    pub(crate) fn hire(
+        &mut self,
+        name: &str,
+        role: Role,
+        now: Timestamp,
+    ) -> Result<StaffId, ShiftError> {
+        let staff = self.roster.add(name, role);
+        self.record(
+            now,
+            Event::StaffHired {
+                staff,
+                name: String::from(name),
+                role,
+            },
+        )?;
+        Ok(staff)
+    }
+
+    pub(crate) fn assign_section(
+        &mut self,
+        staff: StaffId,
+        section: Option<&str>,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.roster.assign_section(staff, section)?;
+        self.record(
+            now,
+            Event::SectionAssigned {
+                staff,
+                section: section.map(String::from),
+            },
+        )
+    }
+
+    /// Takes a line off the bill. A bill that is already open is printed
+    /// again on a single check, so comps have to come before payment.
+    pub(crate) fn comp_item(
+        &mut self,
+        order: OrderId,
+        line: usize,
+        by: StaffId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.ensure_no_payments(order)?;
+        self.orders.get_mut(order)?.comp_item(line)?;
+        self.reprint_bill(order)?;
+        self.record(now, Event::ItemComped { order, line, by })
+    }
+
+    /// Takes `discount` off the order, after any given before it. Like a
+    /// comp, an open bill is printed again, so it has to come before payment.
+    pub(crate) fn apply_discount(
+        &mut self,
+        order: OrderId,
+        discount: Discount,
+        by: StaffId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.ensure_no_payments(order)?;
+        let status = self.orders.get(order)?.status();
+        if status.is_closed() {
+            return Err(OrderError::NotEditable { order, status }.into());
+        }
+        self.discounts
+            .entry(order)
+            .or_default()
+            .push(discount.clone());
+        self.reprint_bill(order)?;
+        self.record(
+            now,
+            Event::DiscountApplied {
+                order,
+                discount,
+                by,
+            },
+        )
+    }
+
+    /// Refunds whatever is left on every payment for a paid order, then
+    /// voids it.
+    pub(crate) fn void_check(
+        &mut self,
+        order: OrderId,
+        gateway: &mut dyn PaymentGateway,
+        by: StaffId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        let status = self.orders.get(order)?.status();
+        if status != OrderStatus::Paid {
+            return Err(OrderError::IllegalTransition {
+                order,
+                from: status,
+                to: OrderStatus::Voided,
+            }
+            .into());
+        }
+        let refundable: Vec<(PaymentId, Cents)> = self
+            .bill(order)?
+            .checks()
+            .iter()
+            .flat_map(|c| c.payments.iter())
+            .filter(|p| p.applied > p.refunded)
+            .map(|p| (p.id, p.applied - p.refunded))
+            .collect();
+        for (payment, amount) in refundable {
+            self.refund(order, payment, amount, gateway, now)?;
+        }
+        self.close_voided_check(order, by, now)
+    }
+
+    fn close_voided_check(
+        &mut self,
+        order: OrderId,
+        by: StaffId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.orders.get_mut(order)?.void_paid(now)?;
+        self.record(now, Event::CheckVoided { order, by })
+    }
+
+    // billing
+
+    /// Prints the bill for an order. Opening it again starts over from a
+    /// single check, which is only allowed before anything was paid.
+    pub fn open_bill(&mut self, order: OrderId, now: Timestamp) -> Result<&Bill, ShiftError> {
+        if let Some(bill) = self.bills.get(&order) {
+            if bill.checks().iter().any(|c| !c.payments.is_empty()) {
+                return Err(PaymentError::SplitAfterPayment.into());
+            }
+        }
+        let bill = self.bill_for(order)?;
+        self.bills.insert(order, bill);
+        self.record(now, Event::BillOpened { order })?;
+        self.bill(order)
+    }
+
+    /// What the order comes to under the shift's pricing rules, with every
+    /// comp, discount, tax and gratuity itemised.
+    pub fn breakdown(&self, order: OrderId) -> Result<Breakdown, ShiftError> {
+        let order = self.orders.get(order)?;
+        let discounts = self.discounts.get(&order.id).map_or(&[][..], Vec::as_slice);
+        Ok(self.pricing.price(order, self.covers(order), discounts))
+    }
+
+    fn bill_for(&self, order: OrderId) -> Result<Bill, ShiftError> {
+        let breakdown = self.breakdown(order)?;
+        Ok(Bill::priced(self.orders.get(order)?, &breakdown))
+    }
+
+    // Prints an open bill again after the order changed under it, back on a
+    // single check.
+    fn reprint_bill(&mut self, order: OrderId) -> Result<(), ShiftError> {
+        if self.bills.contains_key(&order) {
+            let bill = self.bill_for(order)?;
+            self.bills.insert(order, bill);
+        }
+        Ok(())
+    }
+
+    fn ensure_no_payments(&self, order: OrderId) -> Result<(), ShiftError> {
+        let paid = self
+            .bills
+            .get(&order)
+            .is_some_and(|bill| bill.checks().iter().any(|c| !c.payments.is_empty()));
+        if paid {
+            return Err(PaymentError::PaymentsTaken(order).into());
+        }
+        Ok(())
+    }
+
+    // The size of the party that was sat at the order's table last before
+    // the order was opened, or 0 for orders that never had a party.
+    fn covers(&self, order: &Order) -> u8 {
+        let opened = order.history().first().map(|change| change.at);
+        self.waitlist
+            .seated()
+            .iter()
+            .filter(|seated| seated.tables.contains(&order.table))
+            .filter(|seated| opened.is_none_or(|opened| seated.seated_at <= opened))
+            .max_by_key(|seated| seated.seated_at)
+            .map_or(0, |seated| seated.party.size)
+    }
+
+    pub fn split_by_seat(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.bill_mut(order)?.split_by_seat()?;
+        self.record(now, Event::BillSplitBySeat { order })
+    }
+
+    pub fn split_evenly(
+        &mut self,
+        order: OrderId,
+        ways: usize,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.bill_mut(order)?.split_evenly(ways)?;
+        self.record(now, Event::BillSplitEvenly { order, ways })
+    }
+
+    /// Adds an empty check to the bill, for moving lines onto.
+    pub fn open_check(
+        &mut self,
+        order: OrderId,
+        label: &str,
+        now: Timestamp,
+    ) -> Result<CheckId, ShiftError> {
+        let check = self.bill_mut(order)?.open_check(label);
+        self.record(
+            now,
+            Event::CheckOpened {
+                order,
+                check,
+                label: String::from(label),
+            },
+        )?;
+        Ok(check)
+    }
+
+    pub fn merge_checks(
+        &mut self,
+        order: OrderId,
+        from: CheckId,
+        into: CheckId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.bill_mut(order)?.merge(from, into)?;
+        self.record(now, Event::ChecksMerged { order, from, into })
+    }
+
+    pub fn transfer_line(
+        &mut self,
+        order: OrderId,
+        from: CheckId,
+        line: usize,
+        to: CheckId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.bill_mut(order)?.transfer(from, line, to)?;
+        self.record(
+            now,
+            Event::LineTransferred {
+                order,
+                from,
+                line,
+                to,
+            },
+        )
+    }
+
+    pub fn add_tip(
+        &mut self,
+        order: OrderId,
+        check: CheckId,
+        amount: Cents,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.bill_mut(order)?.add_tip(check, amount)?;
+        self.record(
+            now,
+            Event::TipAdded {
+                order,
+                check,
+                amount,
+            },
+        )
+    }
+
+    pub fn take_payment(
+        &mut self,
+        order: OrderId,
+        check: CheckId,
+        tender: Tender,
+        gateway: &mut dyn PaymentGateway,
+        now: Timestamp,
+    ) -> Result<Receipt, ShiftError> {
+        let bill = self
+            .bills
+            .get_mut(&order)
+            .ok_or(ShiftError::NoBill(order))?;
+        let receipt = billing::take_payment(
+            &mut self.orders,
+            bill,
+            check,
+            tender.clone(),
+            &mut LoyaltyGateway::new(&mut self.customers, gateway),
+            now,
+        )?;
+        let transaction = self.payment_transaction(order, receipt.payment);
+        let logged = self.record(
+            now,
+            Event::PaymentCaptured {
+                order,
+                check,
+                payment: receipt.payment,
+                tender: tender.clone(),
+                transaction: transaction.clone(),
+            },
+        );
+        if let Err(err) = logged {
+            // the shift was put back, so give the money back too; points
+            // were taken off the customer book, which was put back with it
+            if let Some(transaction) = &transaction {
+                if !matches!(tender, Tender::Points { .. }) {
+                    let _ = gateway.refund(transaction, receipt.applied);
+                }
+            }
+            return Err(err);
+        }
+        Ok(receipt)
+    }
+
+    pub(crate) fn refund(
+        &mut self,
+        order: OrderId,
+        payment: PaymentId,
+        amount: Cents,
+        gateway: &mut dyn PaymentGateway,
+        now: Timestamp,
+    ) -> Result<Refund, ShiftError> {
+        let bill = self
+            .bills
+            .get_mut(&order)
+            .ok_or(ShiftError::NoBill(order))?;
+        let mut gateway = LoyaltyGateway::new(&mut self.customers, gateway);
+        let refund = bill.refund(payment, amount, &mut gateway)?;
+        self.record(
+            now,
+            Event::PaymentRefunded {
+                order,
+                payment,
+                amount,
+                transaction: refund.transaction.clone(),
+            },
+        )?;
+        Ok(refund)
+    }
+
+    fn payment_transaction(&self, order: OrderId, payment: PaymentId) -> Option<TransactionId> {
+        self.bills
+            .get(&order)?
+            .checks()
+            .iter()
+            .flat_map(|c| c.payments.iter())
+            .find(|p| p.id == payment)?
+            .transaction
+            .clone()
+    }
+}
+
+// Stands in for the real gateway during a replay: the money already moved,
+// so every call just hands back the transaction the log remembers.
+struct Recorded(Option<TransactionId>);
+
+impl Recorded {
+    fn transaction(&self) -> Result<TransactionId, GatewayError> {
+        self.0.clone().ok_or(GatewayError::Unavailable)
+    }
+}
+
+impl PaymentGateway for Recorded {
+    fn charge_card(&mut self, _last4: &str, _amount: Cents) -> Result<TransactionId, GatewayError> {
+        self.transaction()
+    }
+
+    fn redeem_voucher(
+        &mut self,
+        _code: &str,
+        _amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        self.transaction()
+    }
+
+    fn refund(
+        &mut self,
+        _transaction: &TransactionId,
+        _amount: Cents,
+    ) -> Result<TransactionId, GatewayError> {
+        self.transaction()
+    }
+}
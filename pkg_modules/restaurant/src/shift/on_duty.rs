@@ -0,0 +1,312 @@
+use std::time::Duration;
+
+use super::{Shift, ShiftError};
+use crate::billing::{
+    Bill, CheckId, Discount, PaymentGateway, PaymentId, Receipt, Refund, Share, Tender,
+};
+use crate::hosting::{PartyId, Seated, TableNumber};
+use crate::kitchen::TicketId;
+use crate::serving::{Cents, LineItem, OrderId};
+use crate::staff::{Permission, Role, StaffId, StaffMember};
+use crate::time::Timestamp;
+
+/// The shift as one member of staff sees it. Every call checks that they
+/// are still clocked in and that their role allows it; servers with a
+/// section can only work the tables in it.
+pub struct OnDuty<'a> {
+    shift: &'a mut Shift,
+    staff: StaffId,
+}
+
+impl<'a> OnDuty<'a> {
+    pub(super) fn new(shift: &'a mut Shift, staff: StaffId) -> OnDuty<'a> {
+        OnDuty { shift, staff }
+    }
+
+    pub fn staff(&self) -> &StaffMember {
+        self.shift
+            .roster
+            .get(self.staff)
+            .expect("on_duty checked they are on the roster")
+    }
+
+    pub fn shift(&self) -> &Shift {
+        self.shift
+    }
+
+    fn allow(&self, permission: Permission) -> Result<(), ShiftError> {
+        self.shift.roster.authorize(self.staff, permission)?;
+        Ok(())
+    }
+
+    fn allow_table(&self, permission: Permission, table: TableNumber) -> Result<(), ShiftError> {
+        self.shift
+            .roster
+            .authorize_table(self.staff, permission, &self.shift.floor, table)?;
+        Ok(())
+    }
+
+    fn allow_order(&self, permission: Permission, order: OrderId) -> Result<(), ShiftError> {
+        let table = self.shift.orders.get(order)?.table;
+        self.allow_table(permission, table)
+    }
+
+    // hosting
+
+    pub fn seat_next(&mut self, now: Timestamp) -> Result<Seated, ShiftError> {
+        self.allow(Permission::SeatGuests)?;
+        self.shift.seat_next(now)
+    }
+
+    pub fn vacate(
+        &mut self,
+        party: PartyId,
+        now: Timestamp,
+    ) -> Result<Vec<TableNumber>, ShiftError> {
+        self.allow(Permission::SeatGuests)?;
+        self.shift.vacate(party, now)
+    }
+
+    pub fn bus(&mut self, table: TableNumber, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow(Permission::SeatGuests)?;
+        self.shift.bus(table, now)
+    }
+
+    // orders
+
+    pub fn take_order(
+        &mut self,
+        table: TableNumber,
+        items: Vec<LineItem>,
+        now: Timestamp,
+    ) -> Result<OrderId, ShiftError> {
+        self.allow_table(Permission::TakeOrders, table)?;
+        self.shift.take_order(table, items, now)
+    }
+
+    pub fn add_item(
+        &mut self,
+        order: OrderId,
+        item: LineItem,
+        now: Timestamp,
+    ) -> Result<usize, ShiftError> {
+        self.allow_order(Permission::TakeOrders, order)?;
+        self.shift.add_item(order, item, now)
+    }
+
+    pub fn share_item(
+        &mut self,
+        order: OrderId,
+        line: usize,
+        shares: Vec<Share>,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakeOrders, order)?;
+        self.shift.share_item(order, line, shares, now)
+    }
+
+    pub fn send_to_kitchen(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakeOrders, order)?;
+        self.shift.send_to_kitchen(order, now)
+    }
+
+    pub fn serve_order(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakeOrders, order)?;
+        self.shift.serve_order(order, now)
+    }
+
+    /// Voids an order that has not been paid for yet.
+    pub fn void(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow_order(Permission::VoidOrder, order)?;
+        self.shift.void(order, now)
+    }
+
+    // kitchen
+
+    pub fn cook_order(
+        &mut self,
+        order: OrderId,
+        now: Timestamp,
+    ) -> Result<Vec<TicketId>, ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.cook_order(order, now)
+    }
+
+    pub fn fire_course(
+        &mut self,
+        order: OrderId,
+        lines: &[usize],
+        now: Timestamp,
+    ) -> Result<Vec<TicketId>, ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.fire_course(order, lines, now)
+    }
+
+    pub fn mark_ready(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.mark_ready(order, now)
+    }
+
+    pub fn bump(&mut self, ticket: TicketId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.bump(ticket, now)
+    }
+
+    pub fn recall(&mut self, ticket: TicketId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.recall(ticket, now)
+    }
+
+    pub fn fix_incorrect_order(
+        &mut self,
+        ticket: TicketId,
+        reason: &str,
+        now: Timestamp,
+    ) -> Result<TicketId, ShiftError> {
+        self.allow(Permission::Cook)?;
+        self.shift.fix_incorrect_order(ticket, reason, now)
+    }
+
+    // billing
+
+    pub fn open_bill(&mut self, order: OrderId, now: Timestamp) -> Result<&Bill, ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.open_bill(order, now)
+    }
+
+    pub fn split_by_seat(&mut self, order: OrderId, now: Timestamp) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.split_by_seat(order, now)
+    }
+
+    pub fn split_evenly(
+        &mut self,
+        order: OrderId,
+        ways: usize,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.split_evenly(order, ways, now)
+    }
+
+    pub fn open_check(
+        &mut self,
+        order: OrderId,
+        label: &str,
+        now: Timestamp,
+    ) -> Result<CheckId, ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.open_check(order, label, now)
+    }
+
+    pub fn merge_checks(
+        &mut self,
+        order: OrderId,
+        from: CheckId,
+        into: CheckId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.merge_checks(order, from, into, now)
+    }
+
+    pub fn transfer_line(
+        &mut self,
+        order: OrderId,
+        from: CheckId,
+        line: usize,
+        to: CheckId,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.transfer_line(order, from, line, to, now)
+    }
+
+    pub fn add_tip(
+        &mut self,
+        order: OrderId,
+        check: CheckId,
+        amount: Cents,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.add_tip(order, check, amount, now)
+    }
+
+    pub fn take_payment(
+        &mut self,
+        order: OrderId,
+        check: CheckId,
+        tender: Tender,
+        gateway: &mut dyn PaymentGateway,
+        now: Timestamp,
+    ) -> Result<Receipt, ShiftError> {
+        self.allow_order(Permission::TakePayment, order)?;
+        self.shift.take_payment(order, check, tender, gateway, now)
+    }
+
+    pub fn refund(
+        &mut self,
+        order: OrderId,
+        payment: PaymentId,
+        amount: Cents,
+        gateway: &mut dyn PaymentGateway,
+        now: Timestamp,
+    ) -> Result<Refund, ShiftError> {
+        self.allow(Permission::Refund)?;
+        self.shift.refund(order, payment, amount, gateway, now)
+    }
+
+    /// Refunds a paid order in full and voids it.
+    pub fn void_check(
+        &mut self,
+        order: OrderId,
+        gateway: &mut dyn PaymentGateway,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow(Permission::VoidPaidCheck)?;
+        self.shift.void_check(order, gateway, self.staff, now)
+    }
+
+    pub fn comp_item(
+        &mut self,
+        order: OrderId,
+        line: usize,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow(Permission::Comp)?;
+        self.shift.comp_item(order, line, self.staff, now)
+    }
+
+    pub fn apply_discount(
+        &mut self,
+        order: OrderId,
+        discount: Discount,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow(Permission::Comp)?;
+        self.shift.apply_discount(order, discount, self.staff, now)
+    }
+
+    // staff
+
+    pub fn hire(&mut self, name: &str, role: Role, now: Timestamp) -> Result<StaffId, ShiftError> {
+        self.allow(Permission::ManageStaff)?;
+        self.shift.hire(name, role, now)
+    }
+
+    pub fn assign_section(
+        &mut self,
+        staff: StaffId,
+        section: Option<&str>,
+        now: Timestamp,
+    ) -> Result<(), ShiftError> {
+        self.allow(Permission::ManageStaff)?;
+        self.shift.assign_section(staff, section, now)
+    }
+
+    /// Clocks out, which also ends this session.
+    pub fn clock_out(self, now: Timestamp) -> Result<Duration, ShiftError> {
+        self.shift.clock_out(self.staff, now)
+    }
+}
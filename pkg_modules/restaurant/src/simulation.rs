@@ -0,0 +1,600 @@
+// A discrete-event simulation of a whole service, for trying out floor
+// layouts and staffing without a live restaurant. Guests arrive at random,
+// but from a seed, so the same configuration always plays out the same way
+// and two configurations can be compared fairly. Everything runs through a
+// real Shift, so the simulated night has an event log and an end-of-day
+// report like any other.
+
+mod rng;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use self::rng::Rng;
+use crate::billing::{InMemoryGateway, Tender};
+use crate::hosting::{Party, PartyId, SeatError, TableNumber};
+use crate::kitchen::{Station, TicketId};
+use crate::menu::{Category, Menu};
+use crate::report::ShiftReport;
+use crate::serving::{LineItem, OrderId};
+use crate::shift::{Shift, ShiftError};
+use crate::time::Timestamp;
+
+const STATIONS: [Station; 4] = [
+    Station::Grill,
+    Station::Fry,
+    Station::Salad,
+    Station::Dessert,
+];
+
+/// Everything random about the night, plus the staff on it. Durations given
+/// as a pair are a range to draw from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimConfig {
+    pub seed: u64,
+    pub opens: Timestamp,
+    /// No new guests arrive after this long.
+    pub open_for: Duration,
+    /// Average gap between two parties walking in.
+    pub arrival_gap: Duration,
+    /// Party sizes and how often each turns up, relative to the others.
+    pub party_sizes: Vec<(u8, u32)>,
+    /// How long a party will stand on the waitlist before giving up.
+    pub patience: (Duration, Duration),
+    /// From sitting down to ordering.
+    pub browse: (Duration, Duration),
+    /// From the food arriving to paying up.
+    pub dwell: (Duration, Duration),
+    /// Percent of guests who order a drink with their food.
+    pub drinks: u8,
+    pub bussers: u8,
+    pub bus_time: Duration,
+    pub cooks: BTreeMap<Station, u8>,
+    pub cook_times: BTreeMap<Station, (Duration, Duration)>,
+}
+
+impl Default for SimConfig {
+    fn default() -> SimConfig {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        SimConfig {
+            seed: 0,
+            opens: Timestamp::default(),
+            open_for: minutes(4 * 60),
+            arrival_gap: minutes(6),
+            party_sizes: vec![(1, 2), (2, 5), (3, 2), (4, 3), (6, 1)],
+            patience: (minutes(15), minutes(45)),
+            browse: (minutes(3), minutes(10)),
+            dwell: (minutes(25), minutes(60)),
+            drinks: 60,
+            bussers: 1,
+            bus_time: minutes(3),
+            cooks: STATIONS.iter().map(|s| (*s, 1)).collect(),
+            cook_times: [
+                (Station::Grill, (minutes(8), minutes(15))),
+                (Station::Fry, (minutes(6), minutes(10))),
+                (Station::Salad, (minutes(3), minutes(6))),
+                (Station::Dessert, (minutes(2), minutes(5))),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+impl SimConfig {
+    pub fn new(seed: u64, opens: Timestamp) -> SimConfig {
+        SimConfig {
+            seed,
+            opens,
+            ..SimConfig::default()
+        }
+    }
+
+    pub fn open_for(mut self, open_for: Duration) -> SimConfig {
+        self.open_for = open_for;
+        self
+    }
+
+    pub fn arrivals_every(mut self, gap: Duration) -> SimConfig {
+        self.arrival_gap = gap;
+        self
+    }
+
+    pub fn party_sizes(mut self, sizes: &[(u8, u32)]) -> SimConfig {
+        self.party_sizes = sizes.to_vec();
+        self
+    }
+
+    pub fn patience(mut self, low: Duration, high: Duration) -> SimConfig {
+        self.patience = (low, high);
+        self
+    }
+
+    pub fn dwell(mut self, low: Duration, high: Duration) -> SimConfig {
+        self.dwell = (low, high);
+        self
+    }
+
+    pub fn bussers(mut self, bussers: u8) -> SimConfig {
+        self.bussers = bussers;
+        self
+    }
+
+    pub fn cooks(mut self, station: Station, cooks: u8) -> SimConfig {
+        self.cooks.insert(station, cooks);
+        self
+    }
+
+    pub fn cook_time(mut self, station: Station, low: Duration, high: Duration) -> SimConfig {
+        self.cook_times.insert(station, (low, high));
+        self
+    }
+}
+
+/// How hard one kitchen station worked.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationLoad {
+    pub station: Station,
+    pub cooks: u8,
+    pub tickets: usize,
+    pub busy_secs: u64,
+    /// Share of the cooks' time spent cooking, from opening to the last
+    /// guest leaving.
+    pub utilisation: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimReport {
+    pub seed: u64,
+    pub arrived: usize,
+    pub seated: usize,
+    /// Gave up on the waitlist.
+    pub walked_away: usize,
+    /// Bigger than any table the floor can make.
+    pub turned_away: usize,
+    pub average_wait_secs: u64,
+    pub longest_wait_secs: u64,
+    /// Share of table time spent occupied or waiting to be bussed.
+    pub table_utilisation: f64,
+    /// Share of seat time with someone in the seat.
+    pub seat_utilisation: f64,
+    pub kitchen: Vec<StationLoad>,
+    pub closed_at: Timestamp,
+    pub shift: ShiftReport,
+}
+
+impl fmt::Display for SimReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "seed {}: {} parties arrived, {} seated, {} walked away, {} turned away",
+            self.seed, self.arrived, self.seated, self.walked_away, self.turned_away
+        )?;
+        writeln!(
+            f,
+            "wait: average {}m {:02}s, longest {}m {:02}s",
+            self.average_wait_secs / 60,
+            self.average_wait_secs % 60,
+            self.longest_wait_secs / 60,
+            self.longest_wait_secs % 60
+        )?;
+        writeln!(
+            f,
+            "tables {:.0}% busy, seats {:.0}% filled",
+            self.table_utilisation * 100.0,
+            self.seat_utilisation * 100.0
+        )?;
+        for load in &self.kitchen {
+            writeln!(
+                f,
+                "{}: {} tickets, {} cook(s) {:.0}% busy",
+                load.station,
+                load.tickets,
+                load.cooks,
+                load.utilisation * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What is left after a simulated night: the shift itself, with its log,
+/// and the numbers worth comparing.
+#[derive(Debug)]
+pub struct SimOutcome {
+    pub shift: Shift,
+    pub report: SimReport,
+}
+
+// Something that happens at a point in time. Steps due in the same second
+// run in the order they were scheduled.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    Arrive(u8),
+    GiveUp(PartyId),
+    Order(PartyId),
+    Bump(TicketId),
+    Pay(PartyId),
+    Bus(TableNumber),
+}
+
+#[derive(Debug, Default)]
+struct Visit {
+    tables: Vec<TableNumber>,
+    order: Option<OrderId>,
+}
+
+/// A simulated night on top of an opening shift. The shift brings the floor,
+/// station routing, stock and waitlist pace; the config brings the guests
+/// and the staff.
+pub struct Simulation {
+    shift: Shift,
+    menu: Menu,
+    config: SimConfig,
+    rng: Rng,
+    queue: BinaryHeap<Reverse<(Timestamp, u64, Step)>>,
+    next_seq: u64,
+    visits: BTreeMap<PartyId, Visit>,
+    orders: BTreeMap<OrderId, PartyId>,
+    // when each cook and busser is next free
+    cooks: BTreeMap<Station, Vec<Timestamp>>,
+    bussers: Vec<Timestamp>,
+    busy: BTreeMap<Station, (usize, u64)>,
+    occupied: BTreeMap<TableNumber, Timestamp>,
+    table_secs: u64,
+    seat_secs: u64,
+    gateway: InMemoryGateway,
+    arrived: usize,
+    walked_away: usize,
+    turned_away: usize,
+    now: Timestamp,
+}
+
+impl Simulation {
+    pub fn new(shift: Shift, menu: Menu, config: SimConfig) -> Simulation {
+        let cooks = STATIONS
+            .iter()
+            .map(|s| {
+                let count = config.cooks.get(s).copied().unwrap_or(1).max(1);
+                (*s, vec![config.opens; usize::from(count)])
+            })
+            .collect();
+        Simulation {
+            shift,
+            menu,
+            rng: Rng::new(config.seed),
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            visits: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            cooks,
+            bussers: vec![config.opens; usize::from(config.bussers.max(1))],
+            busy: BTreeMap::new(),
+            occupied: BTreeMap::new(),
+            table_secs: 0,
+            seat_secs: 0,
+            gateway: InMemoryGateway::new(),
+            arrived: 0,
+            walked_away: 0,
+            turned_away: 0,
+            now: config.opens,
+            config,
+        }
+    }
+
+    /// Plays the night through until the last table is bussed.
+    pub fn run(mut self) -> Result<SimOutcome, ShiftError> {
+        let closes = self.config.opens + self.config.open_for;
+        let sizes: Vec<u32> = self.config.party_sizes.iter().map(|(_, w)| *w).collect();
+        let mut at = self.config.opens;
+        loop {
+            at = at + self.rng.exponential(self.config.arrival_gap);
+            if at >= closes || sizes.is_empty() {
+                break;
+            }
+            let size = self.config.party_sizes[self.rng.weighted(&sizes)].0;
+            self.schedule(at, Step::Arrive(size));
+        }
+
+        while let Some(Reverse((at, _, step))) = self.queue.pop() {
+            self.now = at;
+            match step {
+                Step::Arrive(size) => self.arrive(size)?,
+                Step::GiveUp(party) => self.give_up(party)?,
+                Step::Order(party) => self.order(party)?,
+                Step::Bump(ticket) => self.bump(ticket)?,
+                Step::Pay(party) => self.pay(party)?,
+                Step::Bus(table) => self.bus(table)?,
+            }
+        }
+        let report = self.report();
+        Ok(SimOutcome {
+            shift: self.shift,
+            report,
+        })
+    }
+
+    fn schedule(&mut self, at: Timestamp, step: Step) {
+        self.next_seq += 1;
+        self.queue.push(Reverse((at, self.next_seq, step)));
+    }
+
+    fn arrive(&mut self, size: u8) -> Result<(), ShiftError> {
+        self.arrived += 1;
+        let party = Party::new(&format!("Guest {}", self.arrived), size, self.now);
+        if self.shift.floor().check_party(&party).is_err() {
+            self.turned_away += 1;
+            return Ok(());
+        }
+        let id = self.shift.add_to_waitlist(party)?;
+        let patience = self
+            .rng
+            .between(self.config.patience.0, self.config.patience.1);
+        self.schedule(self.now + patience, Step::GiveUp(id));
+        self.seat_waiting()
+    }
+
+    fn give_up(&mut self, party: PartyId) -> Result<(), ShiftError> {
+        if self.shift.waitlist().get(party).is_some() {
+            self.shift.leave_waitlist(party, self.now)?;
+            self.walked_away += 1;
+        }
+        Ok(())
+    }
+
+    // Seats everyone the floor has room for.
+    fn seat_waiting(&mut self) -> Result<(), ShiftError> {
+        loop {
+            match self.shift.seat_next(self.now) {
+                Ok(seated) => {
+                    for table in &seated.tables {
+                        self.occupied.insert(*table, self.now);
+                    }
+                    let browse = self.rng.between(self.config.browse.0, self.config.browse.1);
+                    self.schedule(self.now + browse, Step::Order(seated.id));
+                    self.visits.insert(
+                        seated.id,
+                        Visit {
+                            tables: seated.tables,
+                            order: None,
+                        },
+                    );
+                }
+                Err(ShiftError::Seat(SeatError::PartyTooLarge { party, .. })) => {
+                    self.shift.leave_waitlist(party, self.now)?;
+                    self.turned_away += 1;
+                }
+                Err(ShiftError::Seat(_)) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn order(&mut self, party: PartyId) -> Result<(), ShiftError> {
+        let size = self
+            .shift
+            .waitlist()
+            .seated()
+            .iter()
+            .find(|s| s.id == party)
+            .map_or(1, |s| s.party.size);
+        let items = self.pick_items(size);
+        let table = self.visits[&party].tables[0];
+        if items.is_empty() {
+            // nothing they wanted was left, so they have a drink and go
+            let dwell = self.dwell();
+            self.schedule(self.now + dwell, Step::Pay(party));
+            return Ok(());
+        }
+
+        let order = self.shift.take_order(table, items, self.now)?;
+        self.shift.send_to_kitchen(order, self.now)?;
+        let tickets = match self.shift.cook_order(order, self.now) {
+            Ok(tickets) => tickets,
+            Err(ShiftError::Kitchen(_)) => {
+                // the walk-in ran short; the order is voided and they leave
+                self.shift.void(order, self.now)?;
+                let dwell = self.dwell();
+                self.schedule(self.now + dwell, Step::Pay(party));
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        if let Some(visit) = self.visits.get_mut(&party) {
+            visit.order = Some(order);
+        }
+        self.orders.insert(order, party);
+
+        for ticket in tickets {
+            let station = self.shift.kitchen().ticket(ticket)?.station;
+            let (low, high) = self
+                .config
+                .cook_times
+                .get(&station)
+                .copied()
+                .unwrap_or((Duration::from_secs(600), Duration::from_secs(600)));
+            let cook_time = self.rng.between(low, high);
+            let done = self.take_slot(station, cook_time);
+            self.schedule(done, Step::Bump(ticket));
+        }
+        Ok(())
+    }
+
+    // One dish per guest from what is on right now, and maybe a drink.
+    fn pick_items(&mut self, size: u8) -> Vec<LineItem> {
+        let mut items = Vec::new();
+        for _ in 0..size {
+            let dishes: Vec<String> = self
+                .menu
+                .available_at(self.now)
+                .filter(|item| item.category != Category::Drink)
+                .map(|item| item.name.clone())
+                .collect();
+            let drinks: Vec<String> = self
+                .menu
+                .available_at(self.now)
+                .filter(|item| item.category == Category::Drink)
+                .map(|item| item.name.clone())
+                .collect();
+            if !dishes.is_empty() {
+                let dish = &dishes[self.rng.below(dishes.len() as u64) as usize];
+                if let Ok(line) = self.menu.order(dish, 1, &[], self.now) {
+                    items.push(line);
+                }
+            }
+            if !drinks.is_empty() && self.rng.chance(self.config.drinks) {
+                let drink = &drinks[self.rng.below(drinks.len() as u64) as usize];
+                if let Ok(line) = self.menu.order(drink, 1, &[], self.now) {
+                    items.push(line);
+                }
+            }
+        }
+        items
+    }
+
+    // Gives the ticket to whichever cook at the station is free first and
+    // returns when it will be done.
+    fn take_slot(&mut self, station: Station, cook_time: Duration) -> Timestamp {
+        let slots = self
+            .cooks
+            .get_mut(&station)
+            .expect("every station has at least one cook");
+        let slot = slots
+            .iter_mut()
+            .min()
+            .expect("every station has at least one cook");
+        let done = (*slot).max(self.now) + cook_time;
+        *slot = done;
+        let busy = self.busy.entry(station).or_insert((0, 0));
+        busy.0 += 1;
+        busy.1 += cook_time.as_secs();
+        done
+    }
+
+    fn dwell(&mut self) -> Duration {
+        self.rng.between(self.config.dwell.0, self.config.dwell.1)
+    }
+
+    fn bump(&mut self, ticket: TicketId) -> Result<(), ShiftError> {
+        self.shift.bump(ticket, self.now)?;
+        let order = self.shift.kitchen().ticket(ticket)?.order;
+        if self.shift.kitchen().is_order_done(order) {
+            self.shift.mark_ready(order, self.now)?;
+            self.shift.serve_order(order, self.now)?;
+            let party = self.orders[&order];
+            let dwell = self.dwell();
+            self.schedule(self.now + dwell, Step::Pay(party));
+        }
+        Ok(())
+    }
+
+    fn pay(&mut self, party: PartyId) -> Result<(), ShiftError> {
+        if let Some(order) = self.visits.get(&party).and_then(|v| v.order) {
+            self.shift.open_bill(order, self.now)?;
+            let check = &self.shift.bill(order)?.checks()[0];
+            let (check, due) = (check.id, check.balance());
+            self.shift.take_payment(
+                order,
+                check,
+                Tender::Cash(due),
+                &mut self.gateway,
+                self.now,
+            )?;
+        }
+
+        let size = self
+            .shift
+            .waitlist()
+            .seated()
+            .iter()
+            .find(|s| s.id == party)
+            .map_or(0, |s| s.party.size);
+        let tables = self.shift.vacate(party, self.now)?;
+        for table in tables {
+            if let Some(since) = self.occupied.get(&table) {
+                let secs = self.now.saturating_duration_since(*since).as_secs();
+                let seats = self.shift.floor().table(table).map_or(0, |t| t.seats);
+                // a party spread over joined tables fills them in turn
+                let sat = u64::from(size.min(seats));
+                self.seat_secs += secs * sat;
+            }
+            let slot = self
+                .bussers
+                .iter_mut()
+                .min()
+                .expect("there is always at least one busser");
+            let done = (*slot).max(self.now) + self.config.bus_time;
+            *slot = done;
+            self.schedule(done, Step::Bus(table));
+        }
+        Ok(())
+    }
+
+    fn bus(&mut self, table: TableNumber) -> Result<(), ShiftError> {
+        self.shift.bus(table, self.now)?;
+        if let Some(since) = self.occupied.remove(&table) {
+            self.table_secs += self.now.saturating_duration_since(since).as_secs();
+        }
+        self.seat_waiting()
+    }
+
+    fn report(&self) -> SimReport {
+        let closed_at = self.now;
+        let span = closed_at
+            .saturating_duration_since(self.config.opens)
+            .as_secs()
+            .max(1);
+        let tables: Vec<_> = self.shift.floor().tables().collect();
+        let seats: u64 = tables.iter().map(|t| u64::from(t.seats)).sum();
+        let waits: Vec<u64> = self
+            .shift
+            .waitlist()
+            .seated()
+            .iter()
+            .map(|s| s.waited.as_secs())
+            .collect();
+        let ratio = |used: u64, capacity: u64| {
+            if capacity == 0 {
+                0.0
+            } else {
+                used as f64 / capacity as f64
+            }
+        };
+
+        SimReport {
+            seed: self.config.seed,
+            arrived: self.arrived,
+            seated: waits.len(),
+            walked_away: self.walked_away,
+            turned_away: self.turned_away,
+            average_wait_secs: waits
+                .iter()
+                .sum::<u64>()
+                .checked_div(waits.len() as u64)
+                .unwrap_or(0),
+            longest_wait_secs: waits.iter().copied().max().unwrap_or(0),
+            table_utilisation: ratio(self.table_secs, tables.len() as u64 * span),
+            seat_utilisation: ratio(self.seat_secs, seats * span),
+            kitchen: self
+                .cooks
+                .iter()
+                .map(|(station, cooks)| {
+                    let (tickets, busy_secs) = self.busy.get(station).copied().unwrap_or((0, 0));
+                    StationLoad {
+                        station: *station,
+                        cooks: cooks.len() as u8,
+                        tickets,
+                        busy_secs,
+                        utilisation: ratio(busy_secs, cooks.len() as u64 * span),
+                    }
+                })
+                .collect(),
+            closed_at,
+            shift: ShiftReport::from_shift(&self.shift, &self.menu),
+        }
+    }
+}
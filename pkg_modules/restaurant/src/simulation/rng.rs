@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+/// SplitMix64. Not for anything secret, but tiny, fast and the same on every
+/// machine, which is all a reproducible simulation needs.
+#[derive(Debug, Clone)]
+pub(super) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(super) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(super) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, or 0 when `n` is 0.
+    pub(super) fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        // the bias from the modulo is far too small to matter here
+        self.next_u64() % n
+    }
+
+    /// Uniform in `0.0..1.0`.
+    pub(super) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(super) fn chance(&mut self, percent: u8) -> bool {
+        self.below(100) < u64::from(percent)
+    }
+
+    /// Uniform between `low` and `high`, to the second.
+    pub(super) fn between(&mut self, low: Duration, high: Duration) -> Duration {
+        let (low, high) = (low.as_secs(), high.as_secs().max(low.as_secs()));
+        Duration::from_secs(low + self.below(high - low + 1))
+    }
+
+    /// Exponentially distributed with the given mean, which is how gaps
+    /// between independent arrivals behave.
+    pub(super) fn exponential(&mut self, mean: Duration) -> Duration {
+        let u = 1.0 - self.unit();
+        Duration::from_secs_f64(-u.ln() * mean.as_secs_f64()).max(Duration::from_secs(1))
+    }
+
+    /// Picks an index with probability proportional to its weight.
+    pub(super) fn weighted(&mut self, weights: &[u32]) -> usize {
+        let total: u64 = weights.iter().map(|w| u64::from(*w)).sum();
+        let mut roll = self.below(total);
+        for (i, weight) in weights.iter().enumerate() {
+            let weight = u64::from(*weight);
+            if roll < weight {
+                return i;
+            }
+            roll -= weight;
+        }
+        0
+    }
+}
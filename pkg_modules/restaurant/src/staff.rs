@@ -0,0 +1,307 @@
+// Who is working tonight and what they are allowed to do. The roster only
+// answers questions; the checks themselves happen when someone acts on the
+// shift through `Shift::on_duty`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hosting::{FloorPlan, TableNumber};
+use crate::time::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StaffId(pub u32);
+
+impl fmt::Display for StaffId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "staff {}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Role {
+    Host,
+    Server,
+    Cook,
+    Manager,
+}
+
+impl Role {
+    pub fn permissions(self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            Role::Host => &[SeatGuests],
+            Role::Server => &[TakeOrders, TakePayment, VoidOrder],
+            Role::Cook => &[Cook],
+            Role::Manager => &[
+                SeatGuests,
+                TakeOrders,
+                TakePayment,
+                VoidOrder,
+                Cook,
+                Refund,
+                VoidPaidCheck,
+                Comp,
+                ManageStaff,
+            ],
+        }
+    }
+
+    pub fn can(self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Host => "host",
+            Role::Server => "server",
+            Role::Cook => "cook",
+            Role::Manager => "manager",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Something only some roles may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Permission {
+    /// Seat, clear and bus tables.
+    SeatGuests,
+    /// Take, send and serve orders.
+    TakeOrders,
+    /// Open bills, split them and take payment.
+    TakePayment,
+    /// Void an order that has not been paid for.
+    VoidOrder,
+    /// Fire, bump and recall tickets.
+    Cook,
+    Refund,
+    /// Void an order after it was paid, refunding everything on it.
+    VoidPaidCheck,
+    /// Take an item off the bill, or money off with a discount.
+    Comp,
+    /// Hire staff and hand out sections.
+    ManageStaff,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Permission::SeatGuests => "seat guests",
+            Permission::TakeOrders => "take orders",
+            Permission::TakePayment => "take payment",
+            Permission::VoidOrder => "void orders",
+            Permission::Cook => "work the line",
+            Permission::Refund => "refund payments",
+            Permission::VoidPaidCheck => "void paid checks",
+            Permission::Comp => "give comps and discounts",
+            Permission::ManageStaff => "manage staff",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaffMember {
+    pub id: StaffId,
+    pub name: String,
+    pub role: Role,
+    /// The floor section a server looks after. Servers without one can
+    /// work any table.
+    pub section: Option<String>,
+    /// When they clocked in, if they are working right now.
+    pub clocked_in: Option<Timestamp>,
+    /// Time on the clock in earlier stints.
+    pub worked: Duration,
+}
+
+impl StaffMember {
+    pub fn is_on_shift(&self) -> bool {
+        self.clocked_in.is_some()
+    }
+
+    /// Total time on the clock, counting the stint they are on.
+    pub fn hours(&self, now: Timestamp) -> Duration {
+        let current = self
+            .clocked_in
+            .map_or(Duration::ZERO, |since| now.saturating_duration_since(since));
+        self.worked + current
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StaffError {
+    UnknownStaff(StaffId),
+    NotClockedIn(StaffId),
+    AlreadyClockedIn(StaffId),
+    NotPermitted {
+        staff: StaffId,
+        role: Role,
+        permission: Permission,
+    },
+    /// Only servers have sections.
+    NotAServer(StaffId),
+    /// The table is in someone else's section.
+    OutOfSection {
+        staff: StaffId,
+        table: TableNumber,
+    },
+}
+
+impl fmt::Display for StaffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaffError::UnknownStaff(id) => write!(f, "{} is not on the roster", id),
+            StaffError::NotClockedIn(id) => write!(f, "{} is not clocked in", id),
+            StaffError::AlreadyClockedIn(id) => write!(f, "{} is already clocked in", id),
+            StaffError::NotPermitted {
+                staff,
+                role,
+                permission,
+            } => write!(f, "{} is a {} and cannot {}", staff, role, permission),
+            StaffError::NotAServer(id) => write!(f, "{} is not a server", id),
+            StaffError::OutOfSection { staff, table } => {
+                write!(f, "{} is not in {}'s section", table, staff)
+            }
+        }
+    }
+}
+
+impl Error for StaffError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct Roster {
+    staff: BTreeMap<StaffId, StaffMember>,
+    next_id: u32,
+}
+
+impl Roster {
+    pub fn new() -> Roster {
+        Roster::default()
+    }
+
+    pub fn add(&mut self, name: &str, role: Role) -> StaffId {
+        self.next_id += 1;
+        let id = StaffId(self.next_id);
+        self.staff.insert(
+            id,
+            StaffMember {
+                id,
+                name: String::from(name),
+                role,
+                section: None,
+                clocked_in: None,
+                worked: Duration::ZERO,
+            },
+        );
+        id
+    }
+
+    pub fn get(&self, id: StaffId) -> Result<&StaffMember, StaffError> {
+        self.staff.get(&id).ok_or(StaffError::UnknownStaff(id))
+    }
+
+    fn get_mut(&mut self, id: StaffId) -> Result<&mut StaffMember, StaffError> {
+        self.staff.get_mut(&id).ok_or(StaffError::UnknownStaff(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StaffMember> {
+        self.staff.values()
+    }
+
+    pub fn on_shift(&self) -> impl Iterator<Item = &StaffMember> {
+        self.iter().filter(|s| s.is_on_shift())
+    }
+
+    pub fn clock_in(&mut self, id: StaffId, now: Timestamp) -> Result<(), StaffError> {
+        let member = self.get_mut(id)?;
+        if member.is_on_shift() {
+            return Err(StaffError::AlreadyClockedIn(id));
+        }
+        member.clocked_in = Some(now);
+        Ok(())
+    }
+
+    /// Ends the stint and returns how long it was.
+    pub fn clock_out(&mut self, id: StaffId, now: Timestamp) -> Result<Duration, StaffError> {
+        let member = self.get_mut(id)?;
+        let since = member
+            .clocked_in
+            .take()
+            .ok_or(StaffError::NotClockedIn(id))?;
+        let stint = now.saturating_duration_since(since);
+        member.worked += stint;
+        Ok(stint)
+    }
+
+    /// Gives a server a section, or takes it away with `None`.
+    pub fn assign_section(&mut self, id: StaffId, section: Option<&str>) -> Result<(), StaffError> {
+        let member = self.get_mut(id)?;
+        if member.role != Role::Server {
+            return Err(StaffError::NotAServer(id));
+        }
+        member.section = section.map(String::from);
+        Ok(())
+    }
+
+    /// The servers on shift whose section `table` is in.
+    pub fn servers_for<'a>(
+        &'a self,
+        floor: &'a FloorPlan,
+        table: TableNumber,
+    ) -> impl Iterator<Item = &'a StaffMember> {
+        let section = floor.table(table).map(|t| t.section.as_str());
+        self.on_shift()
+            .filter(move |s| s.role == Role::Server && s.section.as_deref() == section)
+    }
+
+    /// Checks that `id` is working and may do `permission`.
+    pub fn authorize(
+        &self,
+        id: StaffId,
+        permission: Permission,
+    ) -> Result<&StaffMember, StaffError> {
+        let member = self.get(id)?;
+        if !member.is_on_shift() {
+            return Err(StaffError::NotClockedIn(id));
+        }
+        if !member.role.can(permission) {
+            return Err(StaffError::NotPermitted {
+                staff: id,
+                role: member.role,
+                permission,
+            });
+        }
+        Ok(member)
+    }
+
+    /// Like `authorize`, and a server with a section must also be acting on
+    /// a table in it.
+    pub fn authorize_table(
+        &self,
+        id: StaffId,
+        permission: Permission,
+        floor: &FloorPlan,
+        table: TableNumber,
+    ) -> Result<&StaffMember, StaffError> {
+        let member = self.authorize(id, permission)?;
+        if let (Role::Server, Some(section)) = (member.role, &member.section) {
+            // the counter is not on the floor, anyone can ring up takeout
+            let in_section = floor.table(table).is_none_or(|t| &t.section == section);
+            if !in_section {
+                return Err(StaffError::OutOfSection { staff: id, table });
+            }
+        }
+        Ok(member)
+    }
+}
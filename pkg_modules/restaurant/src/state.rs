@@ -0,0 +1,236 @@
+// What the command-line tools keep between runs: the floor, staff and
+// inventory the shift opened with, the menu as it stands and the event log.
+// The shift itself is never stored; it is rebuilt by replaying the log onto
+// how it opened, so the file cannot disagree with itself.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigError, ConfigFile, FloorFile, InventoryFile, MenuFile, Spans};
+use crate::events::{EventError, EventLog, Record};
+use crate::hosting::FloorPlan;
+use crate::inventory::Inventory;
+use crate::menu::Menu;
+use crate::shift::Shift;
+use crate::staff::{Role, Roster};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StateError {
+    Io {
+        path: PathBuf,
+        err: io::Error,
+    },
+    /// The file is not a state file at all.
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    Config(ConfigError),
+    /// The log in the file does not replay onto its own floor.
+    Events(EventError),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Io { path, err } => write!(f, "{}: {}", path.display(), err),
+            StateError::Parse { path, message } => write!(f, "{}: {}", path.display(), message),
+            StateError::Config(err) => write!(f, "{}", err),
+            StateError::Events(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for StateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StateError::Io { err, .. } => Some(err),
+            StateError::Config(err) => Some(err),
+            StateError::Events(err) => Some(err),
+            StateError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<ConfigError> for StateError {
+    fn from(err: ConfigError) -> StateError {
+        StateError::Config(err)
+    }
+}
+
+impl From<EventError> for StateError {
+    fn from(err: EventError) -> StateError {
+        StateError::Events(err)
+    }
+}
+
+// The file as it sits on disk.
+#[derive(Serialize, Deserialize)]
+struct Schema {
+    floor: FloorFile,
+    menu: MenuFile,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    staff: Vec<Hire>,
+    #[serde(default)]
+    inventory: InventoryFile,
+    #[serde(default)]
+    events: Vec<Record>,
+}
+
+// Someone on the roster before the shift opened, numbered in file order.
+#[derive(Serialize, Deserialize)]
+struct Hire {
+    name: String,
+    role: Role,
+}
+
+#[derive(Debug)]
+pub struct StateFile {
+    opening: FloorPlan,
+    staff: Roster,
+    inventory: Inventory,
+    pub menu: Menu,
+    pub shift: Shift,
+}
+
+impl StateFile {
+    /// A fresh shift on `floor`, with nothing in the log yet.
+    pub fn new(floor: FloorPlan, menu: Menu) -> StateFile {
+        StateFile {
+            shift: Shift::new(floor.clone(), Inventory::default()),
+            opening: floor,
+            staff: Roster::new(),
+            inventory: Inventory::default(),
+            menu,
+        }
+    }
+
+    /// Opens the shift with `inventory` in the walk-in. Only for a fresh
+    /// state; the log is replayed onto this stock.
+    pub fn with_inventory(mut self, inventory: Inventory) -> StateFile {
+        self.shift =
+            Shift::new(self.opening.clone(), inventory.clone()).with_roster(self.staff.clone());
+        self.inventory = inventory;
+        self
+    }
+
+    /// Opens the shift with `staff` on the roster, none of them clocked in.
+    /// Only for a fresh state; the log is replayed onto this roster.
+    pub fn with_staff(mut self, staff: Roster) -> StateFile {
+        self.shift = self.shift.with_roster(staff.clone());
+        self.staff = staff;
+        self
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<StateFile, StateError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| StateError::Io {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        let schema: Schema = serde_json::from_str(&text).map_err(|err| StateError::Parse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+
+        let mut staff = Roster::new();
+        for hire in schema.staff {
+            staff.add(&hire.name, hire.role);
+        }
+        StateFile::restore(
+            schema.floor,
+            schema.menu,
+            staff,
+            schema.inventory,
+            schema.events,
+        )
+    }
+
+    /// Rebuilds the shift from what was stored. Stored state is JSON, so
+    /// configuration errors in it have no line.
+    pub(crate) fn restore(
+        floor: FloorFile,
+        menu: MenuFile,
+        staff: Roster,
+        inventory: InventoryFile,
+        records: Vec<Record>,
+    ) -> Result<StateFile, StateError> {
+        let opening = FloorPlan::from_schema(floor, &Spans::none())?;
+        let menu = Menu::from_schema(menu, &Spans::none())?;
+        let inventory = Inventory::from_schema(inventory, &Spans::none())?;
+        let shift = replay(&opening, &staff, &inventory, records)?;
+        Ok(StateFile {
+            opening,
+            staff,
+            inventory,
+            menu,
+            shift,
+        })
+    }
+
+    /// Puts the shift back to where it was after the first `len` records of
+    /// its log, with `menu` as the menu was then.
+    #[cfg(feature = "server")]
+    pub(crate) fn rewind(&mut self, len: usize, menu: Menu) -> Result<(), StateError> {
+        let records = self.shift.log().records()[..len].to_vec();
+        self.shift = replay(&self.opening, &self.staff, &self.inventory, records)?;
+        self.menu = menu;
+        Ok(())
+    }
+
+    /// The floor as it was when the shift opened, which the log replays onto.
+    pub fn opening(&self) -> &FloorPlan {
+        &self.opening
+    }
+
+    /// Writes the whole state to `path`. The file is replaced in one go, so
+    /// a crash leaves either the old state or the new one.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StateError> {
+        let path = path.as_ref();
+        let schema = Schema {
+            floor: self.opening.to_schema(),
+            menu: self.menu.to_schema(),
+            staff: self
+                .staff
+                .iter()
+                .map(|member| Hire {
+                    name: member.name.clone(),
+                    role: member.role,
+                })
+                .collect(),
+            inventory: self.inventory.to_schema(),
+            events: self.shift.log().records().to_vec(),
+        };
+        let mut text = serde_json::to_string_pretty(&schema).map_err(|err| StateError::Parse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        text.push('\n');
+
+        let temp = path.with_extension("json.tmp");
+        let io_err = |err| StateError::Io {
+            path: path.to_path_buf(),
+            err,
+        };
+        fs::write(&temp, text).map_err(io_err)?;
+        fs::rename(&temp, path).map_err(io_err)
+    }
+}
+
+fn replay(
+    opening: &FloorPlan,
+    staff: &Roster,
+    inventory: &Inventory,
+    records: Vec<Record>,
+) -> Result<Shift, StateError> {
+    let log = EventLog::from_records(records)?;
+    Ok(Shift::new(opening.clone(), inventory.clone())
+        .with_roster(staff.clone())
+        .replay(log)?)
+}
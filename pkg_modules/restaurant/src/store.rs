@@ -0,0 +1,285 @@
+// Keeping a shift somewhere that outlives the process. A store holds the
+// floor and inventory the shift opened with, the menu as it stands (stock
+// counts change as items are ordered) and the event log. The shift itself
+// is rebuilt by replaying the log. Unlike a state file it has no opening
+// roster: a store-backed shift opens with nobody on it, so everyone has to
+// be hired through the shift, which logs the hire. Staff put on the roster
+// any other way are gone the next time the shift is rebuilt.
+//
+// Changes go through `Persistent::transaction`. Whatever the closure does to
+// the shift and the menu is committed to the store in one go when it
+// returns Ok; when it returns Err, nothing is written and the shift is
+// rebuilt from what was last committed, so an order taken and the stock it
+// used up either both stick or both go. If it cannot be rebuilt, the
+// half-done shift is thrown away rather than kept around, and the store has
+// to be opened again.
+
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::config::{ConfigFile, FloorFile, InventoryFile, MenuFile};
+use crate::events::Record;
+use crate::hosting::FloorPlan;
+use crate::inventory::Inventory;
+use crate::menu::Menu;
+use crate::staff::Roster;
+use crate::state::{StateError, StateFile};
+
+pub use self::memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteStore;
+
+/// Everything a store holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub floor: FloorFile,
+    pub menu: MenuFile,
+    pub inventory: InventoryFile,
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StoreError {
+    /// No shift has been created in the store yet.
+    Empty,
+    AlreadyCreated,
+    /// The records being committed do not follow on from the stored log,
+    /// e.g. because something else wrote to the store in between.
+    OutOfSequence {
+        expected: u64,
+        found: u64,
+    },
+    /// The store was written by a newer version than this one.
+    NewerSchema {
+        version: u32,
+        supported: u32,
+    },
+    Json(serde_json::Error),
+    /// What is stored does not replay.
+    State(StateError),
+    /// A transaction failed and what was committed before it could not be
+    /// loaded back; open the store again.
+    Poisoned,
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Empty => write!(f, "no shift has been stored yet"),
+            StoreError::AlreadyCreated => write!(f, "a shift is already stored"),
+            StoreError::OutOfSequence { expected, found } => write!(
+                f,
+                "expected event {} next but was given event {}",
+                expected, found
+            ),
+            StoreError::NewerSchema { version, supported } => write!(
+                f,
+                "the store is at schema version {}, but only {} is supported",
+                version, supported
+            ),
+            StoreError::Json(err) => write!(f, "{}", err),
+            StoreError::State(err) => write!(f, "{}", err),
+            StoreError::Poisoned => write!(
+                f,
+                "a failed change could not be rolled back; open the store again"
+            ),
+            #[cfg(feature = "sqlite")]
+            StoreError::Sqlite(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Json(err) => Some(err),
+            StoreError::State(err) => Some(err),
+            #[cfg(feature = "sqlite")]
+            StoreError::Sqlite(err) => Some(err),
+            StoreError::Empty
+            | StoreError::AlreadyCreated
+            | StoreError::OutOfSequence { .. }
+            | StoreError::NewerSchema { .. }
+            | StoreError::Poisoned => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(err: serde_json::Error) -> StoreError {
+        StoreError::Json(err)
+    }
+}
+
+impl From<StateError> for StoreError {
+    fn from(err: StateError) -> StoreError {
+        StoreError::State(err)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> StoreError {
+        StoreError::Sqlite(err)
+    }
+}
+
+/// Somewhere to keep a shift. Implementations only move data in and out;
+/// `Persistent` does the replaying and decides what to commit.
+pub trait RestaurantStore {
+    /// Stores a new shift with an empty log. Fails if one is there already.
+    fn create(
+        &mut self,
+        floor: &FloorFile,
+        menu: &MenuFile,
+        inventory: &InventoryFile,
+    ) -> Result<(), StoreError>;
+
+    fn load(&mut self) -> Result<Snapshot, StoreError>;
+
+    /// Appends `records` to the log and replaces the menu, all or nothing.
+    /// The first record must follow on from the last one stored.
+    fn commit(&mut self, records: &[Record], menu: &MenuFile) -> Result<(), StoreError>;
+}
+
+// Checks that `records` carry on from the last stored seq, for stores to
+// call before they write anything.
+fn check_sequence(last: Option<u64>, records: &[Record]) -> Result<(), StoreError> {
+    let next = last.map_or(1, |last| last + 1);
+    for (expected, record) in (next..).zip(records) {
+        if record.seq != expected {
+            return Err(StoreError::OutOfSequence {
+                expected,
+                found: record.seq,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Why a transaction did not go through.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TransactionError<E> {
+    /// The closure failed; nothing was committed.
+    Aborted(E),
+    /// The closure succeeded but the store could not take the result, so
+    /// it was rolled back all the same.
+    Store(StoreError),
+}
+
+impl<E: fmt::Display> fmt::Display for TransactionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::Aborted(err) => write!(f, "{}", err),
+            TransactionError::Store(err) => write!(f, "could not save: {}", err),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TransactionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TransactionError::Aborted(err) => Some(err),
+            TransactionError::Store(err) => Some(err),
+        }
+    }
+}
+
+/// A shift kept in a store.
+#[derive(Debug)]
+pub struct Persistent<S> {
+    store: S,
+    // `None` once a rollback has failed.
+    state: Option<StateFile>,
+}
+
+impl<S: RestaurantStore> Persistent<S> {
+    /// Opens a new shift on `floor` with `inventory` in the walk-in, in an
+    /// empty store. Nobody is on the roster yet.
+    pub fn create(
+        mut store: S,
+        floor: FloorPlan,
+        menu: Menu,
+        inventory: Inventory,
+    ) -> Result<Persistent<S>, StoreError> {
+        store.create(
+            &floor.to_schema(),
+            &menu.to_schema(),
+            &inventory.to_schema(),
+        )?;
+        Ok(Persistent {
+            store,
+            state: Some(StateFile::new(floor, menu).with_inventory(inventory)),
+        })
+    }
+
+    /// Picks up the shift stored in `store`.
+    pub fn open(mut store: S) -> Result<Persistent<S>, StoreError> {
+        let state = restore(&mut store)?;
+        Ok(Persistent {
+            store,
+            state: Some(state),
+        })
+    }
+
+    /// The shift and menu as last committed, unless a failed transaction
+    /// could not be rolled back.
+    pub fn state(&self) -> Result<&StateFile, StoreError> {
+        self.state.as_ref().ok_or(StoreError::Poisoned)
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Runs `f` against the shift and the menu and commits what it did.
+    /// When it fails, the error is the closure's or the commit's, even if
+    /// rolling back failed too; after that the shift is poisoned.
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut StateFile) -> Result<T, E>,
+    ) -> Result<T, TransactionError<E>> {
+        let state = self
+            .state
+            .as_mut()
+            .ok_or(TransactionError::Store(StoreError::Poisoned))?;
+        let before = state.shift.log().len();
+        let result = f(state);
+        let committed = match &result {
+            Ok(_) => {
+                let records = &state.shift.log().records()[before..];
+                self.store.commit(records, &state.menu.to_schema())
+            }
+            Err(_) => Ok(()),
+        };
+
+        if result.is_err() || committed.is_err() {
+            self.state = restore(&mut self.store).ok();
+        }
+        committed.map_err(TransactionError::Store)?;
+        result.map_err(TransactionError::Aborted)
+    }
+}
+
+fn restore(store: &mut impl RestaurantStore) -> Result<StateFile, StoreError> {
+    let snapshot = store.load()?;
+    Ok(StateFile::restore(
+        snapshot.floor,
+        snapshot.menu,
+        Roster::new(),
+        snapshot.inventory,
+        snapshot.records,
+    )?)
+}
@@ -0,0 +1,48 @@
+use super::{check_sequence, RestaurantStore, Snapshot, StoreError};
+use crate::config::{FloorFile, InventoryFile, MenuFile};
+use crate::events::Record;
+
+/// A store that lives as long as the process does, for tests and for
+/// trying things out.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    stored: Option<Snapshot>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl RestaurantStore for MemoryStore {
+    fn create(
+        &mut self,
+        floor: &FloorFile,
+        menu: &MenuFile,
+        inventory: &InventoryFile,
+    ) -> Result<(), StoreError> {
+        if self.stored.is_some() {
+            return Err(StoreError::AlreadyCreated);
+        }
+        self.stored = Some(Snapshot {
+            floor: floor.clone(),
+            menu: menu.clone(),
+            inventory: inventory.clone(),
+            records: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Snapshot, StoreError> {
+        self.stored.clone().ok_or(StoreError::Empty)
+    }
+
+    fn commit(&mut self, records: &[Record], menu: &MenuFile) -> Result<(), StoreError> {
+        let stored = self.stored.as_mut().ok_or(StoreError::Empty)?;
+        check_sequence(stored.records.last().map(|record| record.seq), records)?;
+        stored.records.extend_from_slice(records);
+        stored.menu = menu.clone();
+        Ok(())
+    }
+}
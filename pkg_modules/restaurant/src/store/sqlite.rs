@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{check_sequence, RestaurantStore, Snapshot, StoreError};
+use crate::config::{FloorFile, InventoryFile, MenuFile};
+use crate::events::Record;
+use crate::time::Timestamp;
+
+// Applied in order, each in its own transaction; `PRAGMA user_version`
+// records how many have run. Never edit one that has shipped, add another.
+const MIGRATIONS: &[&str] = &[
+    // 1: the shift and its log. Records are kept whole as JSON, with seq,
+    // time and kind pulled out so the log can be queried without parsing.
+    "CREATE TABLE shift (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        floor TEXT NOT NULL,
+        menu TEXT NOT NULL
+    );
+    CREATE TABLE events (
+        seq INTEGER PRIMARY KEY,
+        at INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        record TEXT NOT NULL
+    );",
+    // 2: reports and the event stream look events up by kind.
+    "CREATE INDEX events_by_kind ON events (kind);",
+    // 3: the walk-in the shift opened with. Shifts stored before had none.
+    "ALTER TABLE shift ADD COLUMN inventory TEXT NOT NULL DEFAULT '{}';",
+];
+
+/// A store in a single SQLite file.
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Opens `path`, creating it if needed, and brings its schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<SqliteStore, StoreError> {
+        SqliteStore::migrated(Connection::open(path)?)
+    }
+
+    /// A database that goes away with the store, mostly for tests.
+    pub fn in_memory() -> Result<SqliteStore, StoreError> {
+        SqliteStore::migrated(Connection::open_in_memory()?)
+    }
+
+    fn migrated(mut conn: Connection) -> Result<SqliteStore, StoreError> {
+        let version = schema_version(&conn)?;
+        let supported = MIGRATIONS.len() as u32;
+        if version > supported {
+            return Err(StoreError::NewerSchema { version, supported });
+        }
+        for (done, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", done as u32 + 1)?;
+            tx.commit()?;
+        }
+        Ok(SqliteStore { conn })
+    }
+
+    /// How many migrations the database has had.
+    pub fn schema_version(&self) -> Result<u32, StoreError> {
+        Ok(schema_version(&self.conn)?)
+    }
+}
+
+fn schema_version(conn: &Connection) -> Result<u32, rusqlite::Error> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+impl RestaurantStore for SqliteStore {
+    fn create(
+        &mut self,
+        floor: &FloorFile,
+        menu: &MenuFile,
+        inventory: &InventoryFile,
+    ) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        let exists: bool =
+            tx.query_row("SELECT EXISTS (SELECT 1 FROM shift)", [], |row| row.get(0))?;
+        if exists {
+            return Err(StoreError::AlreadyCreated);
+        }
+        tx.execute(
+            "INSERT INTO shift (id, floor, menu, inventory) VALUES (1, ?1, ?2, ?3)",
+            params![
+                serde_json::to_string(floor)?,
+                serde_json::to_string(menu)?,
+                serde_json::to_string(inventory)?
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Snapshot, StoreError> {
+        let tx = self.conn.transaction()?;
+        let (floor, menu, inventory): (String, String, String) = tx
+            .query_row(
+                "SELECT floor, menu, inventory FROM shift WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or(StoreError::Empty)?;
+
+        let mut records = Vec::new();
+        {
+            let mut statement = tx.prepare("SELECT record FROM events ORDER BY seq")?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                let record: String = row.get(0)?;
+                records.push(serde_json::from_str(&record)?);
+            }
+        }
+        tx.commit()?;
+
+        Ok(Snapshot {
+            floor: serde_json::from_str(&floor)?,
+            menu: serde_json::from_str(&menu)?,
+            inventory: serde_json::from_str(&inventory)?,
+            records,
+        })
+    }
+
+    fn commit(&mut self, records: &[Record], menu: &MenuFile) -> Result<(), StoreError> {
+        // Dropping the transaction on any early return rolls it back.
+        let tx = self.conn.transaction()?;
+        let last: Option<u64> =
+            tx.query_row("SELECT MAX(seq) FROM events", [], |row| row.get(0))?;
+        check_sequence(last, records)?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO events (seq, at, kind, record) VALUES (?1, ?2, ?3, ?4)")?;
+            for record in records {
+                insert.execute(params![
+                    record.seq,
+                    seconds(record.at),
+                    record.event.kind(),
+                    serde_json::to_string(record)?
+                ])?;
+            }
+        }
+        let updated = tx.execute(
+            "UPDATE shift SET menu = ?1 WHERE id = 1",
+            params![serde_json::to_string(menu)?],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::Empty);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+// SQLite integers are signed.
+fn seconds(at: Timestamp) -> i64 {
+    i64::try_from(at.as_secs()).unwrap_or(i64::MAX)
+}
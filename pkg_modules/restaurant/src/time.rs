@@ -0,0 +1,292 @@
+// Every operation in the restaurant happens at some point in time, so the
+// crate passes explicit timestamps around instead of reading the system
+// clock deep inside the logic. That keeps the behaviour reproducible.
+
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A point in time, in whole seconds since the Unix epoch (UTC).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub const fn from_secs(secs: u64) -> Timestamp {
+        Timestamp(secs)
+    }
+
+    /// Reads the system clock.
+    pub fn now() -> Timestamp {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Timestamp(secs)
+    }
+
+    pub const fn as_secs(self) -> u64 {
+        self.0
+    }
+
+    /// The wall-clock time of day, in UTC.
+    pub fn time_of_day(self) -> TimeOfDay {
+        let secs_today = self.0 % SECS_PER_DAY;
+        TimeOfDay {
+            hour: (secs_today / 3600) as u8,
+            minute: (secs_today % 3600 / 60) as u8,
+        }
+    }
+
+    /// The calendar date, in UTC.
+    pub fn date(self) -> Date {
+        Date::from_days_since_epoch((self.0 / SECS_PER_DAY) as i64)
+    }
+
+    /// How long ago `earlier` was, or zero if it is actually later.
+    pub fn saturating_duration_since(self, earlier: Timestamp) -> Duration {
+        Duration::from_secs(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Duration) -> Timestamp {
+        Timestamp(self.0 + rhs.as_secs())
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Where "now" comes from, for the few places that run on their own
+/// instead of being handed a timestamp, like the server and the kitchen
+/// scheduler.
+pub trait Clock {
+    fn now(&self) -> Timestamp;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+/// A clock that only moves when told to, so tests and simulations see
+/// exactly the times they expect. It can be shared between threads.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    secs: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(start: Timestamp) -> ManualClock {
+        ManualClock {
+            secs: AtomicU64::new(start.0),
+        }
+    }
+
+    pub fn set(&self, at: Timestamp) {
+        self.secs.store(at.0, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.secs.fetch_add(by.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        Timestamp(self.secs.load(Ordering::SeqCst))
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for std::sync::Arc<C> {
+    fn now(&self) -> Timestamp {
+        (**self).now()
+    }
+}
+
+/// A time on the clock, to the minute, e.g. when breakfast stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeOfDay {
+    pub const MIDNIGHT: TimeOfDay = TimeOfDay { hour: 0, minute: 0 };
+
+    /// Panics if `hour` or `minute` are not on a 24-hour clock.
+    pub const fn new(hour: u8, minute: u8) -> TimeOfDay {
+        assert!(hour < 24 && minute < 60, "not a time of day");
+        TimeOfDay { hour, minute }
+    }
+
+    pub const fn hour(self) -> u8 {
+        self.hour
+    }
+
+    pub const fn minute(self) -> u8 {
+        self.minute
+    }
+
+    pub const fn minutes_since_midnight(self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+}
+
+/// Reads "HH:MM" on a 24-hour clock.
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TimeOfDay, String> {
+        let invalid = || format!("{:?} is not a time of day (expected HH:MM)", s);
+        let (hour, minute) = s.split_once(':').ok_or_else(invalid)?;
+        let hour: u8 = hour.trim().parse().map_err(|_| invalid())?;
+        let minute: u8 = minute.trim().parse().map_err(|_| invalid())?;
+        if hour >= 24 || minute >= 60 {
+            return Err(invalid());
+        }
+        Ok(TimeOfDay { hour, minute })
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+/// A stretch of the day, from `from` up to but not including `until`.
+/// Windows may run past midnight, e.g. 22:00 to 02:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub from: TimeOfDay,
+    pub until: TimeOfDay,
+}
+
+impl Window {
+    pub const fn new(from: TimeOfDay, until: TimeOfDay) -> Window {
+        Window { from, until }
+    }
+
+    pub fn contains(&self, time: TimeOfDay) -> bool {
+        if self.from <= self.until {
+            self.from <= time && time < self.until
+        } else {
+            time >= self.from || time < self.until
+        }
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.from, self.until)
+    }
+}
+
+/// A day on the calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Panics if the day does not exist, e.g. February 30th.
+    pub fn new(year: i32, month: u8, day: u8) -> Date {
+        assert!(
+            (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month),
+            "not a date"
+        );
+        Date { year, month, day }
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    pub fn month(self) -> u8 {
+        self.month
+    }
+
+    pub fn day(self) -> u8 {
+        self.day
+    }
+
+    /// The moment `time` strikes on this date, in UTC.
+    pub fn at(self, time: TimeOfDay) -> Timestamp {
+        let days = self.days_since_epoch();
+        assert!(days >= 0, "dates before 1970 have no timestamp");
+        Timestamp(days as u64 * SECS_PER_DAY + time.minutes_since_midnight() as u64 * 60)
+    }
+
+    // Howard Hinnant's days-from-civil algorithm.
+    fn days_since_epoch(self) -> i64 {
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    // ...and its inverse, civil-from-days.
+    fn from_days_since_epoch(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        } as u8;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Date { year, month, day }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
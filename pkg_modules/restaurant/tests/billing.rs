@@ -0,0 +1,198 @@
+use restaurant::billing::{
+    Bill, Cents, CheckId, InMemoryGateway, PaymentError, PricingRules, Rate, Share, Surcharge,
+    Tender,
+};
+use restaurant::hosting::TableNumber;
+use restaurant::menu::{Category, Menu};
+use restaurant::serving::{LineItem, Order, OrderId};
+use restaurant::time::Timestamp;
+
+// Appetizers taxed at 10%, drinks at nothing, and 18% gratuity on parties
+// of six or more.
+fn bill(items: Vec<LineItem>, covers: u8) -> Bill {
+    let mut order = Order::new(OrderId(1), TableNumber(1), Timestamp::from_secs(0));
+    for item in items {
+        order.add_item(item).unwrap();
+    }
+    bill_for(&order, covers)
+}
+
+fn bill_for(order: &Order, covers: u8) -> Bill {
+    let rules = PricingRules::new(&Menu::standard())
+        .tax(Category::Appetizer, Rate::percent(10))
+        .tax(Category::Drink, Rate::ZERO)
+        .auto_gratuity(6, Rate::percent(18));
+    Bill::priced(order, &rules.price(order, covers, &[]))
+}
+
+// Every check adds up its own lines, and the checks add up to the bill.
+fn assert_reconciles(bill: &Bill) {
+    for check in bill.checks() {
+        let lines: Cents = check.lines.iter().map(|line| line.amount).sum();
+        assert_eq!(check.subtotal, lines, "{}", check.label);
+    }
+    let checks: Cents = bill.checks().iter().map(|check| check.subtotal).sum();
+    assert_eq!(checks, bill.subtotal());
+}
+
+fn subtotals(bill: &Bill) -> Vec<Cents> {
+    bill.checks().iter().map(|check| check.subtotal).collect()
+}
+
+#[test]
+fn a_three_way_share_of_an_odd_amount_loses_no_cents() {
+    let mut bill = bill(
+        vec![
+            LineItem::new("Soup", 1_001).shared_by(&[1, 2, 3]),
+            LineItem::new("Salad", 701).for_seat(2),
+        ],
+        6,
+    );
+    assert!(bill.lines.iter().any(|l| l.surcharge.is_some()));
+
+    bill.split_by_seat().unwrap();
+    assert_eq!(bill.checks().len(), 3);
+    assert_reconciles(&bill);
+    let shares: Vec<Cents> = bill
+        .checks()
+        .iter()
+        .map(|check| check.lines[0].amount)
+        .collect();
+    assert_eq!(shares.iter().sum::<Cents>(), 1_001);
+    assert!(shares.iter().all(|&share| share == 333 || share == 334));
+}
+
+#[test]
+fn a_two_to_one_share_of_an_odd_amount_loses_no_cents() {
+    let mut order = Order::new(OrderId(1), TableNumber(1), Timestamp::from_secs(0));
+    order.add_item(LineItem::new("Soup", 1_001)).unwrap();
+    order
+        .share_item(0, vec![Share::new(1, 2), Share::new(2, 1)])
+        .unwrap();
+    let mut bill = bill_for(&order, 6);
+
+    bill.split_by_seat().unwrap();
+    assert_reconciles(&bill);
+    let items: Vec<Cents> = bill
+        .checks()
+        .iter()
+        .map(|check| check.lines[0].amount)
+        .collect();
+    assert_eq!(items, vec![667, 334]);
+}
+
+#[test]
+fn a_seat_only_pays_tax_on_what_it_had() {
+    let mut bill = bill(
+        vec![
+            LineItem::new("Soup", 650).for_seat(1),
+            LineItem::new("Coffee", 300).for_seat(2),
+        ],
+        6,
+    );
+
+    bill.split_by_seat().unwrap();
+    assert_reconciles(&bill);
+    let coffee = &bill.checks()[1];
+    assert_eq!(coffee.label, "seat 2");
+    assert!(!coffee
+        .lines
+        .iter()
+        .any(|line| matches!(line.surcharge, Some(Surcharge::Tax(_)))));
+    let soup = &bill.checks()[0];
+    let tax: Cents = soup
+        .lines
+        .iter()
+        .filter(|line| matches!(line.surcharge, Some(Surcharge::Tax(_))))
+        .map(|line| line.amount)
+        .sum();
+    assert_eq!(tax, 65);
+
+    // gratuity still falls on both, by what each had
+    let gratuity = |check: &restaurant::billing::Check| -> Cents {
+        check
+            .lines
+            .iter()
+            .filter(|line| line.surcharge == Some(Surcharge::Gratuity))
+            .map(|line| line.amount)
+            .sum()
+    };
+    assert_eq!(gratuity(soup) + gratuity(coffee), 171);
+    assert_eq!(gratuity(coffee), 54);
+}
+
+#[test]
+fn moving_lines_and_merging_checks_keeps_the_total() {
+    let mut bill = bill(
+        vec![
+            LineItem::new("Soup", 655).for_seat(1),
+            LineItem::new("Salad", 703).for_seat(2),
+            LineItem::new("Coffee", 301).for_seat(3),
+        ],
+        6,
+    );
+    let total = bill.subtotal();
+
+    bill.split_by_seat().unwrap();
+    assert_eq!(bill.checks().len(), 3);
+    bill.transfer(CheckId(1), 0, CheckId(2)).unwrap();
+    assert_reconciles(&bill);
+    bill.merge(CheckId(3), CheckId(1)).unwrap();
+    assert_reconciles(&bill);
+    assert_eq!(subtotals(&bill).len(), 2);
+    assert_eq!(subtotals(&bill).iter().sum::<Cents>(), total);
+}
+
+#[test]
+fn an_even_split_keeps_the_lines_and_tips() {
+    let mut bill = bill(
+        vec![
+            LineItem::new("Soup", 1_001),
+            LineItem::new("Salad", 701),
+            LineItem::new("Coffee", 301),
+        ],
+        2,
+    );
+    bill.add_tip(CheckId(1), 500).unwrap();
+
+    bill.split_evenly(3).unwrap();
+    assert_reconciles(&bill);
+    let subtotal = bill.subtotal();
+    let mut expected = vec![subtotal / 3; 3];
+    for share in expected.iter_mut().take((subtotal % 3) as usize) {
+        *share += 1;
+    }
+    assert_eq!(subtotals(&bill), expected);
+    for check in bill.checks() {
+        assert_eq!(check.lines.len(), bill.lines.len());
+        assert!(check.lines[0].description.ends_with("(1/3)"));
+    }
+    let tips: Vec<Cents> = bill.checks().iter().map(|check| check.tip).collect();
+    assert_eq!(tips, vec![167, 167, 166]);
+}
+
+#[test]
+fn a_refunded_check_stays_settled() {
+    let mut bill = bill(vec![LineItem::new("Soup", 1_000)], 2);
+    let check = CheckId(1);
+    let total = bill.check(check).unwrap().total();
+    let mut gateway = InMemoryGateway::new();
+    let card = |amount| Tender::Card {
+        amount,
+        last4: "4242".to_string(),
+    };
+    let receipt = bill.pay(check, card(total), &mut gateway).unwrap();
+
+    bill.refund(receipt.payment, 400, &mut gateway).unwrap();
+    let paid = bill.check(check).unwrap();
+    assert_eq!(paid.paid(), total);
+    assert_eq!(paid.refunded(), 400);
+    assert!(paid.is_settled());
+    assert!(bill.is_settled());
+    assert_eq!(bill.tenders_by_kind()["card"], total - 400);
+
+    assert_eq!(
+        bill.pay(check, card(400), &mut gateway),
+        Err(PaymentError::AlreadySettled(check))
+    );
+}
@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use restaurant::config;
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber, TableState};
+use restaurant::serving::{OrderId, OrderStatus};
+use restaurant::state::StateFile;
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("restaurant-cli-{}-{}", std::process::id(), name))
+}
+
+// One run of the binary against `state`, at 19:00 on the first day.
+fn restaurant(state: &Path, words: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_restaurant"))
+        .arg("--state")
+        .arg(state)
+        .args(["--at", "68400"])
+        .args(words)
+        .output()
+        .unwrap()
+}
+
+// Runs a command that has to succeed and returns what it printed.
+fn ok(state: &Path, words: &[&str]) -> String {
+    let output = restaurant(state, words);
+    assert!(
+        output.status.success(),
+        "{:?}: {}",
+        words,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn a_service_scripted_one_command_at_a_time_is_all_in_the_state_file() {
+    let floor_file = temp_file("floor.toml");
+    let state = temp_file("state.json");
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    config::save(&floor, &floor_file).unwrap();
+
+    ok(&state, &["init", "--floor", floor_file.to_str().unwrap()]);
+    let again = restaurant(&state, &["init", "--floor", floor_file.to_str().unwrap()]);
+    assert!(!again.status.success());
+    fs::remove_file(&floor_file).unwrap();
+
+    assert_eq!(ok(&state, &["waitlist", "add", "Ferris", "2"]), "1");
+    assert_eq!(ok(&state, &["waitlist", "seat"]), "#1 (Ferris) at T1");
+    let order = ok(&state, &["order", "new", "1"]);
+    assert_eq!(
+        ok(&state, &["order", "add-item", &order, "Soup", "--qty", "2"]),
+        "0"
+    );
+    let ticket = ok(&state, &["order", "send", &order]);
+    assert_eq!(
+        ok(&state, &["kitchen", "bump", &ticket]),
+        format!("order {} is ready", order)
+    );
+    ok(&state, &["order", "serve", &order]);
+    let saved = fs::read_to_string(&state).unwrap();
+
+    // a command that fails leaves the file as it was, bill and all
+    let refused = restaurant(&state, &["bill", "pay", &order, "9", "cash", "13.00"]);
+    assert!(!refused.status.success());
+    assert_eq!(fs::read_to_string(&state).unwrap(), saved);
+    assert_eq!(
+        ok(&state, &["bill", "pay", &order, "1", "cash", "13.00"]),
+        "balance $0.00"
+    );
+
+    let loaded = StateFile::load(&state).unwrap();
+    fs::remove_file(&state).unwrap();
+    let order = OrderId(order.parse().unwrap());
+    assert_eq!(
+        loaded.shift.orders().get(order).unwrap().status(),
+        OrderStatus::Paid
+    );
+    assert!(loaded.shift.waitlist().is_empty());
+    assert!(matches!(
+        loaded.shift.floor().table(TableNumber(1)).unwrap().state,
+        TableState::Occupied(_)
+    ));
+}
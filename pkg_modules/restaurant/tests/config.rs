@@ -0,0 +1,142 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use restaurant::config::{self, ConfigFile, Format};
+use restaurant::hosting::{FloorPlan, SeatingArea, Table};
+use restaurant::inventory::{Ingredient, Inventory, RecipeBook, Unit};
+use restaurant::menu::{Category, Menu, MenuItem, SeasonalProduce};
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("restaurant-config-{}-{}", std::process::id(), name))
+}
+
+// Saves `value` in both formats and loads it back.
+fn round_trip<T: ConfigFile>(value: &T, name: &str)
+where
+    T::Schema: PartialEq + std::fmt::Debug,
+{
+    for extension in ["toml", "json"] {
+        let path = temp_file(&format!("{}.{}", name, extension));
+        config::save(value, &path).unwrap();
+        let loaded: Result<T, _> = config::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.unwrap().to_schema(),
+            value.to_schema(),
+            "{}",
+            extension
+        );
+    }
+}
+
+#[test]
+fn a_prep_time_too_long_to_count_is_an_error_on_its_line() {
+    let source = r#"
+[[items]]
+name = "Soup"
+category = "appetizer"
+price = 650
+prep_minutes = 9223372036854775807
+"#;
+    let err = config::parse::<Menu>(source, Format::Toml).unwrap_err();
+    assert_eq!(err.line, Some(6));
+    assert!(err.message.contains("too long"), "{}", err.message);
+}
+
+#[test]
+fn prep_times_in_seconds_survive_a_round_trip() {
+    let mut menu = Menu::new();
+    menu.add(MenuItem::new("Espresso", Category::Drink, 300).prep_time(Duration::from_secs(90)));
+    menu.add(
+        MenuItem::new("Burger", Category::Main, 1_500).prep_time(Duration::from_secs(12 * 60)),
+    );
+
+    for format in [Format::Toml, Format::Json] {
+        let text = config::to_string(&menu, format).unwrap();
+        let back: Menu = config::parse(&text, format).unwrap();
+        assert_eq!(
+            back.item("Espresso").unwrap().prep_time,
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            back.item("Burger").unwrap().prep_time,
+            Some(Duration::from_secs(12 * 60))
+        );
+    }
+}
+
+#[test]
+fn a_prep_time_is_given_one_way() {
+    let source = r#"
+[[items]]
+name = "Soup"
+category = "appetizer"
+price = 650
+prep_minutes = 4
+prep_seconds = 240
+"#;
+    let err = config::parse::<Menu>(source, Format::Toml).unwrap_err();
+    assert_eq!(err.line, Some(7));
+}
+
+#[test]
+fn every_file_survives_saving_and_loading_in_both_formats() {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor).in_section("front"))
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 2, SeatingArea::Bar).combines_with(&[1]))
+        .unwrap();
+    round_trip(&floor, "floor");
+
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    inventory.stock(Ingredient::new("lettuce", Unit::Grams, 1_000).par(2_000));
+    round_trip(&inventory, "inventory");
+
+    round_trip(&Menu::standard(), "menu");
+    round_trip(&SeasonalProduce::default(), "produce");
+}
+
+#[test]
+fn a_problem_found_after_parsing_has_a_line_in_toml_but_not_json() {
+    let toml = r#"
+[[tables]]
+number = 1
+seats = 4
+area = "indoor"
+
+[[tables]]
+number = 1
+seats = 2
+area = "bar"
+"#;
+    let err = config::parse::<FloorPlan>(toml, Format::Toml).unwrap_err();
+    assert_eq!((err.line, err.column), (Some(8), Some(10)));
+    assert_eq!(err.to_string(), "8:10: table 1 is defined twice");
+
+    let json = r#"{"tables": [
+        {"number": 1, "seats": 4, "area": "indoor"},
+        {"number": 1, "seats": 2, "area": "bar"}
+    ]}"#;
+    let err = config::parse::<FloorPlan>(json, Format::Json).unwrap_err();
+    assert_eq!(err.line, None);
+    assert_eq!(err.to_string(), "table 1 is defined twice");
+}
+
+#[test]
+fn a_syntax_error_has_a_line_in_either_format_and_names_the_file() {
+    let path = temp_file("broken.json");
+    fs::write(&path, "{\n  \"tables\": [\n    {\"number\": 1,}\n  ]\n}\n").unwrap();
+    let err = config::load::<FloorPlan>(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(err.line, Some(3));
+    assert!(err
+        .to_string()
+        .starts_with(&format!("{}:3:", path.display())));
+
+    let err = config::parse::<FloorPlan>("[[tables]]\nnumber = \n", Format::Toml).unwrap_err();
+    assert_eq!(err.line, Some(2));
+}
@@ -0,0 +1,128 @@
+use restaurant::billing::{InMemoryGateway, Tender};
+use restaurant::customer::{CustomerBook, CustomerId, LoyaltyProgram, Profile};
+use restaurant::events::{Event, EventError, EventLog};
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::Inventory;
+use restaurant::menu::Menu;
+use restaurant::serving::OrderId;
+use restaurant::shift::Shift;
+use restaurant::staff::{Role, Roster};
+use restaurant::time::Timestamp;
+
+fn profile_with_points() -> Profile {
+    serde_json::from_str(
+        r#"{
+            "name": "Ferris",
+            "contact": null,
+            "allergens": [],
+            "diet": [],
+            "favourites": [],
+            "visits": [{
+                "at": 0,
+                "order": 1,
+                "check": 1,
+                "spent": 100000,
+                "points": 1000,
+                "items": ["Soup"]
+            }],
+            "points": 1000
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn a_new_member_starts_without_points_or_visits() {
+    let mut shift = Shift::new(FloorPlan::new(), Inventory::default());
+    let customer = shift
+        .join_loyalty(profile_with_points(), Timestamp::from_secs(0))
+        .unwrap();
+
+    let joined = shift.customers().get(customer).unwrap();
+    assert_eq!(joined.points(), 0);
+    assert!(joined.visits().is_empty());
+}
+
+#[test]
+fn a_logged_join_cannot_bring_points_with_it() {
+    let mut log = EventLog::new();
+    log.append(
+        Timestamp::from_secs(0),
+        Event::CustomerJoined {
+            customer: CustomerId(1),
+            profile: profile_with_points(),
+        },
+    )
+    .unwrap();
+
+    let replayed = Shift::new(FloorPlan::new(), Inventory::default()).replay(log);
+    assert!(matches!(replayed, Err(EventError::Replay { seq: 1, .. })));
+}
+
+fn served(shift: &mut Shift, item: &str, now: Timestamp) -> OrderId {
+    let line = Menu::standard().order(item, 1, &[], now).unwrap();
+    let order = shift.take_order(TableNumber(1), vec![line], now).unwrap();
+    shift.send_to_kitchen(order, now).unwrap();
+    shift.cook_order(order, now).unwrap();
+    shift.mark_ready(order, now).unwrap();
+    shift.serve_order(order, now).unwrap();
+    order
+}
+
+#[test]
+fn a_refunded_redemption_gives_the_points_back_once() {
+    let now = Timestamp::from_secs(19 * 60 * 60);
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    let mut roster = Roster::new();
+    let manager = roster.add("Ada", Role::Manager);
+    let program = LoyaltyProgram {
+        points_per_dollar: 100,
+        point_value: 1,
+        minimum_redemption: 1,
+    };
+    let mut shift = Shift::new(floor, Inventory::default())
+        .with_customers(CustomerBook::new(program))
+        .with_roster(roster);
+    shift.clock_in(manager, now).unwrap();
+    let customer = shift.join_loyalty(Profile::new("Ferris"), now).unwrap();
+    let mut gateway = InMemoryGateway::new();
+
+    let first = served(&mut shift, "Burger", now);
+    let check = shift.open_bill(first, now).unwrap().checks()[0].clone();
+    shift
+        .take_payment(
+            first,
+            check.id,
+            Tender::Cash(check.total()),
+            &mut gateway,
+            now,
+        )
+        .unwrap();
+    shift.record_visit(customer, first, check.id, now).unwrap();
+    let earned = shift.customers().get(customer).unwrap().points();
+    assert!(earned > 0);
+
+    let second = served(&mut shift, "Coffee", now);
+    let check = shift.open_bill(second, now).unwrap().checks()[0].clone();
+    let points = Tender::Points {
+        customer,
+        amount: check.total(),
+    };
+    let receipt = shift
+        .take_payment(second, check.id, points, &mut gateway, now)
+        .unwrap();
+    let left = shift.customers().get(customer).unwrap().points();
+    assert_eq!(left, earned - check.total());
+
+    let mut manager = shift.on_duty(manager).unwrap();
+    manager
+        .refund(second, receipt.payment, check.total(), &mut gateway, now)
+        .unwrap();
+    assert!(manager
+        .refund(second, receipt.payment, 1, &mut gateway, now)
+        .is_err());
+    assert_eq!(shift.customers().get(customer).unwrap().points(), earned);
+}
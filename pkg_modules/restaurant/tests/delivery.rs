@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use restaurant::delivery::{
+    deliver_order, Address, CourierPool, DeliveryBoard, DeliveryError, DeliveryId, DeliveryStatus,
+    Fulfilment,
+};
+use restaurant::serving::OrderId;
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn minutes(n: u64) -> Duration {
+    Duration::from_secs(n * 60)
+}
+
+fn delivery() -> Fulfilment {
+    Fulfilment::Delivery(Address::new("1 Main St", "Springfield", "12345"))
+}
+
+fn open(board: &mut DeliveryBoard, order: u32, fulfilment: Fulfilment) -> DeliveryId {
+    board
+        .open(
+            OrderId(order),
+            "Ferris",
+            fulfilment,
+            six_pm() + minutes(45),
+            six_pm(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn the_courier_who_has_waited_longest_goes_next() {
+    let mut couriers = CourierPool::new();
+    let ann = couriers.add("Ann", six_pm());
+    let ben = couriers.add("Ben", six_pm() + minutes(5));
+    let mut board = DeliveryBoard::new(couriers);
+    let first = open(&mut board, 1, delivery());
+    let second = open(&mut board, 2, delivery());
+    let third = open(&mut board, 3, delivery());
+
+    let out = deliver_order(&mut board, OrderId(1), six_pm() + minutes(20)).unwrap();
+    assert_eq!(out.id, first);
+    assert_eq!(out.status, DeliveryStatus::OutForDelivery(ann));
+    assert_eq!(out.packed_at, Some(six_pm() + minutes(20)));
+    assert_eq!(
+        deliver_order(&mut board, OrderId(2), six_pm() + minutes(20))
+            .unwrap()
+            .courier,
+        Some(ben)
+    );
+
+    // nobody is left, and the bag stays where it was
+    assert_eq!(
+        deliver_order(&mut board, OrderId(3), six_pm() + minutes(25)),
+        Err(DeliveryError::NoCourierAvailable)
+    );
+    assert_eq!(board.get(third).unwrap().status, DeliveryStatus::Preparing);
+
+    board.complete(second, six_pm() + minutes(40)).unwrap();
+    board.complete(first, six_pm() + minutes(50)).unwrap();
+    let late = deliver_order(&mut board, OrderId(3), six_pm() + minutes(50)).unwrap();
+    assert_eq!(late.courier, Some(ben));
+    assert_eq!(
+        board
+            .late(six_pm() + minutes(50))
+            .iter()
+            .map(|d| d.id)
+            .collect::<Vec<_>>(),
+        vec![third]
+    );
+    assert!(board.get(first).unwrap().is_late(six_pm() + minutes(60)));
+    assert!(!board.get(second).unwrap().is_late(six_pm() + minutes(60)));
+}
+
+#[test]
+fn a_booked_courier_keeps_the_run_and_off_shift_couriers_are_skipped() {
+    let mut couriers = CourierPool::new();
+    let ann = couriers.add("Ann", six_pm());
+    let ben = couriers.add("Ben", six_pm() + minutes(5));
+    let cy = couriers.add("Cy", six_pm() + minutes(10));
+    couriers.clock_out(ann);
+    let mut board = DeliveryBoard::new(couriers);
+    let booked = open(&mut board, 1, delivery());
+    open(&mut board, 2, delivery());
+
+    board.assign_to(booked, cy).unwrap();
+    assert_eq!(
+        board.assign_to(booked, ben),
+        Err(DeliveryError::InvalidStatus {
+            delivery: booked,
+            status: DeliveryStatus::Preparing,
+            action: "assign a courier to",
+        })
+    );
+    let out = deliver_order(&mut board, OrderId(1), six_pm() + minutes(20)).unwrap();
+    assert_eq!(out.status, DeliveryStatus::OutForDelivery(cy));
+    let out = deliver_order(&mut board, OrderId(2), six_pm() + minutes(20)).unwrap();
+    assert_eq!(out.courier, Some(ben));
+    assert!(board.couriers().get(ann).unwrap().delivering.is_none());
+}
+
+#[test]
+fn takeout_is_handed_over_without_a_courier() {
+    let mut board = DeliveryBoard::new(CourierPool::new());
+    let takeout = open(&mut board, 1, Fulfilment::Takeout);
+    board.pack(takeout, six_pm() + minutes(10)).unwrap();
+
+    let collected = deliver_order(&mut board, OrderId(1), six_pm() + minutes(15)).unwrap();
+    assert_eq!(collected.status, DeliveryStatus::PickedUp);
+    assert_eq!(collected.courier, None);
+    assert_eq!(collected.packed_at, Some(six_pm() + minutes(10)));
+    assert_eq!(collected.completed_at(), Some(six_pm() + minutes(15)));
+
+    assert_eq!(
+        board.assign(takeout),
+        Err(DeliveryError::NotADelivery(takeout))
+    );
+    assert_eq!(
+        deliver_order(&mut board, OrderId(9), six_pm()),
+        Err(DeliveryError::NoDeliveryForOrder(OrderId(9)))
+    );
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use restaurant::billing::{InMemoryGateway, Tender};
+use restaurant::events::{Event, EventError, EventLog};
+use restaurant::hosting::{FloorPlan, Party, SeatingArea, Table, TableNumber};
+use restaurant::inventory::{Ingredient, Inventory, RecipeBook, Unit};
+use restaurant::menu::Menu;
+use restaurant::serving::OrderStatus;
+use restaurant::shift::Shift;
+use restaurant::time::Timestamp;
+
+fn opening() -> Shift {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 2, SeatingArea::Outdoor))
+        .unwrap();
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    inventory.stock(Ingredient::new("soup base", Unit::Millilitres, 3_000));
+    inventory.stock(Ingredient::new("cream", Unit::Millilitres, 300));
+    inventory.stock(Ingredient::new("beef patty", Unit::Pieces, 10));
+    inventory.stock(Ingredient::new("burger bun", Unit::Pieces, 10));
+    inventory.stock(Ingredient::new("potato", Unit::Grams, 5_000));
+    Shift::new(floor, inventory)
+}
+
+// A table from the door to the dish pit, paid half cash and half card.
+fn service(shift: &mut Shift) {
+    let now = Timestamp::from_secs(19 * 60 * 60);
+    let later = now + Duration::from_secs(45 * 60);
+    let mut menu = Menu::standard();
+
+    let party = shift.add_to_waitlist(Party::new("Ferris", 3, now)).unwrap();
+    shift.seat_next(now).unwrap();
+    let lines = vec![
+        menu.order("Soup", 2, &[], now).unwrap(),
+        menu.order("Burger", 1, &[], now).unwrap(),
+    ];
+    let order = shift.take_order(TableNumber(1), lines, now).unwrap();
+    shift.send_to_kitchen(order, now).unwrap();
+    for ticket in shift.cook_order(order, now).unwrap() {
+        shift.bump(ticket, now).unwrap();
+    }
+    shift.mark_ready(order, now).unwrap();
+    shift.serve_order(order, now).unwrap();
+
+    shift.open_bill(order, later).unwrap();
+    shift.split_evenly(order, 2, later).unwrap();
+    let checks: Vec<_> = shift
+        .bill(order)
+        .unwrap()
+        .checks()
+        .iter()
+        .map(|check| (check.id, check.total()))
+        .collect();
+    let mut gateway = InMemoryGateway::new();
+    shift
+        .take_payment(
+            order,
+            checks[0].0,
+            Tender::Cash(checks[0].1),
+            &mut gateway,
+            later,
+        )
+        .unwrap();
+    let card = Tender::Card {
+        amount: checks[1].1,
+        last4: "4242".to_string(),
+    };
+    shift
+        .take_payment(order, checks[1].0, card, &mut gateway, later)
+        .unwrap();
+
+    shift.vacate(party, later).unwrap();
+    shift.bus(TableNumber(1), later).unwrap();
+}
+
+#[test]
+fn a_logged_service_replays_to_the_same_shift() {
+    let mut shift = opening();
+    service(&mut shift);
+    let order = shift.orders().iter().next().unwrap().id;
+    assert_eq!(
+        shift.orders().get(order).unwrap().status(),
+        OrderStatus::Paid
+    );
+
+    let log = EventLog::from_ndjson(&shift.log().to_ndjson()).unwrap();
+    let replayed = opening().replay(log).unwrap();
+
+    assert_eq!(replayed.log().records(), shift.log().records());
+    assert_eq!(
+        replayed.orders().iter().collect::<Vec<_>>(),
+        shift.orders().iter().collect::<Vec<_>>()
+    );
+    assert_eq!(replayed.bill(order).unwrap(), shift.bill(order).unwrap());
+    assert_eq!(
+        replayed.floor().tables().collect::<Vec<_>>(),
+        shift.floor().tables().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        replayed.kitchen().tickets().count(),
+        shift.kitchen().tickets().count()
+    );
+    assert_eq!(
+        replayed
+            .inventory()
+            .ingredient("soup base")
+            .unwrap()
+            .on_hand,
+        shift.inventory().ingredient("soup base").unwrap().on_hand
+    );
+    assert!(replayed.waitlist().is_empty());
+}
+
+#[test]
+fn a_replayed_event_must_be_the_one_that_was_logged() {
+    let mut shift = opening();
+    service(&mut shift);
+    let mut records = shift.log().records().to_vec();
+    // say the party was bussed off table 2 instead
+    let last = records.last_mut().unwrap();
+    assert!(matches!(last.event, Event::TableBussed { .. }));
+    last.event = Event::TableBussed {
+        table: TableNumber(2),
+    };
+    let seq = last.seq;
+
+    let log = EventLog::from_records(records).unwrap();
+    assert!(matches!(
+        opening().replay(log),
+        Err(EventError::Replay { seq: s, .. }) if s == seq
+    ));
+}
@@ -0,0 +1,85 @@
+use restaurant::hosting::{FloorError, FloorPlan, Party, PartyId, SeatingArea, Table, TableNumber};
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+// Two two-tops on either side of a four-top inside, and a big table outside.
+fn floor() -> FloorPlan {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 2, SeatingArea::Indoor).combines_with(&[2]))
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 4, SeatingArea::Indoor))
+        .unwrap();
+    floor
+        .add_table(Table::new(3, 2, SeatingArea::Indoor).combines_with(&[2]))
+        .unwrap();
+    floor
+        .add_table(Table::new(4, 6, SeatingArea::Outdoor))
+        .unwrap();
+    floor
+}
+
+fn tables(numbers: &[u16]) -> Vec<TableNumber> {
+    numbers.iter().map(|&n| TableNumber(n)).collect()
+}
+
+#[test]
+fn joins_work_from_either_side_and_numbers_are_unique() {
+    let mut floor = floor();
+    assert_eq!(
+        floor.table(TableNumber(2)).unwrap().combines_with,
+        tables(&[1, 3])
+    );
+
+    assert_eq!(
+        floor.add_table(Table::new(2, 8, SeatingArea::Bar)),
+        Err(FloorError::DuplicateTable(TableNumber(2)))
+    );
+    assert_eq!(floor.table(TableNumber(2)).unwrap().seats, 4);
+    assert_eq!(floor.tables().count(), 4);
+}
+
+#[test]
+fn a_single_table_is_preferred_over_joining() {
+    let floor = floor();
+    let six = Party::new("Ada", 6, six_pm());
+    assert_eq!(floor.find_tables(&six), Ok(tables(&[4])));
+    assert_eq!(
+        floor.find_tables(&Party::new("Bo", 3, six_pm())),
+        Ok(tables(&[2]))
+    );
+
+    let inside = six.prefers(SeatingArea::Indoor);
+    assert_eq!(floor.find_tables(&inside), Ok(tables(&[1, 2])));
+    let eight = Party::new("Cy", 8, six_pm()).prefers(SeatingArea::Indoor);
+    assert_eq!(floor.find_tables(&eight), Ok(tables(&[1, 2, 3])));
+    assert_eq!(
+        floor.find_tables(&Party::new("Di", 9, six_pm()).prefers(SeatingArea::Indoor)),
+        Err(FloorError::PartyTooLarge { size: 9, max: 8 })
+    );
+}
+
+#[test]
+fn tables_cannot_be_joined_through_an_occupied_one() {
+    let mut floor = floor();
+    floor.seat(&tables(&[2]), PartyId(1)).unwrap();
+    let four = Party::new("Ada", 4, six_pm()).prefers(SeatingArea::Indoor);
+
+    assert_eq!(
+        floor.find_tables(&four),
+        Err(FloorError::NoTableAvailable { size: 4 })
+    );
+    // planning ahead as if the room were empty still finds the four-top
+    assert_eq!(floor.find_tables_where(&four, |_| true), Ok(tables(&[2])));
+
+    assert_eq!(floor.vacate(PartyId(1)), tables(&[2]));
+    floor.bus(TableNumber(2)).unwrap();
+    let joined = Party::new("Bo", 6, six_pm()).prefers(SeatingArea::Indoor);
+    let found = floor.find_tables(&joined).unwrap();
+    floor.seat(&found, PartyId(2)).unwrap();
+    assert_eq!(floor.tables_of(PartyId(2)), tables(&[1, 2]));
+}
@@ -0,0 +1,104 @@
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::{
+    Ingredient, Inventory, InventoryError, Recipe, RecipeBook, Shortage, Unit,
+};
+use restaurant::kitchen::KitchenError;
+use restaurant::menu::Menu;
+use restaurant::serving::{LineItem, OrderId, OrderStatus};
+use restaurant::shift::{Shift, ShiftError};
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn walk_in() -> Inventory {
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    inventory.stock(Ingredient::new("lettuce", Unit::Grams, 1_000));
+    inventory.stock(Ingredient::new("tomato", Unit::Pieces, 10));
+    inventory.stock(Ingredient::new("feta", Unit::Grams, 100));
+    inventory
+}
+
+fn shift(inventory: Inventory) -> Shift {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    Shift::new(floor, inventory)
+}
+
+fn sent(shift: &mut Shift, lines: Vec<LineItem>) -> OrderId {
+    let order = shift.take_order(TableNumber(1), lines, six_pm()).unwrap();
+    shift.send_to_kitchen(order, six_pm()).unwrap();
+    order
+}
+
+fn on_hand(shift: &Shift, ingredient: &str) -> u32 {
+    shift.inventory().ingredient(ingredient).unwrap().on_hand
+}
+
+#[test]
+fn cooking_takes_the_recipe_times_the_quantity_with_its_modifiers() {
+    let mut menu = Menu::standard();
+    let mut shift = shift(walk_in());
+    let lines = vec![
+        menu.order("Salad", 2, &["feta"], six_pm()).unwrap(),
+        menu.order("Salad", 1, &[], six_pm()).unwrap(),
+    ];
+    let order = sent(&mut shift, lines);
+
+    shift.cook_order(order, six_pm()).unwrap();
+    assert_eq!(on_hand(&shift, "lettuce"), 1_000 - 3 * 120);
+    assert_eq!(on_hand(&shift, "tomato"), 7);
+    assert_eq!(on_hand(&shift, "feta"), 20);
+}
+
+#[test]
+fn a_stock_out_keeps_the_order_from_cooking() {
+    let mut menu = Menu::standard();
+    let mut shift = shift(walk_in());
+    let lines = vec![menu.order("Salad", 3, &["feta"], six_pm()).unwrap()];
+    let order = sent(&mut shift, lines);
+
+    let err = shift.cook_order(order, six_pm()).unwrap_err();
+    assert!(matches!(
+        err,
+        ShiftError::Kitchen(KitchenError::Inventory(InventoryError::CannotFulfil(ref shortages)))
+            if shortages == &[Shortage {
+                ingredient: "feta".to_string(),
+                needed: 120,
+                on_hand: 100,
+            }]
+    ));
+    // nothing was taken and nothing went to the pass
+    assert_eq!(on_hand(&shift, "lettuce"), 1_000);
+    assert_eq!(shift.kitchen().tickets().count(), 0);
+    assert_eq!(
+        shift.orders().get(order).unwrap().status(),
+        OrderStatus::SentToKitchen
+    );
+
+    shift.receive_stock("feta", 20, six_pm()).unwrap();
+    shift.cook_order(order, six_pm()).unwrap();
+    assert_eq!(on_hand(&shift, "feta"), 0);
+}
+
+#[test]
+fn amounts_too_large_to_count_are_errors() {
+    let mut recipes = RecipeBook::standard();
+    let line = LineItem::new("Soup", 650).quantity(u16::MAX);
+    recipes.add("Soup", Recipe::new().uses("soup base", u32::MAX / 2));
+    assert_eq!(
+        recipes.needs_for_line(&line),
+        Err(InventoryError::TooMuch("soup base".to_string()))
+    );
+
+    let mut shift = shift(walk_in());
+    assert!(matches!(
+        shift.receive_stock("lettuce", u32::MAX, six_pm()),
+        Err(ShiftError::Inventory(InventoryError::TooMuch(_)))
+    ));
+    assert_eq!(on_hand(&shift, "lettuce"), 1_000);
+    assert!(shift.log().is_empty());
+}
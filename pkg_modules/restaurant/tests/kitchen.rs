@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use restaurant::billing::{InMemoryGateway, Tender};
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::Inventory;
+use restaurant::kitchen::{KitchenError, Station, StationRouter};
+use restaurant::menu::{Category, Menu, MenuItem};
+use restaurant::serving::{OrderError, OrderId, OrderStatus};
+use restaurant::shift::{Shift, ShiftError};
+use restaurant::staff::{Role, Roster};
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn shift() -> Shift {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    Shift::new(floor, Inventory::default())
+}
+
+fn cooking(shift: &mut Shift, items: &[&str]) -> OrderId {
+    let mut menu = Menu::standard();
+    let lines = items
+        .iter()
+        .map(|item| menu.order(item, 1, &[], six_pm()).unwrap())
+        .collect();
+    let order = shift.take_order(TableNumber(1), lines, six_pm()).unwrap();
+    shift.send_to_kitchen(order, six_pm()).unwrap();
+    shift.cook_order(order, six_pm()).unwrap();
+    order
+}
+
+#[test]
+fn every_standard_dish_has_a_station_for_its_category() {
+    let router = StationRouter::default();
+    let stations: Vec<(&str, Station)> = [
+        "Soup",
+        "Salad",
+        "Breakfast",
+        "Burger",
+        "Fish and Chips",
+        "Coffee",
+        "Orange Juice",
+    ]
+    .into_iter()
+    .map(|item| (item, router.station_for(item)))
+    .collect();
+    assert_eq!(
+        stations,
+        vec![
+            ("Soup", Station::Grill),
+            ("Salad", Station::Salad),
+            ("Breakfast", Station::Grill),
+            ("Burger", Station::Grill),
+            ("Fish and Chips", Station::Fry),
+            ("Coffee", Station::Dessert),
+            ("Orange Juice", Station::Dessert),
+        ]
+    );
+    assert_eq!(router.station_for("orange juice"), Station::Dessert);
+}
+
+#[test]
+fn an_item_route_beats_its_category_and_unknown_items_fall_back() {
+    let mut menu = Menu::new();
+    menu.add(MenuItem::new("Lemonade", Category::Drink, 350));
+    menu.add(MenuItem::new("Milkshake", Category::Drink, 550));
+    let router = StationRouter::new(Station::Grill)
+        .menu(&menu)
+        .route_category(Category::Drink, Station::Dessert)
+        .route("Milkshake", Station::Fry);
+
+    assert_eq!(router.station_for("Lemonade"), Station::Dessert);
+    assert_eq!(router.station_for("Milkshake"), Station::Fry);
+    assert_eq!(router.station_for("Daily Special"), Station::Grill);
+}
+
+#[test]
+fn a_ticket_is_bumped_once_and_can_be_recalled() {
+    let mut shift = shift();
+    let order = cooking(&mut shift, &["Burger", "Coffee"]);
+    let kitchen = shift.kitchen();
+    let grill = kitchen.queue(Station::Grill)[0].id;
+    let drinks = kitchen.queue(Station::Dessert)[0].id;
+    assert!(kitchen.queue(Station::Salad).is_empty());
+
+    let later = six_pm() + Duration::from_secs(60);
+    assert!(matches!(
+        shift.recall(grill, later),
+        Err(ShiftError::Kitchen(KitchenError::NotBumped(_)))
+    ));
+    shift.bump(drinks, later).unwrap();
+    assert!(matches!(
+        shift.bump(drinks, later),
+        Err(ShiftError::Kitchen(KitchenError::AlreadyBumped(_)))
+    ));
+    assert!(!shift.kitchen().is_order_done(order));
+    shift.bump(grill, later).unwrap();
+    assert!(shift.kitchen().is_order_done(order));
+
+    shift.recall(grill, later).unwrap();
+    assert_eq!(shift.kitchen().queue(Station::Grill)[0].id, grill);
+    assert!(!shift.kitchen().is_order_done(order));
+}
+
+#[test]
+fn nothing_is_refired_for_a_closed_order() {
+    let mut roster = Roster::new();
+    let server = roster.add("Sam", Role::Server);
+    let mut shift = shift().with_roster(roster);
+    shift.clock_in(server, six_pm()).unwrap();
+    let order = cooking(&mut shift, &["Burger"]);
+    let ticket = shift.kitchen().queue(Station::Grill)[0].id;
+    shift.bump(ticket, six_pm()).unwrap();
+    shift.mark_ready(order, six_pm()).unwrap();
+    shift.serve_order(order, six_pm()).unwrap();
+    shift
+        .fix_incorrect_order(ticket, "overdone", six_pm())
+        .unwrap();
+
+    let check = shift.open_bill(order, six_pm()).unwrap().checks()[0].clone();
+    shift
+        .take_payment(
+            order,
+            check.id,
+            Tender::Cash(check.total()),
+            &mut InMemoryGateway::new(),
+            six_pm(),
+        )
+        .unwrap();
+    let tickets = shift.kitchen().tickets().count();
+    assert!(matches!(
+        shift.fix_incorrect_order(ticket, "cold", six_pm()),
+        Err(ShiftError::Kitchen(KitchenError::Order(
+            OrderError::Closed {
+                status: OrderStatus::Paid,
+                ..
+            }
+        )))
+    ));
+    assert_eq!(shift.kitchen().tickets().count(), tickets);
+
+    let voided = cooking(&mut shift, &["Soup"]);
+    shift
+        .on_duty(server)
+        .unwrap()
+        .void(voided, six_pm())
+        .unwrap();
+    let ticket = shift.kitchen().tickets_for(voided).next().unwrap().id;
+    assert!(shift.fix_incorrect_order(ticket, "cold", six_pm()).is_err());
+}
@@ -0,0 +1,91 @@
+use restaurant::menu::{Allergen, Category, Menu, MenuError, MenuItem, BREAKFAST_HOURS};
+use restaurant::time::{TimeOfDay, Timestamp};
+
+fn at(hour: u64, minute: u64) -> Timestamp {
+    Timestamp::from_secs((hour * 60 + minute) * 60)
+}
+
+fn names<'a>(items: impl Iterator<Item = &'a MenuItem>) -> Vec<&'a str> {
+    items.map(|item| item.name.as_str()).collect()
+}
+
+#[test]
+fn breakfast_stops_at_eleven() {
+    let mut menu = Menu::standard();
+    assert_eq!(
+        menu.item("breakfast").unwrap().available,
+        Some(BREAKFAST_HOURS)
+    );
+    assert!(names(menu.available_at(at(10, 59))).contains(&"Breakfast"));
+    assert!(!names(menu.available_at(at(11, 0))).contains(&"Breakfast"));
+
+    let line = menu
+        .order("Breakfast", 1, &["Rye Toast"], at(8, 30))
+        .unwrap();
+    assert_eq!(line.modifiers[0].name, "rye toast");
+    assert_eq!(
+        menu.order("Breakfast", 1, &[], at(11, 0)),
+        Err(MenuError::NotServedNow {
+            item: "Breakfast".to_string(),
+            window: BREAKFAST_HOURS,
+        })
+    );
+    assert_eq!(BREAKFAST_HOURS.until, TimeOfDay::new(11, 0));
+}
+
+#[test]
+fn the_last_portion_86s_the_item_until_it_is_restored() {
+    let mut menu = Menu::standard();
+    menu.set_stock("Fish and Chips", Some(3)).unwrap();
+
+    assert_eq!(
+        menu.order("Fish and Chips", 4, &[], at(19, 0)),
+        Err(MenuError::OutOfStock {
+            item: "Fish and Chips".to_string(),
+            left: 3,
+        })
+    );
+    let line = menu.order("fish and chips", 3, &[], at(19, 0)).unwrap();
+    assert_eq!(line.total(), 3 * 1650);
+    let fish = menu.item("Fish and Chips").unwrap();
+    assert_eq!(fish.stock, Some(0));
+    assert!(fish.eighty_sixed);
+    assert!(!names(menu.available_at(at(19, 0))).contains(&"Fish and Chips"));
+    assert_eq!(
+        menu.order("Fish and Chips", 1, &[], at(19, 5)),
+        Err(MenuError::EightySixed("Fish and Chips".to_string()))
+    );
+
+    menu.restore("Fish and Chips", None).unwrap();
+    assert!(menu.order("Fish and Chips", 1, &[], at(19, 10)).is_ok());
+}
+
+#[test]
+fn an_86d_item_stays_on_the_menu_but_cannot_be_ordered() {
+    let mut menu = Menu::standard();
+    menu.eighty_six("Soup").unwrap();
+    assert!(menu.item("Soup").unwrap().eighty_sixed);
+    assert_eq!(
+        menu.order("Soup", 1, &[], at(12, 0)),
+        Err(MenuError::EightySixed("Soup".to_string()))
+    );
+    assert_eq!(
+        menu.eighty_six("Lobster"),
+        Err(MenuError::UnknownItem("Lobster".to_string()))
+    );
+    assert_eq!(
+        menu.order("Salad", 1, &["bacon"], at(12, 0)),
+        Err(MenuError::UnknownModifier {
+            item: "Salad".to_string(),
+            modifier: "bacon".to_string(),
+        })
+    );
+
+    assert_eq!(
+        names(menu.in_category(Category::Appetizer)),
+        vec!["Salad", "Soup"]
+    );
+    let lunch = names(menu.available_at(at(12, 0)));
+    assert!(!lunch.contains(&"Soup") && lunch.contains(&"Salad"));
+    assert!(!names(menu.without(&[Allergen::Gluten])).contains(&"Burger"));
+}
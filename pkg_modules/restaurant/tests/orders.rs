@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use restaurant::hosting::TableNumber;
+use restaurant::serving::{
+    serve_order, take_order, LineItem, Modifier, OrderBook, OrderError, OrderId, OrderStatus,
+};
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn burger() -> LineItem {
+    LineItem::new("Burger", 1_450)
+        .for_seat(1)
+        .with_modifier(Modifier::priced("extra cheese", 150))
+        .with_modifier(Modifier::new("no onions"))
+}
+
+#[test]
+fn an_order_moves_from_the_pad_to_the_till() {
+    let mut orders = OrderBook::new();
+    let soup = LineItem::new("Soup", 650).quantity(2).note("one hot");
+    let id = take_order(&mut orders, TableNumber(4), vec![burger(), soup], six_pm());
+
+    let order = orders.get_mut(id).unwrap();
+    assert_eq!(order.table, TableNumber(4));
+    assert_eq!(order.subtotal(), 1_600 + 2 * 650);
+    let steps = [
+        OrderStatus::SentToKitchen,
+        OrderStatus::Cooking,
+        OrderStatus::Ready,
+    ];
+    for (minutes, status) in (1..).zip(steps) {
+        order
+            .advance(status, six_pm() + Duration::from_secs(minutes * 60))
+            .unwrap();
+    }
+    serve_order(&mut orders, id, six_pm() + Duration::from_secs(20 * 60)).unwrap();
+    let order = orders.get_mut(id).unwrap();
+    order
+        .mark_paid(six_pm() + Duration::from_secs(60 * 60))
+        .unwrap();
+
+    let history: Vec<_> = order.history().iter().map(|c| c.status).collect();
+    assert_eq!(
+        history,
+        vec![
+            OrderStatus::Taken,
+            OrderStatus::SentToKitchen,
+            OrderStatus::Cooking,
+            OrderStatus::Ready,
+            OrderStatus::Served,
+            OrderStatus::Paid,
+        ]
+    );
+    assert_eq!(
+        order.reached(OrderStatus::Served),
+        Some(six_pm() + Duration::from_secs(20 * 60))
+    );
+    assert_eq!(orders.open_orders().count(), 0);
+}
+
+#[test]
+fn steps_cannot_be_skipped_or_undone() {
+    let mut orders = OrderBook::new();
+    let id = take_order(&mut orders, TableNumber(1), vec![burger()], six_pm());
+
+    assert_eq!(
+        serve_order(&mut orders, id, six_pm()),
+        Err(OrderError::IllegalTransition {
+            order: id,
+            from: OrderStatus::Taken,
+            to: OrderStatus::Served,
+        })
+    );
+    let order = orders.get_mut(id).unwrap();
+    order.send_to_kitchen(six_pm()).unwrap();
+    assert_eq!(
+        order.advance(OrderStatus::Taken, six_pm()),
+        Err(OrderError::IllegalTransition {
+            order: id,
+            from: OrderStatus::SentToKitchen,
+            to: OrderStatus::Taken,
+        })
+    );
+    assert_eq!(
+        order.add_item(LineItem::new("Salad", 700)),
+        Err(OrderError::NotEditable {
+            order: id,
+            status: OrderStatus::SentToKitchen,
+        })
+    );
+    assert_eq!(order.status(), OrderStatus::SentToKitchen);
+    assert_eq!(order.history().len(), 2);
+
+    assert_eq!(
+        serve_order(&mut orders, OrderId(99), six_pm()),
+        Err(OrderError::UnknownOrder(OrderId(99)))
+    );
+}
+
+#[test]
+fn a_voided_order_is_closed() {
+    let mut orders = OrderBook::new();
+    let empty = orders.open(TableNumber(2), six_pm());
+    assert_eq!(
+        orders.get_mut(empty).unwrap().send_to_kitchen(six_pm()),
+        Err(OrderError::EmptyOrder(empty))
+    );
+    let order = orders.get_mut(empty).unwrap();
+    assert_eq!(
+        order.remove_item(0),
+        Err(OrderError::NoSuchLine {
+            order: empty,
+            line: 0,
+        })
+    );
+
+    order.void(six_pm()).unwrap();
+    assert!(order.status().is_closed());
+    assert_eq!(
+        order.void(six_pm()),
+        Err(OrderError::IllegalTransition {
+            order: empty,
+            from: OrderStatus::Voided,
+            to: OrderStatus::Voided,
+        })
+    );
+    assert!(matches!(
+        order.comp_item(0),
+        Err(OrderError::NotEditable {
+            status: OrderStatus::Voided,
+            ..
+        })
+    ));
+    assert!(!OrderStatus::Paid.can_become(OrderStatus::Voided));
+    assert_eq!(orders.for_table(TableNumber(2)).count(), 1);
+}
@@ -0,0 +1,73 @@
+use restaurant::billing::{Discount, InMemoryGateway, PricingRules, Rate, Tender};
+use restaurant::hosting::{FloorPlan, Party, SeatingArea, Table, TableNumber};
+use restaurant::inventory::Inventory;
+use restaurant::menu::{Category, Menu};
+use restaurant::report::ShiftReport;
+use restaurant::shift::Shift;
+use restaurant::staff::{Role, Roster};
+use restaurant::time::Timestamp;
+
+#[test]
+fn revenue_is_what_the_bill_charged_with_tax_and_gratuity_apart() {
+    let now = Timestamp::from_secs(19 * 60 * 60);
+    let menu = Menu::standard();
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 6, SeatingArea::Indoor))
+        .unwrap();
+    let mut roster = Roster::new();
+    let manager = roster.add("Ada", Role::Manager);
+    let pricing = PricingRules::new(&menu)
+        .tax(Category::Appetizer, Rate::percent(10))
+        .auto_gratuity(6, Rate::percent(18));
+    let mut shift = Shift::new(floor, Inventory::default())
+        .with_roster(roster)
+        .with_pricing(pricing);
+    shift.clock_in(manager, now).unwrap();
+
+    shift.add_to_waitlist(Party::new("Ferris", 6, now)).unwrap();
+    shift.seat_next(now).unwrap();
+    let mut menu = menu;
+    let lines = vec![
+        menu.order("Soup", 2, &[], now).unwrap(),
+        menu.order("Salad", 1, &[], now).unwrap(),
+    ];
+    let order = shift.take_order(TableNumber(1), lines, now).unwrap();
+    shift
+        .on_duty(manager)
+        .unwrap()
+        .apply_discount(order, Discount::fixed("regulars", 200), now)
+        .unwrap();
+    shift.send_to_kitchen(order, now).unwrap();
+    shift.cook_order(order, now).unwrap();
+    shift.mark_ready(order, now).unwrap();
+    shift.serve_order(order, now).unwrap();
+
+    let breakdown = shift.breakdown(order).unwrap();
+    let check = shift.open_bill(order, now).unwrap().checks()[0].id;
+    shift.add_tip(order, check, 300, now).unwrap();
+    let total = shift.bill(order).unwrap().checks()[0].total();
+    let mut gateway = InMemoryGateway::new();
+    shift
+        .take_payment(order, check, Tender::Cash(total), &mut gateway, now)
+        .unwrap();
+
+    let report = ShiftReport::from_shift(&shift, &menu);
+    let sales = &report.sales;
+    assert_eq!(sales.revenue, 1_800);
+    assert_eq!(sales.adjustments, 200);
+    assert_eq!(sales.tax, breakdown.tax);
+    assert_eq!(sales.gratuity, breakdown.gratuity.unwrap().amount);
+    assert_eq!(sales.tips, 300);
+    assert_eq!(
+        sales.revenue + sales.tax + sales.gratuity + sales.tips,
+        total
+    );
+    assert_eq!(
+        sales.by_item.iter().map(|i| i.revenue).sum::<u64>(),
+        sales.revenue
+    );
+    assert_eq!(report.checks.count, 1);
+    assert_eq!(report.checks.average, 1_800);
+    assert_eq!(report.checks.per_cover, 300);
+}
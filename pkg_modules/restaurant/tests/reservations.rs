@@ -0,0 +1,76 @@
+use restaurant::hosting::{
+    FloorPlan, ReservationError, ReservationRequest, ReservationStatus, SeatingArea, Table,
+    TableNumber, TableState,
+};
+use restaurant::inventory::Inventory;
+use restaurant::shift::{Shift, ShiftError};
+use restaurant::time::{Date, TimeOfDay};
+
+fn shift_with_one_table() -> Shift {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    Shift::new(floor, Inventory::default())
+}
+
+fn day() -> Date {
+    Date::new(2024, 6, 1)
+}
+
+#[test]
+fn a_seated_reservation_blocks_its_table_until_the_party_leaves() {
+    let mut shift = shift_with_one_table();
+    let seven = day().at(TimeOfDay::new(19, 0));
+    let half_past = day().at(TimeOfDay::new(19, 30));
+
+    let first = shift
+        .book(
+            ReservationRequest::new("Ada", 4, day(), TimeOfDay::new(19, 0)),
+            seven,
+        )
+        .unwrap();
+    let party = shift.check_in(first, seven).unwrap();
+    shift.seat_next(seven).unwrap();
+
+    let late = ReservationRequest::new("Grace", 2, day(), TimeOfDay::new(19, 30));
+    assert!(matches!(
+        shift.book(late.clone(), half_past),
+        Err(ShiftError::Reservation(ReservationError::Conflict { .. }))
+    ));
+
+    shift.vacate(party, half_past).unwrap();
+    assert_eq!(
+        shift.reservations().get(first).unwrap().status,
+        ReservationStatus::Finished
+    );
+    shift.book(late, half_past).unwrap();
+}
+
+#[test]
+fn closing_a_reservation_leaves_tables_held_for_another() {
+    let mut shift = shift_with_one_table();
+    let noon = day().at(TimeOfDay::new(12, 0));
+
+    let lunch = shift
+        .book(
+            ReservationRequest::new("Ada", 2, day(), TimeOfDay::new(12, 30)),
+            noon,
+        )
+        .unwrap();
+    let dinner = shift
+        .book(
+            ReservationRequest::new("Grace", 2, day(), TimeOfDay::new(20, 0)),
+            noon,
+        )
+        .unwrap();
+    shift.hold(dinner, noon).unwrap();
+
+    shift.cancel(lunch, noon).unwrap();
+    let table = shift.floor().table(TableNumber(1)).unwrap();
+    assert_eq!(table.state, TableState::Reserved);
+
+    shift.cancel(dinner, noon).unwrap();
+    let table = shift.floor().table(TableNumber(1)).unwrap();
+    assert_eq!(table.state, TableState::Free);
+}
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::{Ingredient, Inventory, RecipeBook, Unit};
+use restaurant::kitchen::{
+    Course, CourseScheduler, FiringStatus, KitchenError, ScheduleError, Station, StationRouter,
+};
+use restaurant::menu::Menu;
+use restaurant::serving::{LineItem, Order, OrderError, OrderId};
+use restaurant::shift::{Shift, ShiftError};
+use restaurant::time::{Clock, ManualClock, Timestamp};
+
+fn minutes(m: u64) -> Duration {
+    Duration::from_secs(m * 60)
+}
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn order(items: &[&str]) -> Order {
+    let mut order = Order::new(OrderId(1), TableNumber(1), six_pm());
+    for item in items {
+        order.add_item(LineItem::new(item, 1_000)).unwrap();
+    }
+    order
+}
+
+// Earliest first, whichever line the cook takes first.
+fn fire_times(scheduler: &CourseScheduler) -> Vec<Option<Timestamp>> {
+    let mut times: Vec<Option<Timestamp>> = scheduler.firings().iter().map(|f| f.fire_at).collect();
+    times.sort();
+    times
+}
+
+fn shift(inventory: Inventory) -> Shift {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    Shift::new(floor, inventory)
+}
+
+fn sent(shift: &mut Shift, items: &[&str]) -> OrderId {
+    let mut menu = Menu::standard();
+    let lines = items
+        .iter()
+        .map(|item| menu.order(item, 1, &[], six_pm()).unwrap())
+        .collect();
+    let order = shift.take_order(TableNumber(1), lines, six_pm()).unwrap();
+    shift.send_to_kitchen(order, six_pm()).unwrap();
+    order
+}
+
+#[test]
+fn one_cook_makes_two_lines_back_to_back() {
+    let scheduler = || {
+        CourseScheduler::new(&Menu::standard(), StationRouter::default())
+            .prep_time("Burger", minutes(10))
+    };
+
+    let mut alone = scheduler().cooks(Station::Grill, 1);
+    let lands = alone
+        .schedule(&order(&["Burger", "Burger"]), six_pm())
+        .unwrap();
+    assert_eq!(lands, six_pm() + minutes(20));
+    assert_eq!(
+        fire_times(&alone),
+        vec![Some(six_pm()), Some(six_pm() + minutes(10))]
+    );
+
+    let mut pair = scheduler().cooks(Station::Grill, 2);
+    let lands = pair
+        .schedule(&order(&["Burger", "Burger"]), six_pm())
+        .unwrap();
+    assert_eq!(lands, six_pm() + minutes(10));
+    assert_eq!(fire_times(&pair), vec![Some(six_pm()), Some(six_pm())]);
+}
+
+#[test]
+fn mains_wait_for_the_starters_to_be_served() {
+    let mut scheduler = CourseScheduler::new(&Menu::standard(), StationRouter::default());
+    let order = order(&["Soup", "Burger"]);
+    scheduler.schedule(&order, six_pm()).unwrap();
+
+    let burger = &scheduler.firings()[1];
+    assert_eq!(burger.course, Course::Mains);
+    assert_eq!(burger.status, FiringStatus::Held);
+    assert_eq!(burger.fire_at, None);
+    let later = six_pm() + minutes(60);
+    assert_eq!(scheduler.due(later).get(&order.id), Some(&vec![0]));
+
+    assert_eq!(
+        scheduler.served(order.id, Course::Starters, later),
+        Err(ScheduleError::NotFired {
+            order: order.id,
+            course: Course::Starters,
+        })
+    );
+
+    scheduler.fired(order.id, &[0]);
+    let lands = scheduler
+        .served(order.id, Course::Starters, later)
+        .unwrap()
+        .unwrap();
+    assert!(lands >= later + minutes(15));
+    let burger = &scheduler.firings()[1];
+    assert_eq!(burger.status, FiringStatus::Scheduled);
+    assert_eq!(burger.lands_at, Some(lands));
+}
+
+#[test]
+fn fire_due_fires_each_line_as_the_clock_reaches_it() {
+    let mut shift = shift(Inventory::default());
+    let order = sent(&mut shift, &["Soup", "Salad"]);
+    let mut scheduler = CourseScheduler::new(&Menu::standard(), StationRouter::default());
+    let clock = ManualClock::new(six_pm());
+
+    // the salad takes a minute longer, so the soup goes on a minute after
+    let lands = scheduler
+        .schedule(shift.orders().get(order).unwrap(), clock.now())
+        .unwrap();
+    assert_eq!(lands, six_pm() + minutes(5));
+
+    let fired = scheduler.fire_due(&mut shift, &clock).unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(
+        shift.kitchen().ticket(fired[0]).unwrap().station,
+        Station::Salad
+    );
+
+    clock.advance(Duration::from_secs(30));
+    assert!(scheduler.fire_due(&mut shift, &clock).unwrap().is_empty());
+
+    clock.advance(Duration::from_secs(30));
+    let fired = scheduler.fire_due(&mut shift, &clock).unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(
+        shift.kitchen().ticket(fired[0]).unwrap().station,
+        Station::Grill
+    );
+
+    clock.advance(minutes(60));
+    assert!(scheduler.fire_due(&mut shift, &clock).unwrap().is_empty());
+    assert_eq!(scheduler.next_due(), None);
+}
+
+#[test]
+fn a_line_is_only_fired_once() {
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    inventory.stock(Ingredient::new("soup base", Unit::Millilitres, 3_000));
+    inventory.stock(Ingredient::new("cream", Unit::Millilitres, 300));
+    let mut shift = shift(inventory);
+    let soup_base = |shift: &Shift| shift.inventory().ingredient("soup base").unwrap().on_hand;
+    let already_fired = |result: Result<_, ShiftError>| {
+        matches!(
+            result,
+            Err(ShiftError::Kitchen(KitchenError::Order(
+                OrderError::AlreadyFired { line: 0, .. }
+            )))
+        )
+    };
+
+    let coursed = sent(&mut shift, &["Soup", "Coffee"]);
+    let tickets = shift.fire_course(coursed, &[0, 0], six_pm()).unwrap();
+    assert_eq!(tickets.len(), 1);
+    assert_eq!(soup_base(&shift), 2_700);
+    assert!(already_fired(shift.fire_course(coursed, &[0], six_pm())));
+    assert!(shift.cook_order(coursed, six_pm()).is_err());
+    assert_eq!(soup_base(&shift), 2_700);
+
+    let cooked = sent(&mut shift, &["Soup"]);
+    let tickets = shift.cook_order(cooked, six_pm()).unwrap();
+    assert_eq!(soup_base(&shift), 2_400);
+    assert!(already_fired(shift.fire_course(cooked, &[0], six_pm())));
+    assert_eq!(shift.kitchen().tickets().count(), 2);
+
+    // a dish that went out wrong is remade from its ticket
+    shift
+        .fix_incorrect_order(tickets[0], "cold", six_pm())
+        .unwrap();
+    assert_eq!(shift.kitchen().tickets().count(), 3);
+}
@@ -0,0 +1,71 @@
+use restaurant::menu::{Breakfast, Season, SeasonalProduce};
+use restaurant::time::Date;
+
+#[test]
+fn seasons_turn_on_the_first_of_the_month() {
+    let seasons: Vec<_> = [(2, 28), (3, 1), (5, 31), (6, 1), (9, 1), (11, 30), (12, 1)]
+        .into_iter()
+        .map(|(month, day)| Season::of(Date::new(2024, month, day)))
+        .collect();
+    assert_eq!(
+        seasons,
+        vec![
+            Season::Winter,
+            Season::Spring,
+            Season::Spring,
+            Season::Summer,
+            Season::Autumn,
+            Season::Autumn,
+            Season::Winter,
+        ]
+    );
+}
+
+#[test]
+fn every_season_has_house_fruit() {
+    let breakfasts = [
+        Breakfast::spring("rye"),
+        Breakfast::summer("rye"),
+        Breakfast::autumn("rye"),
+        Breakfast::winter("rye"),
+    ];
+    let fruit: Vec<_> = breakfasts
+        .iter()
+        .map(|b| (b.season(), b.seasonal_fruit()))
+        .collect();
+    assert_eq!(
+        fruit,
+        vec![
+            (Season::Spring, "strawberries"),
+            (Season::Summer, "peaches"),
+            (Season::Autumn, "apples"),
+            (Season::Winter, "oranges"),
+        ]
+    );
+
+    let january = Breakfast::for_date("wheat", Date::new(2025, 1, 15));
+    assert_eq!(january, Breakfast::winter("wheat"));
+    assert_eq!(january.toast, "wheat");
+}
+
+#[test]
+fn a_configured_table_falls_back_to_the_house_fruit() {
+    let produce = SeasonalProduce::new()
+        .set(Season::Summer, "cherries")
+        .set(Season::Autumn, "pears");
+    assert_eq!(
+        produce.entries().collect::<Vec<_>>(),
+        vec![(Season::Summer, "cherries"), (Season::Autumn, "pears")]
+    );
+    assert_eq!(produce.fruit(Season::Spring), "strawberries");
+
+    let july = produce.breakfast_on("rye", Date::new(2024, 7, 4));
+    assert_eq!(july.season(), Season::Summer);
+    assert_eq!(july.seasonal_fruit(), "cherries");
+    assert_eq!(
+        produce.breakfast("rye", Season::Winter).seasonal_fruit(),
+        "oranges"
+    );
+    // the plain constructors keep using the house table
+    assert_eq!(Breakfast::summer("rye").seasonal_fruit(), "peaches");
+}
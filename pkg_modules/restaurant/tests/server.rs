@@ -0,0 +1,136 @@
+#![cfg(feature = "server")]
+
+use std::env;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use restaurant::hosting::{FloorPlan, SeatingArea, Table};
+use restaurant::menu::Menu;
+use restaurant::server::{Client, ClientError, Running, Server};
+use restaurant::state::StateFile;
+
+fn state() -> StateFile {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    StateFile::new(floor, Menu::standard())
+}
+
+fn serve(server: Server) -> (Running, Client) {
+    let running = server.bind("127.0.0.1:0").unwrap();
+    let client = running.client();
+    (running, client)
+}
+
+fn status(result: Result<Value, ClientError>) -> u16 {
+    match result {
+        Err(ClientError::Status { status, .. }) => status,
+        other => panic!("expected an error status, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_party_is_seated_served_and_pays_over_http() {
+    let (running, client) = serve(Server::new(state()));
+
+    let joined = client
+        .post("/waitlist", &json!({"name": "Ferris", "size": 2}))
+        .unwrap();
+    assert_eq!(joined["party"], 1);
+    assert_eq!(
+        client.get("/waitlist").unwrap().as_array().unwrap().len(),
+        1
+    );
+    let seated = client.action("/waitlist/seat").unwrap();
+    assert_eq!(seated["tables"], json!([1]));
+
+    let order = client.post("/orders", &json!({"table": 1})).unwrap()["id"].clone();
+    let order = order.as_u64().unwrap();
+    client
+        .post(
+            &format!("/orders/{}/items", order),
+            &json!({"item": "Soup", "seat": 1}),
+        )
+        .unwrap();
+    let fired = client.action(&format!("/orders/{}/send", order)).unwrap();
+    let tickets = fired["tickets"].as_array().unwrap().clone();
+    assert!(!tickets.is_empty());
+
+    let mut events = client
+        .events()
+        .unwrap()
+        .with_timeout(Duration::from_secs(5))
+        .unwrap();
+    for ticket in &tickets {
+        client.action(&format!("/tickets/{}/bump", ticket)).unwrap();
+    }
+    let bumped = events.next().unwrap().unwrap();
+    assert_eq!(bumped.kind, "ticket_bumped");
+    assert_eq!(bumped.data["ticket"], tickets[0]);
+
+    client.action(&format!("/orders/{}/serve", order)).unwrap();
+    let bill = client.action(&format!("/orders/{}/bill", order)).unwrap();
+    let check = &bill["checks"][0];
+    let receipt = client
+        .post(
+            &format!("/orders/{}/bill/checks/{}/payments", order, check["id"]),
+            &json!({"cash": check["total"]}),
+        )
+        .unwrap();
+    assert_eq!(receipt["balance"], 0);
+    assert_eq!(
+        client.get(&format!("/orders/{}", order)).unwrap()["status"],
+        "paid"
+    );
+
+    let state = running.shutdown();
+    assert!(!state.shift.log().is_empty());
+}
+
+#[test]
+fn errors_map_to_statuses() {
+    let (_running, client) = serve(Server::new(state()));
+
+    assert_eq!(status(client.get("/nowhere")), 404);
+    assert_eq!(status(client.get("/orders/7")), 404);
+    assert_eq!(status(client.delete("/tables")), 405);
+
+    client.post("/orders", &json!({"table": 1})).unwrap();
+    assert_eq!(status(client.action("/orders/1/serve")), 409);
+    assert_eq!(
+        status(client.post("/orders/1/items", &json!({"item": "Nothing"}))),
+        400
+    );
+}
+
+#[test]
+fn a_change_that_cannot_be_saved_is_undone() {
+    let path = env::temp_dir()
+        .join(format!("restaurant-missing-{}", std::process::id()))
+        .join("state.json");
+    let (running, client) = serve(Server::new(state()).save_to(path));
+
+    let failed = client.post("/waitlist", &json!({"name": "Ferris", "size": 2}));
+    assert_eq!(status(failed), 500);
+    assert_eq!(client.get("/waitlist").unwrap(), json!([]));
+
+    let state = running.shutdown();
+    assert!(state.shift.log().is_empty());
+}
+
+#[test]
+fn a_failed_request_puts_back_the_stock_it_took() {
+    let mut state = state();
+    state.menu.set_stock("Soup", Some(1)).unwrap();
+    let (_running, client) = serve(Server::new(state));
+
+    let missing = client.post("/orders/9/items", &json!({"item": "Soup"}));
+    assert_eq!(status(missing), 404);
+
+    client.post("/orders", &json!({"table": 1})).unwrap();
+    client
+        .post("/orders/1/items", &json!({"item": "Soup"}))
+        .unwrap();
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use restaurant::hosting::{FloorPlan, SeatingArea, Table};
+use restaurant::inventory::Inventory;
+use restaurant::kitchen::Station;
+use restaurant::menu::Menu;
+use restaurant::shift::Shift;
+use restaurant::simulation::{SimConfig, SimReport, Simulation};
+use restaurant::time::Timestamp;
+
+fn minutes(m: u64) -> Duration {
+    Duration::from_secs(m * 60)
+}
+
+// A busy night: a party every four minutes for three hours.
+fn config(seed: u64) -> SimConfig {
+    SimConfig::new(seed, Timestamp::from_secs(17 * 60 * 60))
+        .open_for(minutes(180))
+        .arrivals_every(minutes(4))
+}
+
+fn run(config: SimConfig) -> SimReport {
+    let mut floor = FloorPlan::new();
+    for number in 1..=6 {
+        floor
+            .add_table(Table::new(number, 4, SeatingArea::Indoor))
+            .unwrap();
+    }
+    let shift = Shift::new(floor, Inventory::default());
+    Simulation::new(shift, Menu::standard(), config)
+        .run()
+        .unwrap()
+        .report
+}
+
+fn grill(report: &SimReport) -> f64 {
+    report
+        .kitchen
+        .iter()
+        .find(|load| load.station == Station::Grill)
+        .unwrap()
+        .utilisation
+}
+
+#[test]
+fn the_same_seed_plays_out_the_same_night() {
+    let first = run(config(42));
+    assert!(first.arrived > 0);
+    assert_eq!(first, run(config(42)));
+    assert_ne!(first, run(config(43)));
+}
+
+#[test]
+fn more_cooks_are_each_less_busy() {
+    let one = run(config(42).cooks(Station::Grill, 1));
+    let three = run(config(42).cooks(Station::Grill, 3));
+    assert!(grill(&one) > 0.0);
+    assert!(grill(&three) < grill(&one));
+}
+
+#[test]
+fn more_bussers_get_more_parties_seated() {
+    let slow = |bussers| {
+        let mut config = config(42).bussers(bussers);
+        config.bus_time = minutes(20);
+        config
+    };
+    let one = run(slow(1));
+    let four = run(slow(4));
+    assert!(four.seated > one.seated);
+    assert!(four.seat_utilisation > one.seat_utilisation);
+}
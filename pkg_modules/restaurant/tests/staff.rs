@@ -0,0 +1,125 @@
+use restaurant::billing::{InMemoryGateway, Tender};
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::Inventory;
+use restaurant::menu::Menu;
+use restaurant::serving::{OrderId, OrderStatus};
+use restaurant::shift::{Shift, ShiftError};
+use restaurant::staff::{Permission, Role, Roster, StaffError};
+use restaurant::time::Timestamp;
+
+fn now() -> Timestamp {
+    Timestamp::from_secs(19 * 60 * 60)
+}
+
+fn floor() -> FloorPlan {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor).in_section("front"))
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 4, SeatingArea::Indoor).in_section("back"))
+        .unwrap();
+    floor
+}
+
+// Taken, cooked and served, ready for the bill.
+fn served(shift: &mut Shift, table: u16) -> OrderId {
+    let soup = Menu::standard().order("Soup", 1, &[], now()).unwrap();
+    let order = shift
+        .take_order(TableNumber(table), vec![soup], now())
+        .unwrap();
+    shift.send_to_kitchen(order, now()).unwrap();
+    shift.cook_order(order, now()).unwrap();
+    shift.mark_ready(order, now()).unwrap();
+    shift.serve_order(order, now()).unwrap();
+    order
+}
+
+fn not_permitted(result: Result<impl Sized, ShiftError>, permission: Permission) -> bool {
+    matches!(
+        result,
+        Err(ShiftError::Staff(StaffError::NotPermitted { permission: p, .. })) if p == permission
+    )
+}
+
+#[test]
+fn the_shift_itself_does_not_ask_who_is_working() {
+    let mut shift = Shift::new(floor(), Inventory::default());
+    assert_eq!(shift.roster().iter().count(), 0);
+
+    let order = served(&mut shift, 1);
+    let check = shift.open_bill(order, now()).unwrap().checks()[0].clone();
+    shift
+        .take_payment(
+            order,
+            check.id,
+            Tender::Cash(check.total()),
+            &mut InMemoryGateway::new(),
+            now(),
+        )
+        .unwrap();
+    assert_eq!(
+        shift.orders().get(order).unwrap().status(),
+        OrderStatus::Paid
+    );
+}
+
+#[test]
+fn on_duty_checks_the_role_and_the_section() {
+    let mut roster = Roster::new();
+    let host = roster.add("Hana", Role::Host);
+    let server = roster.add("Sam", Role::Server);
+    let manager = roster.add("Ada", Role::Manager);
+    roster.assign_section(server, Some("front")).unwrap();
+    let mut shift = Shift::new(floor(), Inventory::default()).with_roster(roster);
+    let mut gateway = InMemoryGateway::new();
+
+    assert!(matches!(
+        shift.on_duty(server),
+        Err(ShiftError::Staff(StaffError::NotClockedIn(_)))
+    ));
+    for staff in [host, server, manager] {
+        shift.clock_in(staff, now()).unwrap();
+    }
+
+    let soup = Menu::standard().order("Soup", 1, &[], now()).unwrap();
+    let taken = shift
+        .on_duty(host)
+        .unwrap()
+        .take_order(TableNumber(1), vec![soup.clone()], now());
+    assert!(not_permitted(taken, Permission::TakeOrders));
+    let taken = shift
+        .on_duty(server)
+        .unwrap()
+        .take_order(TableNumber(2), vec![soup], now());
+    assert!(matches!(
+        taken,
+        Err(ShiftError::Staff(StaffError::OutOfSection { .. }))
+    ));
+
+    let order = served(&mut shift, 1);
+    let check = shift.open_bill(order, now()).unwrap().checks()[0].clone();
+    let receipt = shift
+        .on_duty(server)
+        .unwrap()
+        .take_payment(
+            order,
+            check.id,
+            Tender::Cash(check.total()),
+            &mut gateway,
+            now(),
+        )
+        .unwrap();
+
+    let refund =
+        shift
+            .on_duty(server)
+            .unwrap()
+            .refund(order, receipt.payment, 100, &mut gateway, now());
+    assert!(not_permitted(refund, Permission::Refund));
+    shift
+        .on_duty(manager)
+        .unwrap()
+        .refund(order, receipt.payment, 100, &mut gateway, now())
+        .unwrap();
+}
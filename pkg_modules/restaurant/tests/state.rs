@@ -0,0 +1,63 @@
+use std::env;
+use std::fs;
+
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::menu::Menu;
+use restaurant::serving::OrderStatus;
+use restaurant::shift::ShiftError;
+use restaurant::staff::{Role, Roster, StaffError, StaffId};
+use restaurant::state::StateFile;
+use restaurant::time::Timestamp;
+
+fn floor() -> FloorPlan {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    floor
+}
+
+#[test]
+fn the_opening_roster_survives_a_save_and_voids_need_someone_allowed_to() {
+    let path = env::temp_dir().join(format!("restaurant-state-{}.json", std::process::id()));
+    let now = Timestamp::from_secs(1_000);
+
+    let mut roster = Roster::new();
+    let manager = roster.add("Ada", Role::Manager);
+    let mut state = StateFile::new(floor(), Menu::standard()).with_staff(roster);
+    let order = state
+        .shift
+        .take_order(TableNumber(1), Vec::new(), now)
+        .unwrap();
+    state.shift.clock_in(manager, now).unwrap();
+    let cook = state
+        .shift
+        .on_duty(manager)
+        .unwrap()
+        .hire("Cy", Role::Cook, now)
+        .unwrap();
+    state.shift.clock_in(cook, now).unwrap();
+    state.save(&path).unwrap();
+
+    let mut state = StateFile::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(state.shift.roster().iter().count(), 2);
+    assert!(matches!(
+        state.shift.on_duty(cook).unwrap().void(order, now),
+        Err(ShiftError::Staff(StaffError::NotPermitted { .. }))
+    ));
+    assert!(matches!(
+        state.shift.on_duty(StaffId(9)),
+        Err(ShiftError::Staff(StaffError::UnknownStaff(_)))
+    ));
+    state
+        .shift
+        .on_duty(manager)
+        .unwrap()
+        .void(order, now)
+        .unwrap();
+    assert_eq!(
+        state.shift.orders().get(order).unwrap().status(),
+        OrderStatus::Voided
+    );
+}
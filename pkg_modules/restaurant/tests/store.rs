@@ -0,0 +1,233 @@
+use restaurant::config::{FloorFile, InventoryFile, MenuFile};
+use restaurant::events::{Event, Record};
+use restaurant::hosting::{FloorPlan, SeatingArea, Table, TableNumber};
+use restaurant::inventory::{Ingredient, Inventory, RecipeBook, Unit};
+use restaurant::menu::Menu;
+use restaurant::store::{
+    MemoryStore, Persistent, RestaurantStore, Snapshot, StoreError, TransactionError,
+};
+use restaurant::time::Timestamp;
+
+fn floor() -> FloorPlan {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor))
+        .unwrap();
+    floor
+}
+
+fn menu() -> Menu {
+    let mut menu = Menu::standard();
+    menu.set_stock("Soup", Some(5)).unwrap();
+    menu
+}
+
+fn inventory() -> Inventory {
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    inventory.stock(Ingredient::new("soup base", Unit::Millilitres, 3_000));
+    inventory.stock(Ingredient::new("cream", Unit::Millilitres, 300));
+    inventory
+}
+
+fn noon() -> Timestamp {
+    Timestamp::from_secs(12 * 60 * 60)
+}
+
+fn soup_stock(menu: &Menu) -> Option<u32> {
+    menu.item("Soup").unwrap().stock
+}
+
+// Orders a soup, which takes one off the menu's count, and then fails.
+fn order_soup_then_fail<S: RestaurantStore>(store: S) {
+    let mut shift = Persistent::create(store, floor(), menu(), inventory()).unwrap();
+    let result = shift.transaction(|state| {
+        let soup = state.menu.order("Soup", 1, &[], noon())?;
+        let order = state.shift.take_order(TableNumber(1), vec![soup], noon())?;
+        state.shift.serve_order(order, noon())?;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    });
+
+    assert!(matches!(result, Err(TransactionError::Aborted(_))));
+    assert_eq!(soup_stock(&shift.state().unwrap().menu), Some(5));
+    assert!(shift.state().unwrap().shift.log().is_empty());
+
+    let reopened = Persistent::open(shift.into_store()).unwrap();
+    assert_eq!(soup_stock(&reopened.state().unwrap().menu), Some(5));
+    assert!(reopened.state().unwrap().shift.log().is_empty());
+}
+
+// The log replays onto the walk-in the shift opened with.
+fn cook_against_the_opening_inventory<S: RestaurantStore>(store: S) {
+    let mut shift = Persistent::create(store, floor(), menu(), inventory()).unwrap();
+    shift
+        .transaction(|state| {
+            let soup = state.menu.order("Soup", 2, &[], noon())?;
+            let order = state.shift.take_order(TableNumber(1), vec![soup], noon())?;
+            state.shift.send_to_kitchen(order, noon())?;
+            state.shift.cook_order(order, noon())?;
+            Ok::<_, Box<dyn std::error::Error>>(())
+        })
+        .unwrap();
+
+    let reopened = Persistent::open(shift.into_store()).unwrap();
+    let walk_in = reopened.state().unwrap().shift.inventory();
+    assert_eq!(walk_in.ingredient("soup base").unwrap().on_hand, 2_400);
+    assert_eq!(walk_in.ingredient("cream").unwrap().on_hand, 240);
+    assert_eq!(soup_stock(&reopened.state().unwrap().menu), Some(3));
+}
+
+fn reject_a_gap_in_the_log<S: RestaurantStore>(mut store: S) {
+    store
+        .create(
+            &config_of(&floor()),
+            &config_of(&menu()),
+            &config_of(&inventory()),
+        )
+        .unwrap();
+    let record = |seq| Record {
+        seq,
+        at: noon(),
+        event: Event::TableBussed {
+            table: TableNumber(1),
+        },
+    };
+
+    store.commit(&[record(1)], &config_of(&menu())).unwrap();
+    let gap = store.commit(&[record(3)], &config_of(&menu()));
+    assert!(matches!(
+        gap,
+        Err(StoreError::OutOfSequence {
+            expected: 2,
+            found: 3
+        })
+    ));
+    assert_eq!(store.load().unwrap().records.len(), 1);
+}
+
+fn config_of<T: restaurant::config::ConfigFile>(value: &T) -> T::Schema {
+    value.to_schema()
+}
+
+// A store that stops loading once it has been opened.
+struct Unloadable {
+    store: MemoryStore,
+    loads: bool,
+}
+
+impl RestaurantStore for Unloadable {
+    fn create(
+        &mut self,
+        floor: &FloorFile,
+        menu: &MenuFile,
+        inventory: &InventoryFile,
+    ) -> Result<(), StoreError> {
+        self.store.create(floor, menu, inventory)
+    }
+
+    fn load(&mut self) -> Result<Snapshot, StoreError> {
+        if !self.loads {
+            return Err(StoreError::Empty);
+        }
+        self.store.load()
+    }
+
+    fn commit(&mut self, records: &[Record], menu: &MenuFile) -> Result<(), StoreError> {
+        self.store.commit(records, menu)
+    }
+}
+
+#[test]
+fn a_failed_rollback_keeps_the_error_and_poisons_the_shift() {
+    let store = Unloadable {
+        store: MemoryStore::new(),
+        loads: false,
+    };
+    let mut shift = Persistent::create(store, floor(), menu(), inventory()).unwrap();
+    let result = shift.transaction(|state| {
+        let soup = state.menu.order("Soup", 1, &[], noon()).unwrap();
+        state
+            .shift
+            .take_order(TableNumber(1), vec![soup], noon())
+            .unwrap();
+        Err::<(), _>("the card machine is down")
+    });
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::Aborted("the card machine is down"))
+    ));
+    assert!(matches!(shift.state(), Err(StoreError::Poisoned)));
+    assert!(matches!(
+        shift.transaction(|_| Ok::<_, String>(())),
+        Err(TransactionError::Store(StoreError::Poisoned))
+    ));
+
+    // nothing of the failed change was committed
+    let mut store = shift.into_store();
+    store.loads = true;
+    let reopened = Persistent::open(store).unwrap();
+    assert_eq!(soup_stock(&reopened.state().unwrap().menu), Some(5));
+    assert!(reopened.state().unwrap().shift.log().is_empty());
+}
+
+#[test]
+fn memory_store_rolls_back_a_failed_transaction() {
+    order_soup_then_fail(MemoryStore::new());
+}
+
+#[test]
+fn memory_store_keeps_the_opening_inventory() {
+    cook_against_the_opening_inventory(MemoryStore::new());
+}
+
+#[test]
+fn memory_store_rejects_a_gap_in_the_log() {
+    reject_a_gap_in_the_log(MemoryStore::new());
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::env;
+    use std::fs;
+
+    use restaurant::store::{SqliteStore, StoreError};
+
+    #[test]
+    fn rolls_back_a_failed_transaction() {
+        super::order_soup_then_fail(SqliteStore::in_memory().unwrap());
+    }
+
+    #[test]
+    fn keeps_the_opening_inventory() {
+        super::cook_against_the_opening_inventory(SqliteStore::in_memory().unwrap());
+    }
+
+    #[test]
+    fn rejects_a_gap_in_the_log() {
+        super::reject_a_gap_in_the_log(SqliteStore::in_memory().unwrap());
+    }
+
+    #[test]
+    fn migrations_run_once_and_refuse_a_newer_schema() {
+        let path = env::temp_dir().join(format!("restaurant-store-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let version = SqliteStore::open(&path).unwrap().schema_version().unwrap();
+        assert!(version > 0);
+        let reopened = SqliteStore::open(&path).unwrap();
+        assert_eq!(reopened.schema_version().unwrap(), version);
+        drop(reopened);
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", version + 1)
+            .unwrap();
+        drop(conn);
+        let newer = SqliteStore::open(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            newer,
+            Err(StoreError::NewerSchema { version: found, supported })
+                if found == version + 1 && supported == version
+        ));
+    }
+}
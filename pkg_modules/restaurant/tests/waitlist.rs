@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use restaurant::hosting::{
+    add_to_waitlist, seat_at_table, FloorPlan, Party, PartyId, Priority, SeatError, SeatingArea,
+    Table, TableNumber, TableState, Waitlist, DEFAULT_PACE,
+};
+use restaurant::time::Timestamp;
+
+fn six_pm() -> Timestamp {
+    Timestamp::from_secs(18 * 60 * 60)
+}
+
+fn minutes(n: u64) -> Duration {
+    Duration::from_secs(n * 60)
+}
+
+#[test]
+fn high_priority_parties_are_called_first_and_the_rest_in_arrival_order() {
+    let mut waitlist = Waitlist::new();
+    let first = add_to_waitlist(&mut waitlist, Party::new("Ada", 2, six_pm()));
+    let second = add_to_waitlist(&mut waitlist, Party::new("Bo", 2, six_pm() + minutes(1)));
+    // came in before Bo but was only added after
+    let early = add_to_waitlist(&mut waitlist, Party::new("Cy", 2, six_pm()));
+    let vip = Party::new("Di", 4, six_pm() + minutes(5)).priority(Priority::High);
+    assert_eq!(waitlist.quote(&vip), DEFAULT_PACE);
+    let vip = add_to_waitlist(&mut waitlist, vip);
+
+    let order: Vec<_> = waitlist.iter().map(|entry| entry.id).collect();
+    assert_eq!(order, vec![vip, first, early, second]);
+    assert_eq!(waitlist.position(second), Some(3));
+    assert_eq!(waitlist.get(second).unwrap().quoted_wait, DEFAULT_PACE * 2);
+    assert_eq!(waitlist.get(early).unwrap().quoted_wait, DEFAULT_PACE * 2);
+
+    waitlist.set_pace(minutes(5));
+    assert_eq!(
+        waitlist.quote(&Party::new("Ed", 2, six_pm() + minutes(6))),
+        minutes(25)
+    );
+    assert_eq!(waitlist.remove(first).unwrap().party.name, "Ada");
+    assert_eq!(waitlist.position(second), Some(2));
+    assert!(waitlist.remove(first).is_none());
+}
+
+#[test]
+fn the_first_party_that_fits_is_seated_and_the_rest_keep_their_place() {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 2, SeatingArea::Indoor))
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 4, SeatingArea::Outdoor))
+        .unwrap();
+    let mut waitlist = Waitlist::new();
+    let big = add_to_waitlist(&mut waitlist, Party::new("Ada", 6, six_pm()));
+    let outside = add_to_waitlist(
+        &mut waitlist,
+        Party::new("Bo", 2, six_pm()).prefers(SeatingArea::Outdoor),
+    );
+    let couple = add_to_waitlist(&mut waitlist, Party::new("Cy", 2, six_pm()));
+
+    let later = six_pm() + minutes(12);
+    let seated = seat_at_table(&mut waitlist, &mut floor, later).unwrap();
+    assert_eq!(seated.id, outside);
+    assert_eq!(seated.tables, vec![TableNumber(2)]);
+    assert_eq!(seated.waited, minutes(12));
+    assert_eq!(
+        floor.table(TableNumber(2)).unwrap().state,
+        TableState::Occupied(outside)
+    );
+
+    let seated = seat_at_table(&mut waitlist, &mut floor, later).unwrap();
+    assert_eq!(seated.id, couple);
+    assert_eq!(seated.tables, vec![TableNumber(1)]);
+
+    // nobody left fits, and Ada never could
+    assert_eq!(
+        seat_at_table(&mut waitlist, &mut floor, later),
+        Err(SeatError::PartyTooLarge {
+            party: big,
+            size: 6,
+            max: 4,
+        })
+    );
+    assert_eq!(waitlist.position(big), Some(0));
+    let sat: Vec<_> = waitlist.seated().iter().map(|s| s.id).collect();
+    assert_eq!(sat, vec![outside, couple]);
+}
+
+#[test]
+fn nobody_is_seated_when_no_table_is_free() {
+    let mut floor = FloorPlan::new();
+    floor
+        .add_table(Table::new(1, 4, SeatingArea::Indoor).accessible())
+        .unwrap();
+    floor
+        .add_table(Table::new(2, 4, SeatingArea::Indoor))
+        .unwrap();
+    let mut waitlist = Waitlist::new();
+    assert_eq!(
+        seat_at_table(&mut waitlist, &mut floor, six_pm()),
+        Err(SeatError::EmptyWaitlist)
+    );
+
+    // the only accessible table is held and the other one is taken
+    floor.reserve(TableNumber(1)).unwrap();
+    floor.seat(&[TableNumber(2)], PartyId(99)).unwrap();
+    add_to_waitlist(
+        &mut waitlist,
+        Party::new("Ada", 2, six_pm()).needs_accessible(),
+    );
+    assert_eq!(
+        seat_at_table(&mut waitlist, &mut floor, six_pm()),
+        Err(SeatError::NoTableAvailable)
+    );
+    assert_eq!(waitlist.len(), 1);
+    assert!(waitlist.seated().is_empty());
+}
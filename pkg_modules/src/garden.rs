@@ -1,5 +1,8 @@
 // modules are private by default.
 // so for be public we add 'pub' keyword before it.
 
+pub mod beds;
+pub mod calendar;
+pub mod harvest;
+pub mod plan;
 pub mod vegetables;
-
@@ -0,0 +1,36 @@
+// The raised beds in the kitchen garden. Positions in a bed are measured in
+// centimetres from its front left corner: x along the width, y along the
+// length.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BedId(pub u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bed {
+    pub name: String,
+    pub width_cm: u32,
+    pub length_cm: u32,
+}
+
+impl Bed {
+    pub fn new(name: &str, width_cm: u32, length_cm: u32) -> Bed {
+        Bed {
+            name: name.to_string(),
+            width_cm,
+            length_cm,
+        }
+    }
+
+    pub fn area_cm2(&self) -> u32 {
+        self.width_cm * self.length_cm
+    }
+
+    /// Whether a plant at `x_cm`, `y_cm` has `margin_cm` of the bed all
+    /// round it.
+    pub fn fits(&self, x_cm: u32, y_cm: u32, margin_cm: u32) -> bool {
+        x_cm >= margin_cm
+            && y_cm >= margin_cm
+            && x_cm + margin_cm <= self.width_cm
+            && y_cm + margin_cm <= self.length_cm
+    }
+}
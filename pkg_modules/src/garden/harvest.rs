@@ -0,0 +1,280 @@
+// Getting what the garden grows into the restaurant's walk-in. Each harvest
+// brought in is logged next to what the plan expected, goes into inventory
+// as ingredient stock under the species' name, and the kitchen can ask which
+// dishes on the menu the harvested produce is good for.
+
+use std::error::Error;
+use std::fmt;
+
+use restaurant::inventory::{Ingredient, Inventory, InventoryError, Unit};
+use restaurant::menu::Menu;
+
+use super::beds::BedId;
+use super::calendar::Date;
+use super::plan::Harvest;
+use super::vegetables::Yield;
+
+/// One harvest as it was actually picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarvestRecord {
+    pub date: Date,
+    pub bed: BedId,
+    /// Also the ingredient it is stocked as, in lower case.
+    pub species: String,
+    pub plants: u32,
+    pub expected: Option<Yield>,
+    pub picked: Yield,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HarvestError {
+    /// The kitchen already stocks it, counted some other way.
+    UnitMismatch {
+        ingredient: String,
+        stocked: Unit,
+        picked: Unit,
+    },
+    /// The walk-in could not take it, e.g. more than it can count.
+    Stock(InventoryError),
+}
+
+impl fmt::Display for HarvestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HarvestError::UnitMismatch {
+                ingredient,
+                stocked,
+                picked,
+            } => write!(
+                f,
+                "{} is stocked in {} but was picked in {}",
+                ingredient, stocked, picked
+            ),
+            HarvestError::Stock(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for HarvestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HarvestError::Stock(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<InventoryError> for HarvestError {
+    fn from(err: InventoryError) -> HarvestError {
+        HarvestError::Stock(err)
+    }
+}
+
+/// How many portions of a dish the walk-in can make now, for dishes that
+/// use something from the garden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub item: String,
+    pub portions: u32,
+    /// The ingredient that runs out first.
+    pub limited_by: String,
+    pub from_garden: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HarvestLog {
+    records: Vec<HarvestRecord>,
+}
+
+impl HarvestLog {
+    pub fn new() -> HarvestLog {
+        HarvestLog::default()
+    }
+
+    pub fn records(&self) -> &[HarvestRecord] {
+        &self.records
+    }
+
+    /// Logs `harvest` as picked on `picked_on` and puts it into stock.
+    /// Produce the kitchen has never stocked is added with a par of nothing,
+    /// since there is no ordering more of it.
+    ///
+    /// This stocks a bare `Inventory`, such as the walk-in a shift is about
+    /// to open with. It does not go through `Shift::receive_stock`, so
+    /// nothing brought in here shows up in a shift's event log.
+    pub fn bring_in(
+        &mut self,
+        inventory: &mut Inventory,
+        harvest: &Harvest,
+        picked_on: Date,
+        picked: Yield,
+    ) -> Result<(), HarvestError> {
+        let ingredient = harvest.species.to_lowercase();
+        match inventory.ingredient(&ingredient) {
+            Some(stocked) if stocked.unit != picked.unit => {
+                return Err(HarvestError::UnitMismatch {
+                    ingredient,
+                    stocked: stocked.unit,
+                    picked: picked.unit,
+                });
+            }
+            Some(_) => inventory.receive(&ingredient, picked.quantity)?,
+            None => {
+                inventory.stock(Ingredient::new(&ingredient, picked.unit, picked.quantity).par(0))
+            }
+        }
+
+        self.records.push(HarvestRecord {
+            date: picked_on,
+            bed: harvest.bed,
+            species: harvest.species.clone(),
+            plants: harvest.plants,
+            expected: harvest.expected,
+            picked,
+        });
+        Ok(())
+    }
+
+    /// Every dish on `menu` whose recipe uses something this log brought
+    /// in, and how many of it the walk-in has enough of everything for.
+    pub fn coverage(&self, inventory: &Inventory, menu: &Menu) -> Vec<Coverage> {
+        let harvested: Vec<String> = self
+            .records
+            .iter()
+            .map(|record| record.species.to_lowercase())
+            .collect();
+
+        menu.items()
+            .filter_map(|item| {
+                let recipe = inventory.recipes().recipe(&item.name)?;
+                let from_garden: Vec<String> = recipe
+                    .ingredients
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .filter(|name| harvested.contains(name))
+                    .collect();
+                if from_garden.is_empty() {
+                    return None;
+                }
+                let (portions, limited_by) = recipe
+                    .ingredients
+                    .iter()
+                    .map(|(name, quantity)| {
+                        let on_hand = inventory.ingredient(name).map_or(0, |i| i.on_hand);
+                        (on_hand / (*quantity).max(1), name.clone())
+                    })
+                    .min()?;
+                Some(Coverage {
+                    item: item.name.clone(),
+                    portions,
+                    limited_by,
+                    from_garden,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::beds::Bed;
+    use crate::garden::plan::PlantingPlan;
+    use crate::garden::vegetables::Species;
+    use restaurant::inventory::RecipeBook;
+
+    fn harvest(species: &str, picked: Yield) -> Harvest {
+        Harvest {
+            date: Date::new(2026, 7, 1),
+            bed: BedId(1),
+            species: species.to_string(),
+            plants: 4,
+            expected: Some(picked),
+        }
+    }
+
+    #[test]
+    fn a_new_crop_is_stocked_without_a_par_on_the_day_it_was_picked() {
+        let mut inventory = Inventory::new(RecipeBook::standard());
+        let mut log = HarvestLog::new();
+        let lettuce = harvest("Lettuce", Yield::new(1_200, Unit::Grams));
+        let picked_on = Date::new(2026, 7, 3);
+
+        log.bring_in(
+            &mut inventory,
+            &lettuce,
+            picked_on,
+            Yield::new(900, Unit::Grams),
+        )
+        .unwrap();
+        let stocked = inventory.ingredient("lettuce").unwrap();
+        assert_eq!(stocked.on_hand, 900);
+        assert_eq!(stocked.par, 0);
+        let record = &log.records()[0];
+        assert_eq!(record.date, picked_on);
+        assert_eq!(record.expected, lettuce.expected);
+        assert_eq!(record.picked, Yield::new(900, Unit::Grams));
+    }
+
+    #[test]
+    fn a_crop_counted_another_way_is_turned_away() {
+        let mut inventory = Inventory::new(RecipeBook::standard());
+        inventory.stock(Ingredient::new("tomato", Unit::Pieces, 10));
+        let mut log = HarvestLog::new();
+        let date = Date::new(2026, 7, 1);
+
+        let pieces = harvest("Tomato", Yield::new(30, Unit::Pieces));
+        log.bring_in(&mut inventory, &pieces, date, Yield::new(30, Unit::Pieces))
+            .unwrap();
+        assert_eq!(inventory.ingredient("tomato").unwrap().on_hand, 40);
+
+        let grams = harvest("Tomato", Yield::new(3_000, Unit::Grams));
+        let err = log
+            .bring_in(&mut inventory, &grams, date, Yield::new(3_000, Unit::Grams))
+            .unwrap_err();
+        assert!(matches!(err, HarvestError::UnitMismatch { .. }));
+        assert_eq!(inventory.ingredient("tomato").unwrap().on_hand, 40);
+        assert_eq!(log.records().len(), 1);
+    }
+
+    #[test]
+    fn a_harvest_expects_every_plants_yield() {
+        let mut plan = PlantingPlan::new();
+        let bed = plan.add_bed(Bed::new("Kitchen bed", 120, 240));
+        let tomato = Species::new("Tomato", 30, 75).yields(30, Unit::Pieces);
+        let planted = plan
+            .plant_row(bed, &tomato, 30, Date::new(2026, 5, 15))
+            .unwrap();
+        assert_eq!(planted, 4);
+
+        let harvests = plan.harvests_until(2026);
+        assert_eq!(harvests.len(), 1);
+        assert_eq!(harvests[0].plants, 4);
+        assert_eq!(harvests[0].expected, Some(Yield::new(120, Unit::Pieces)));
+    }
+
+    #[test]
+    fn coverage_is_the_dishes_the_harvest_goes_into() {
+        let mut inventory = Inventory::new(RecipeBook::standard());
+        let mut log = HarvestLog::new();
+        let date = Date::new(2026, 7, 1);
+        for (species, picked) in [
+            ("Lettuce", Yield::new(600, Unit::Grams)),
+            ("Tomato", Yield::new(10, Unit::Pieces)),
+        ] {
+            log.bring_in(&mut inventory, &harvest(species, picked), date, picked)
+                .unwrap();
+        }
+
+        let coverage = log.coverage(&inventory, &Menu::standard());
+        assert_eq!(
+            coverage,
+            vec![Coverage {
+                item: "Salad".to_string(),
+                portions: 5,
+                limited_by: "lettuce".to_string(),
+                from_garden: vec!["lettuce".to_string(), "tomato".to_string()],
+            }]
+        );
+    }
+}
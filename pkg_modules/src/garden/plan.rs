@@ -0,0 +1,365 @@
+// A planting plan: what goes where in which bed, and when. Every plant
+// needs half its spacing clear all round it, so two plants have to be at
+// least the average of their spacings apart, and neither may hang over the
+// edge of its bed. An annual only holds its spot until it is harvested, so
+// the same ground can be planted again later in the year; a perennial holds
+// it for good.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use super::beds::{Bed, BedId};
+use super::calendar::Date;
+use super::vegetables::{Lifecycle, Plant, Species, Yield};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Planting {
+    pub bed: BedId,
+    pub species: Species,
+    pub x_cm: u32,
+    pub y_cm: u32,
+    pub planted: Date,
+}
+
+impl Planting {
+    pub fn first_harvest(&self) -> Date {
+        let days = self.species.days_to_harvest;
+        match self.species.lifecycle {
+            Lifecycle::Annual => self.planted.add_days(days),
+            Lifecycle::Perennial { years } => self.planted.add_years(years).add_days(days),
+        }
+    }
+
+    /// Every harvest expected up to the end of `year`.
+    pub fn harvests_until(&self, year: i32) -> Vec<Date> {
+        let first = self.first_harvest();
+        match self.species.lifecycle {
+            Lifecycle::Annual if first.year() <= year => vec![first],
+            Lifecycle::Annual => Vec::new(),
+            Lifecycle::Perennial { .. } => (0..)
+                .map(|later| first.add_years(later))
+                .take_while(|d| d.year() <= year)
+                .collect(),
+        }
+    }
+
+    // Whether the two are in the ground at the same time.
+    fn overlaps(&self, other: &Planting) -> bool {
+        let clears = |a: &Planting, b: &Planting| {
+            a.species.lifecycle == Lifecycle::Annual && a.first_harvest() < b.planted
+        };
+        !clears(self, other) && !clears(other, self)
+    }
+
+    // Twice the distance the two have to be apart, to stay in whole
+    // centimetres.
+    fn twice_needed(&self, other: &Planting) -> u64 {
+        self.species.spacing_cm as u64 + other.species.spacing_cm as u64
+    }
+
+    fn twice_distance_squared(&self, other: &Planting) -> u64 {
+        let dx = self.x_cm.abs_diff(other.x_cm) as u64;
+        let dy = self.y_cm.abs_diff(other.y_cm) as u64;
+        4 * (dx * dx + dy * dy)
+    }
+}
+
+/// What comes in from one bed on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Harvest {
+    pub date: Date,
+    pub bed: BedId,
+    pub species: String,
+    pub plants: u32,
+    /// Every plant's yield added up, if the species has one.
+    pub expected: Option<Yield>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    NoSuchBed(BedId),
+    /// The plant would not have its spacing inside the bed.
+    OutsideBed {
+        bed: BedId,
+        x_cm: u32,
+        y_cm: u32,
+    },
+    TooClose {
+        species: String,
+        other: String,
+        distance_cm: u32,
+        needed_cm: u32,
+    },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::NoSuchBed(bed) => write!(f, "there is no bed {}", bed.0),
+            PlanError::OutsideBed { bed, x_cm, y_cm } => write!(
+                f,
+                "a plant at {}cm, {}cm does not have room in bed {}",
+                x_cm, y_cm, bed.0
+            ),
+            PlanError::TooClose {
+                species,
+                other,
+                distance_cm,
+                needed_cm,
+            } => write!(
+                f,
+                "{} would be {}cm from {}, which needs {}cm",
+                species, distance_cm, other, needed_cm
+            ),
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlantingPlan {
+    beds: Vec<Bed>,
+    plantings: Vec<Planting>,
+}
+
+impl PlantingPlan {
+    pub fn new() -> PlantingPlan {
+        PlantingPlan::default()
+    }
+
+    pub fn add_bed(&mut self, bed: Bed) -> BedId {
+        self.beds.push(bed);
+        BedId(self.beds.len() as u32)
+    }
+
+    pub fn bed(&self, id: BedId) -> Result<&Bed, PlanError> {
+        (id.0 as usize)
+            .checked_sub(1)
+            .and_then(|index| self.beds.get(index))
+            .ok_or(PlanError::NoSuchBed(id))
+    }
+
+    pub fn beds(&self) -> impl Iterator<Item = (BedId, &Bed)> {
+        self.beds
+            .iter()
+            .enumerate()
+            .map(|(index, bed)| (BedId(index as u32 + 1), bed))
+    }
+
+    pub fn plantings(&self) -> &[Planting] {
+        &self.plantings
+    }
+
+    /// Puts one plant in, if it has the room.
+    pub fn plant(
+        &mut self,
+        bed: BedId,
+        plant: &impl Plant,
+        x_cm: u32,
+        y_cm: u32,
+        planted: Date,
+    ) -> Result<(), PlanError> {
+        let planting = Planting {
+            bed,
+            species: plant.species(),
+            x_cm,
+            y_cm,
+            planted,
+        };
+        self.check(&planting)?;
+        self.plantings.push(planting);
+        Ok(())
+    }
+
+    /// Plants a row across the bed at `y_cm`, one every spacing, wherever
+    /// there is room. Returns how many went in.
+    pub fn plant_row(
+        &mut self,
+        bed: BedId,
+        plant: &impl Plant,
+        y_cm: u32,
+        planted: Date,
+    ) -> Result<u32, PlanError> {
+        let species = plant.species();
+        let spacing = species.spacing_cm.max(1);
+        let margin = species.spacing_cm / 2;
+        if !self.bed(bed)?.fits(margin, y_cm, margin) {
+            return Err(PlanError::OutsideBed {
+                bed,
+                x_cm: margin,
+                y_cm,
+            });
+        }
+
+        let mut count = 0;
+        let mut x_cm = margin;
+        while self.bed(bed)?.fits(x_cm, y_cm, margin) {
+            match self.plant(bed, &species, x_cm, y_cm, planted) {
+                Ok(()) => count += 1,
+                Err(PlanError::TooClose { .. }) => {}
+                Err(err) => return Err(err),
+            }
+            x_cm += spacing;
+        }
+        Ok(count)
+    }
+
+    /// Everything expected to come in up to the end of `year`, earliest
+    /// first.
+    pub fn harvests_until(&self, year: i32) -> Vec<Harvest> {
+        let mut harvests: BTreeMap<(Date, BedId, &str), Harvest> = BTreeMap::new();
+        for planting in &self.plantings {
+            let species = &planting.species;
+            for date in planting.harvests_until(year) {
+                let harvest = harvests
+                    .entry((date, planting.bed, &species.name))
+                    .or_insert_with(|| Harvest {
+                        date,
+                        bed: planting.bed,
+                        species: species.name.clone(),
+                        plants: 0,
+                        expected: species.yield_per_plant.map(|per| Yield::new(0, per.unit)),
+                    });
+                harvest.plants += 1;
+                if let (Some(expected), Some(per)) =
+                    (&mut harvest.expected, species.yield_per_plant)
+                {
+                    expected.quantity += per.quantity;
+                }
+            }
+        }
+        harvests.into_values().collect()
+    }
+
+    fn check(&self, planting: &Planting) -> Result<(), PlanError> {
+        let margin = planting.species.spacing_cm / 2;
+        if !self
+            .bed(planting.bed)?
+            .fits(planting.x_cm, planting.y_cm, margin)
+        {
+            return Err(PlanError::OutsideBed {
+                bed: planting.bed,
+                x_cm: planting.x_cm,
+                y_cm: planting.y_cm,
+            });
+        }
+
+        let crowded = self.plantings.iter().find(|other| {
+            other.bed == planting.bed
+                && other.overlaps(planting)
+                && planting.twice_distance_squared(other) < planting.twice_needed(other).pow(2)
+        });
+        match crowded {
+            Some(other) => Err(PlanError::TooClose {
+                species: planting.species.name.clone(),
+                other: other.species.name.clone(),
+                distance_cm: ((planting.twice_distance_squared(other) as f64).sqrt() / 2.0) as u32,
+                needed_cm: planting.twice_needed(other).div_ceil(2) as u32,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garden::vegetables::Asparagus;
+    use restaurant::inventory::Unit;
+
+    fn spring() -> Date {
+        Date::new(2026, 4, 10)
+    }
+
+    #[test]
+    fn plants_keep_the_average_of_their_spacings_apart() {
+        let mut plan = PlantingPlan::new();
+        let bed = plan.add_bed(Bed::new("Kitchen bed", 120, 240));
+        let tomato = Species::new("Tomato", 60, 75);
+        let lettuce = Species::new("Lettuce", 20, 45);
+        plan.plant(bed, &tomato, 30, 30, spring()).unwrap();
+
+        assert_eq!(
+            plan.plant(bed, &lettuce, 30, 69, spring()),
+            Err(PlanError::TooClose {
+                species: "Lettuce".to_string(),
+                other: "Tomato".to_string(),
+                distance_cm: 39,
+                needed_cm: 40,
+            })
+        );
+        plan.plant(bed, &lettuce, 30, 70, spring()).unwrap();
+        // another bed is other ground
+        let other = plan.add_bed(Bed::new("Herb bed", 120, 240));
+        plan.plant(other, &lettuce, 30, 40, spring()).unwrap();
+    }
+
+    #[test]
+    fn a_harvested_annual_gives_its_ground_back() {
+        let mut plan = PlantingPlan::new();
+        let bed = plan.add_bed(Bed::new("Kitchen bed", 120, 240));
+        let lettuce = Species::new("Lettuce", 20, 45);
+        plan.plant(bed, &lettuce, 30, 30, spring()).unwrap();
+
+        let harvested = spring().add_days(45);
+        assert!(plan.plant(bed, &lettuce, 30, 30, harvested).is_err());
+        plan.plant(bed, &lettuce, 30, 30, harvested.add_days(1))
+            .unwrap();
+
+        plan.plant(bed, &Asparagus {}, 90, 90, spring()).unwrap();
+        let years_later = Date::new(2040, 1, 1);
+        assert!(plan.plant(bed, &lettuce, 90, 90, years_later).is_err());
+    }
+
+    #[test]
+    fn a_bed_holds_what_fits_inside_its_edges() {
+        let mut plan = PlantingPlan::new();
+        let bed = plan.add_bed(Bed::new("Kitchen bed", 120, 240));
+        let tomato = Species::new("Tomato", 60, 75);
+
+        assert_eq!(
+            plan.plant(bed, &tomato, 20, 30, spring()),
+            Err(PlanError::OutsideBed {
+                bed,
+                x_cm: 20,
+                y_cm: 30,
+            })
+        );
+        assert_eq!(plan.plant_row(bed, &tomato, 30, spring()), Ok(2));
+        assert_eq!(plan.plant_row(bed, &tomato, 90, spring()), Ok(2));
+        assert!(plan.plant_row(bed, &tomato, 220, spring()).is_err());
+        // a row over one already there only fills the gaps
+        assert_eq!(plan.plant_row(bed, &tomato, 60, spring()), Ok(0));
+        assert_eq!(plan.plantings().len(), 4);
+        assert_eq!(
+            plan.plant(BedId(9), &tomato, 30, 30, spring()),
+            Err(PlanError::NoSuchBed(BedId(9)))
+        );
+    }
+
+    #[test]
+    fn perennials_are_first_picked_years_after_planting() {
+        let mut plan = PlantingPlan::new();
+        let bed = plan.add_bed(Bed::new("Perennials", 120, 300));
+        plan.plant(bed, &Asparagus {}, 30, 30, spring()).unwrap();
+        let lettuce = Species::new("Lettuce", 20, 45).yields(300, Unit::Grams);
+        plan.plant(bed, &lettuce, 90, 30, spring()).unwrap();
+
+        let dates: Vec<(Date, String)> = plan
+            .harvests_until(2029)
+            .into_iter()
+            .map(|harvest| (harvest.date, harvest.species))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                (Date::new(2026, 5, 25), "Lettuce".to_string()),
+                (Date::new(2028, 5, 1), "Asparagus".to_string()),
+                (Date::new(2029, 5, 1), "Asparagus".to_string()),
+            ]
+        );
+        assert_eq!(plan.harvests_until(2027).len(), 1);
+    }
+}
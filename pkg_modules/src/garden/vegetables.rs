@@ -1,5 +1,98 @@
 // items in modules are private by default.
 // so for be public we add 'pub' keyword before it.
 
+use std::fmt;
+
+use restaurant::inventory::Unit;
+
+/// Whether a plant is gone after one harvest or comes back every year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lifecycle {
+    Annual,
+    /// Left alone for `years` after planting before the first harvest, then
+    /// picked every year after that.
+    Perennial {
+        years: u32,
+    },
+}
+
+/// What the planner needs to know about something we grow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Species {
+    pub name: String,
+    /// How far apart plants go, centre to centre.
+    pub spacing_cm: u32,
+    /// From planting for annuals. For perennials, from the day they were
+    /// planted, in the first year they are harvested; they are picked on
+    /// the same day every year after that.
+    pub days_to_harvest: u32,
+    pub lifecycle: Lifecycle,
+    /// What one plant is expected to give each harvest, if we know.
+    pub yield_per_plant: Option<Yield>,
+}
+
+/// An amount of produce, in the units the kitchen stocks it in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Yield {
+    pub quantity: u32,
+    pub unit: Unit,
+}
+
+impl Yield {
+    pub fn new(quantity: u32, unit: Unit) -> Yield {
+        Yield { quantity, unit }
+    }
+}
+
+impl fmt::Display for Yield {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.quantity, self.unit)
+    }
+}
+
+impl Species {
+    /// An annual, which is most of the garden.
+    pub fn new(name: &str, spacing_cm: u32, days_to_harvest: u32) -> Species {
+        Species {
+            name: name.to_string(),
+            spacing_cm,
+            days_to_harvest,
+            lifecycle: Lifecycle::Annual,
+            yield_per_plant: None,
+        }
+    }
+
+    pub fn perennial(mut self, years: u32) -> Species {
+        self.lifecycle = Lifecycle::Perennial { years };
+        self
+    }
+
+    pub fn yields(mut self, quantity: u32, unit: Unit) -> Species {
+        self.yield_per_plant = Some(Yield::new(quantity, unit));
+        self
+    }
+}
+
+/// Anything that can go in a bed.
+pub trait Plant {
+    fn species(&self) -> Species;
+}
+
+impl Plant for Species {
+    fn species(&self) -> Species {
+        self.clone()
+    }
+}
+
+// Crowns go in in spring and are left to build up for two years; after that
+// the spears are cut every spring for the next twenty or so.
 #[derive(Debug)]
 pub struct Asparagus {}
+
+impl Plant for Asparagus {
+    fn species(&self) -> Species {
+        Species::new("Asparagus", 45, 21)
+            .perennial(2)
+            .yields(250, Unit::Grams)
+    }
+}
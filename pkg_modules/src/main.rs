@@ -15,8 +15,16 @@ the parent module in these places:
     In the file src/garden/vegetables/mod.rs
 */
 
-use garden::vegetables::Asparagus;
-use std::{cmp::Ordering, io};
+use garden::beds::Bed;
+use garden::calendar::Date;
+use garden::harvest::HarvestLog;
+use garden::plan::PlantingPlan;
+use garden::vegetables::{Asparagus, Species};
+use restaurant::inventory::{Inventory, RecipeBook, Unit};
+use restaurant::menu::Menu;
+
+// nested paths bring in several items from one place:
+// use std::{cmp::Ordering, io};
 
 // use std::io;
 // use std::io::Write;
@@ -24,15 +32,15 @@ use std::{cmp::Ordering, io};
 // use std::io::{self, Write}
 
 // import all public items in collections
-use std::collections::*;
+// use std::collections::*;
 
-/* 
+/*
     To show Rust where to find an item in a module tree,
     we use a path in the same way we use a path when navigating a filesystem.
     To call a function, we need to know its path.
 
     A path can take two forms:
-        
+
         An absolute path is the full path starting from a crate root;
             for code from an external crate, the absolute path begins with the crate name,
             and for code from the current crate, it starts with the literal crate.
@@ -43,5 +51,68 @@ use std::collections::*;
 mod garden;
 
 fn main() {
-    let a = Asparagus {};
+    let mut plan = PlantingPlan::new();
+    let perennials = plan.add_bed(Bed::new("Perennials", 120, 300));
+    let kitchen = plan.add_bed(Bed::new("Kitchen bed", 120, 240));
+
+    // the asparagus goes in once and stays
+    let spring = Date::new(2026, 4, 10);
+    for y_cm in [30, 90, 150] {
+        plan.plant_row(perennials, &Asparagus {}, y_cm, spring)
+            .expect("the rows are inside the bed");
+    }
+
+    // the kitchen bed gets tomatoes, then lettuce where they were
+    let tomato = Species::new("Tomato", 60, 75).yields(30, Unit::Pieces);
+    let lettuce = Species::new("Lettuce", 25, 45).yields(300, Unit::Grams);
+    plan.plant_row(kitchen, &tomato, 30, Date::new(2026, 5, 15))
+        .expect("the row is inside the bed");
+    plan.plant_row(kitchen, &lettuce, 20, Date::new(2026, 8, 1))
+        .expect("the row is inside the bed");
+    plan.plant_row(kitchen, &lettuce, 80, Date::new(2026, 5, 15))
+        .expect("the row is inside the bed");
+
+    // too close to the tomatoes, which are still growing
+    if let Err(err) = plan.plant(kitchen, &lettuce, 60, 50, Date::new(2026, 6, 1)) {
+        println!("not planted: {}", err);
+    }
+
+    for (id, bed) in plan.beds() {
+        let plants = plan.plantings().iter().filter(|p| p.bed == id).count();
+        println!(
+            "{}: {} plants in {:.2} m2",
+            bed.name,
+            plants,
+            bed.area_cm2() as f64 / 10_000.0
+        );
+    }
+    for harvest in plan.harvests_until(2029) {
+        let bed = plan.bed(harvest.bed).expect("harvests are from our beds");
+        println!(
+            "{}  {} x {} from {}",
+            harvest.date, harvest.plants, harvest.species, bed.name
+        );
+    }
+
+    let mut inventory = Inventory::new(RecipeBook::standard());
+    let mut log = HarvestLog::new();
+    // this year's crops go to the kitchen as they come in
+    for harvest in plan.harvests_until(2026) {
+        let picked = harvest.expected.expect("everything we grow has a yield");
+        if let Err(err) = log.bring_in(&mut inventory, &harvest, harvest.date, picked) {
+            println!("not brought in: {}", err);
+        }
+    }
+    for record in log.records() {
+        println!(
+            "{}  picked {} of {}",
+            record.date, record.picked, record.species
+        );
+    }
+    for coverage in log.coverage(&inventory, &Menu::standard()) {
+        println!(
+            "{}: {} portions, until the {} runs out",
+            coverage.item, coverage.portions, coverage.limited_by
+        );
+    }
 }